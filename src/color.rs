@@ -8,6 +8,10 @@ use embedded_graphics_core::pixelcolor::BinaryColor;
 #[cfg(feature = "graphics")]
 use embedded_graphics_core::pixelcolor::PixelColor;
 
+#[cfg(feature = "graphics")]
+use crate::palette::PaletteColor;
+use crate::traits::{CleanStep, DefaultCleanSequence};
+
 /// When trying to parse u8 to one of the color types
 #[derive(Debug, PartialEq, Eq)]
 pub struct OutOfColorRangeParseError(u8);
@@ -68,6 +72,38 @@ pub enum OctColor {
     HiZ = 0x07,
 }
 
+/// For the 6 color (Spectra 6) Displays
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SixColor {
+    /// Black Color
+    Black = 0x00,
+    /// White Color
+    #[default]
+    White = 0x01,
+    /// Yellow Color
+    Yellow = 0x02,
+    /// Red Color
+    Red = 0x03,
+    /// Blue Color
+    Blue = 0x05,
+    /// Green Color
+    Green = 0x06,
+}
+
+/// For the 4 color (black, white, yellow, red) Displays, with 2 bits per pixel
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum QuadColor {
+    /// Black Color
+    Black = 0b00,
+    /// White Color
+    #[default]
+    White = 0b01,
+    /// Yellow Color
+    Yellow = 0b10,
+    /// Red Color
+    Red = 0b11,
+}
+
 /// Color trait for use in `Display`s
 pub trait ColorType {
     /// Number of bit used to represent this color type in a single buffer.
@@ -87,8 +123,16 @@ pub trait ColorType {
     /// Return values are :
     /// * .0 is the mask used to exclude this pixel from the byte (eg: 0x7F in BiColor)
     /// * .1 are the bits used to set the color in the byte (eg: 0x80 in BiColor)
-    ///      this is u16 because we set 2 bytes in case of split buffer
+    ///   this is u16 because we set 2 bytes in case of split buffer
     fn bitmask(&self, bwrbit: bool, pos: u32) -> (u8, u16);
+
+    /// RGB value of the raw bits of a pixel, `None` if they aren't a color of this type
+    ///
+    /// Only needed for the color types with several bits per pixel, which can't be told from
+    /// their bit count (e.g. to render a buffer as an image).
+    fn raw_rgb(_raw: u16) -> Option<(u8, u8, u8)> {
+        None
+    }
 }
 
 impl ColorType for Color {
@@ -131,6 +175,81 @@ impl ColorType for OctColor {
         let bits = self.get_nibble() as u16;
         (mask, if pos % 2 == 1 { bits } else { bits << 4 })
     }
+
+    fn raw_rgb(raw: u16) -> Option<(u8, u8, u8)> {
+        OctColor::from_nibble(raw as u8).ok().map(OctColor::rgb)
+    }
+}
+
+impl ColorType for QuadColor {
+    const BITS_PER_PIXEL_PER_BUFFER: usize = 2;
+    const BUFFER_COUNT: usize = 1;
+    fn bitmask(&self, _bwrbit: bool, pos: u32) -> (u8, u16) {
+        let shift = 6 - (pos % 4) * 2;
+        (!(0b11 << shift), (self.get_bits() as u16) << shift)
+    }
+
+    fn raw_rgb(raw: u16) -> Option<(u8, u8, u8)> {
+        Some(QuadColor::from_bits(raw as u8).rgb())
+    }
+}
+
+impl ColorType for SixColor {
+    const BITS_PER_PIXEL_PER_BUFFER: usize = 4;
+    const BUFFER_COUNT: usize = 1;
+    fn bitmask(&self, _bwrbit: bool, pos: u32) -> (u8, u16) {
+        let mask = !(0xF0 >> ((pos % 2) * 4));
+        let bits = self.get_nibble() as u16;
+        (mask, if pos % 2 == 1 { bits } else { bits << 4 })
+    }
+
+    fn raw_rgb(raw: u16) -> Option<(u8, u8, u8)> {
+        SixColor::from_nibble(raw as u8).ok().map(SixColor::rgb)
+    }
+}
+
+impl DefaultCleanSequence for Color {
+    const CLEAN_SEQUENCE: &'static [CleanStep<Self>] = &[
+        CleanStep::Inverted,
+        CleanStep::Fill(Color::Black),
+        CleanStep::Fill(Color::White),
+        CleanStep::Image,
+    ];
+}
+
+/// Without inverted image, the chromatic plane would be inverted as well
+impl DefaultCleanSequence for TriColor {
+    const CLEAN_SEQUENCE: &'static [CleanStep<Self>] = &[
+        CleanStep::Fill(TriColor::Black),
+        CleanStep::Fill(TriColor::White),
+        CleanStep::Image,
+    ];
+}
+
+/// The clean color drives the pigments of the seven color panels to a neutral state
+impl DefaultCleanSequence for OctColor {
+    const CLEAN_SEQUENCE: &'static [CleanStep<Self>] = &[
+        CleanStep::Fill(OctColor::Black),
+        CleanStep::Fill(OctColor::White),
+        CleanStep::Fill(OctColor::HiZ),
+        CleanStep::Image,
+    ];
+}
+
+impl DefaultCleanSequence for QuadColor {
+    const CLEAN_SEQUENCE: &'static [CleanStep<Self>] = &[
+        CleanStep::Fill(QuadColor::Black),
+        CleanStep::Fill(QuadColor::White),
+        CleanStep::Image,
+    ];
+}
+
+impl DefaultCleanSequence for SixColor {
+    const CLEAN_SEQUENCE: &'static [CleanStep<Self>] = &[
+        CleanStep::Fill(SixColor::Black),
+        CleanStep::Fill(SixColor::White),
+        CleanStep::Image,
+    ];
 }
 
 #[cfg(feature = "graphics")]
@@ -170,27 +289,36 @@ impl From<embedded_graphics_core::pixelcolor::Rgb888> for OctColor {
             return *found;
         }
 
-        // This is not ideal but just pick the nearest color
-        *colors
-            .iter()
-            .map(|c| (c, c.rgb()))
-            .map(|(c, (r, g, b))| {
-                let dist = (i32::from(r) - i32::from(p.r())).pow(2)
-                    + (i32::from(g) - i32::from(p.g())).pow(2)
-                    + (i32::from(b) - i32::from(p.b())).pow(2);
-                (c, dist)
-            })
-            .min_by_key(|(_c, dist)| *dist)
-            .map(|(c, _)| c)
-            .unwrap_or(&OctColor::White)
+        // otherwise pick the color which looks the most alike on the panel
+        OctColor::PALETTE.nearest((p.r(), p.g(), p.b()))
     }
 }
 
 #[cfg(feature = "graphics")]
-impl From<embedded_graphics_core::pixelcolor::raw::RawU4> for OctColor {
-    fn from(b: embedded_graphics_core::pixelcolor::raw::RawU4) -> Self {
+impl TryFrom<embedded_graphics_core::pixelcolor::raw::RawU4> for OctColor {
+    type Error = OutOfColorRangeParseError;
+
+    fn try_from(b: embedded_graphics_core::pixelcolor::raw::RawU4) -> Result<Self, Self::Error> {
         use embedded_graphics_core::prelude::RawData;
-        OctColor::from_nibble(b.into_inner()).unwrap()
+        OctColor::try_from(b.into_inner())
+    }
+}
+
+impl TryFrom<u8> for OctColor {
+    type Error = OutOfColorRangeParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(OctColor::Black),
+            0x01 => Ok(OctColor::White),
+            0x02 => Ok(OctColor::Green),
+            0x03 => Ok(OctColor::Blue),
+            0x04 => Ok(OctColor::Red),
+            0x05 => Ok(OctColor::Yellow),
+            0x06 => Ok(OctColor::Orange),
+            0x07 => Ok(OctColor::HiZ),
+            e => Err(OutOfColorRangeParseError(e)),
+        }
     }
 }
 
@@ -201,32 +329,22 @@ impl PixelColor for OctColor {
 
 impl OctColor {
     /// Gets the Nibble representation of the Color as needed by the display
-    pub fn get_nibble(self) -> u8 {
+    pub const fn get_nibble(self) -> u8 {
         self as u8
     }
     /// Converts two colors into a single byte for the Display
-    pub fn colors_byte(a: OctColor, b: OctColor) -> u8 {
+    pub const fn colors_byte(a: OctColor, b: OctColor) -> u8 {
         a.get_nibble() << 4 | b.get_nibble()
     }
 
     ///Take the nibble (lower 4 bits) and convert to an OctColor if possible
     pub fn from_nibble(nibble: u8) -> Result<OctColor, OutOfColorRangeParseError> {
-        match nibble & 0xf {
-            0x00 => Ok(OctColor::Black),
-            0x01 => Ok(OctColor::White),
-            0x02 => Ok(OctColor::Green),
-            0x03 => Ok(OctColor::Blue),
-            0x04 => Ok(OctColor::Red),
-            0x05 => Ok(OctColor::Yellow),
-            0x06 => Ok(OctColor::Orange),
-            0x07 => Ok(OctColor::HiZ),
-            e => Err(OutOfColorRangeParseError(e)),
-        }
+        OctColor::try_from(nibble & 0xf)
     }
     ///Split the nibbles of a single byte and convert both to an OctColor if possible
     pub fn split_byte(byte: u8) -> Result<(OctColor, OctColor), OutOfColorRangeParseError> {
-        let low = OctColor::from_nibble(byte & 0xf)?;
-        let high = OctColor::from_nibble((byte >> 4) & 0xf)?;
+        let low = OctColor::from_nibble(byte)?;
+        let high = OctColor::from_nibble(byte >> 4)?;
         Ok((high, low))
     }
     /// Converts to limited range of RGB values.
@@ -243,11 +361,221 @@ impl OctColor {
         }
     }
 }
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for QuadColor {
+    fn from(b: BinaryColor) -> QuadColor {
+        match b {
+            BinaryColor::On => QuadColor::Black,
+            BinaryColor::Off => QuadColor::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<QuadColor> for embedded_graphics_core::pixelcolor::Rgb888 {
+    fn from(b: QuadColor) -> Self {
+        let (r, g, b) = b.rgb();
+        Self::new(r, g, b)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Rgb888> for QuadColor {
+    fn from(p: embedded_graphics_core::pixelcolor::Rgb888) -> QuadColor {
+        use embedded_graphics_core::prelude::RgbColor;
+        let rgb = (p.r(), p.g(), p.b());
+        // the colors themselves map back, anything else to the color which looks the most alike
+        QuadColor::ALL
+            .iter()
+            .copied()
+            .find(|c| c.rgb() == rgb)
+            .unwrap_or_else(|| QuadColor::PALETTE.nearest(rgb))
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::raw::RawU2> for QuadColor {
+    fn from(b: embedded_graphics_core::pixelcolor::raw::RawU2) -> Self {
+        use embedded_graphics_core::prelude::RawData;
+        QuadColor::from_bits(b.into_inner())
+    }
+}
+
+impl TryFrom<u8> for QuadColor {
+    type Error = OutOfColorRangeParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0b00..=0b11 => Ok(QuadColor::from_bits(value)),
+            e => Err(OutOfColorRangeParseError(e)),
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelColor for QuadColor {
+    type Raw = embedded_graphics_core::pixelcolor::raw::RawU2;
+}
+
+impl QuadColor {
+    /// All colors, in the order of their bits
+    pub const ALL: [QuadColor; 4] = [
+        QuadColor::Black,
+        QuadColor::White,
+        QuadColor::Yellow,
+        QuadColor::Red,
+    ];
+
+    /// Gets the 2 bit representation of the Color as needed by the display
+    pub const fn get_bits(self) -> u8 {
+        self as u8
+    }
+    /// Converts four colors into a single byte for the Display, the first color is the leftmost
+    /// pixel
+    pub const fn colors_byte(a: QuadColor, b: QuadColor, c: QuadColor, d: QuadColor) -> u8 {
+        a.get_bits() << 6 | b.get_bits() << 4 | c.get_bits() << 2 | d.get_bits()
+    }
+
+    /// Take the lower 2 bits and convert them to a QuadColor
+    pub const fn from_bits(bits: u8) -> QuadColor {
+        match bits & 0b11 {
+            0b00 => QuadColor::Black,
+            0b01 => QuadColor::White,
+            0b10 => QuadColor::Yellow,
+            _ => QuadColor::Red,
+        }
+    }
+    /// Split a single byte into its four colors, the first color is the leftmost pixel
+    pub const fn split_byte(byte: u8) -> [QuadColor; 4] {
+        [
+            QuadColor::from_bits(byte >> 6),
+            QuadColor::from_bits(byte >> 4),
+            QuadColor::from_bits(byte >> 2),
+            QuadColor::from_bits(byte),
+        ]
+    }
+    /// Converts to limited range of RGB values.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            QuadColor::White => (0xff, 0xff, 0xff),
+            QuadColor::Black => (0x00, 0x00, 0x00),
+            QuadColor::Yellow => (0xff, 0xff, 0x00),
+            QuadColor::Red => (0xff, 0x00, 0x00),
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for SixColor {
+    fn from(b: BinaryColor) -> SixColor {
+        match b {
+            BinaryColor::On => SixColor::Black,
+            BinaryColor::Off => SixColor::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<SixColor> for embedded_graphics_core::pixelcolor::Rgb888 {
+    fn from(b: SixColor) -> Self {
+        let (r, g, b) = b.rgb();
+        Self::new(r, g, b)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Rgb888> for SixColor {
+    fn from(p: embedded_graphics_core::pixelcolor::Rgb888) -> SixColor {
+        use embedded_graphics_core::prelude::RgbColor;
+        let rgb = (p.r(), p.g(), p.b());
+        // the colors themselves map back, anything else to the color which looks the most alike
+        SixColor::ALL
+            .iter()
+            .copied()
+            .find(|c| c.rgb() == rgb)
+            .unwrap_or_else(|| SixColor::PALETTE.nearest(rgb))
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl TryFrom<embedded_graphics_core::pixelcolor::raw::RawU4> for SixColor {
+    type Error = OutOfColorRangeParseError;
+
+    fn try_from(b: embedded_graphics_core::pixelcolor::raw::RawU4) -> Result<Self, Self::Error> {
+        use embedded_graphics_core::prelude::RawData;
+        SixColor::try_from(b.into_inner())
+    }
+}
+
+impl TryFrom<u8> for SixColor {
+    type Error = OutOfColorRangeParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(SixColor::Black),
+            0x01 => Ok(SixColor::White),
+            0x02 => Ok(SixColor::Yellow),
+            0x03 => Ok(SixColor::Red),
+            0x05 => Ok(SixColor::Blue),
+            0x06 => Ok(SixColor::Green),
+            e => Err(OutOfColorRangeParseError(e)),
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelColor for SixColor {
+    type Raw = embedded_graphics_core::pixelcolor::raw::RawU4;
+}
+
+impl SixColor {
+    /// All colors, in the order of their nibbles
+    pub const ALL: [SixColor; 6] = [
+        SixColor::Black,
+        SixColor::White,
+        SixColor::Yellow,
+        SixColor::Red,
+        SixColor::Blue,
+        SixColor::Green,
+    ];
+
+    /// Gets the Nibble representation of the Color as needed by the display
+    pub const fn get_nibble(self) -> u8 {
+        self as u8
+    }
+    /// Converts two colors into a single byte for the Display
+    pub const fn colors_byte(a: SixColor, b: SixColor) -> u8 {
+        a.get_nibble() << 4 | b.get_nibble()
+    }
+
+    ///Take the nibble (lower 4 bits) and convert to a SixColor if possible
+    pub fn from_nibble(nibble: u8) -> Result<SixColor, OutOfColorRangeParseError> {
+        SixColor::try_from(nibble & 0xf)
+    }
+    ///Split the nibbles of a single byte and convert both to a SixColor if possible
+    pub fn split_byte(byte: u8) -> Result<(SixColor, SixColor), OutOfColorRangeParseError> {
+        let low = SixColor::from_nibble(byte)?;
+        let high = SixColor::from_nibble(byte >> 4)?;
+        Ok((high, low))
+    }
+    /// Converts to limited range of RGB values.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            SixColor::White => (0xff, 0xff, 0xff),
+            SixColor::Black => (0x00, 0x00, 0x00),
+            SixColor::Yellow => (0xff, 0xff, 0x00),
+            SixColor::Red => (0xff, 0x00, 0x00),
+            SixColor::Blue => (0x00, 0x00, 0xff),
+            SixColor::Green => (0x00, 0xff, 0x00),
+        }
+    }
+}
+
 //TODO: Rename get_bit_value to bit() and get_byte_value to byte() ?
 
 impl Color {
     /// Get the color encoding of the color for one bit
-    pub fn get_bit_value(self) -> u8 {
+    pub const fn get_bit_value(self) -> u8 {
         match self {
             Color::White => 1u8,
             Color::Black => 0u8,
@@ -255,29 +583,17 @@ impl Color {
     }
 
     /// Gets a full byte of black or white pixels
-    pub fn get_byte_value(self) -> u8 {
+    pub const fn get_byte_value(self) -> u8 {
         match self {
             Color::White => 0xff,
             Color::Black => 0x00,
         }
     }
 
-    /// Parses from u8 to Color
-    fn from_u8(val: u8) -> Self {
-        match val {
-            0 => Color::Black,
-            1 => Color::White,
-            e => panic!(
-                "DisplayColor only parses 0 and 1 (Black and White) and not `{}`",
-                e
-            ),
-        }
-    }
-
     /// Returns the inverse of the given color.
     ///
     /// Black returns White and White returns Black
-    pub fn inverse(self) -> Color {
+    pub const fn inverse(self) -> Color {
         match self {
             Color::White => Color::Black,
             Color::Black => Color::White,
@@ -285,9 +601,16 @@ impl Color {
     }
 }
 
-impl From<u8> for Color {
-    fn from(value: u8) -> Self {
-        Color::from_u8(value)
+impl TryFrom<u8> for Color {
+    type Error = OutOfColorRangeParseError;
+
+    /// Parses the bit value of a color, 0 is Black and 1 is White
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Color::Black),
+            1 => Ok(Color::White),
+            e => Err(OutOfColorRangeParseError(e)),
+        }
     }
 }
 
@@ -416,8 +739,19 @@ impl From<Color> for embedded_graphics_core::pixelcolor::Rgb555 {
 }
 
 impl TriColor {
+    /// Red chromatic ink, as `0xRRGGBB` for the `CHROMATIC` parameter of
+    /// [`Display`](crate::graphics::Display)
+    pub const RED_INK: u32 = 0xFF0000;
+    /// Yellow chromatic ink, e.g. of the (C) variants of the tricolor displays
+    pub const YELLOW_INK: u32 = 0xFFFF00;
+
+    /// Splits an ink given as `0xRRGGBB` into its sRGB values
+    pub const fn ink_rgb(ink: u32) -> (u8, u8, u8) {
+        ((ink >> 16) as u8, (ink >> 8) as u8, ink as u8)
+    }
+
     /// Get the color encoding of the color for one bit
-    pub fn get_bit_value(self) -> u8 {
+    pub const fn get_bit_value(self) -> u8 {
         match self {
             TriColor::White => 1u8,
             TriColor::Black | TriColor::Chromatic => 0u8,
@@ -425,7 +759,7 @@ impl TriColor {
     }
 
     /// Gets a full byte of black or white pixels
-    pub fn get_byte_value(self) -> u8 {
+    pub const fn get_byte_value(self) -> u8 {
         match self {
             TriColor::White => 0xff,
             TriColor::Black | TriColor::Chromatic => 0x00,
@@ -433,6 +767,21 @@ impl TriColor {
     }
 }
 
+impl TryFrom<u8> for TriColor {
+    type Error = OutOfColorRangeParseError;
+
+    /// Parses the same values as the raw data of the color, 0 is White, 1 is Black and 2 is
+    /// Chromatic
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0b00 => Ok(TriColor::White),
+            0b01 => Ok(TriColor::Black),
+            0b10 => Ok(TriColor::Chromatic),
+            e => Err(OutOfColorRangeParseError(e)),
+        }
+    }
+}
+
 #[cfg(feature = "graphics")]
 impl From<embedded_graphics_core::pixelcolor::raw::RawU2> for TriColor {
     fn from(b: embedded_graphics_core::pixelcolor::raw::RawU2) -> Self {
@@ -464,14 +813,43 @@ impl From<BinaryColor> for TriColor {
 #[cfg(feature = "graphics")]
 impl From<embedded_graphics_core::pixelcolor::Rgb888> for TriColor {
     fn from(rgb: embedded_graphics_core::pixelcolor::Rgb888) -> Self {
+        TriColor::from_rgb_with_ink(rgb, TriColor::RED_INK)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl TriColor {
+    /// Color of a display with the chromatic `ink` (as `0xRRGGBB`) which looks the most alike
+    /// `rgb`
+    ///
+    /// `From<Rgb888>` is the same with the red ink, the display types convert with their own ink
+    /// in `Display::color_from_rgb`.
+    pub fn from_rgb_with_ink(rgb: embedded_graphics_core::pixelcolor::Rgb888, ink: u32) -> Self {
+        if ink == TriColor::RED_INK {
+            TriColor::from_rgb_in(rgb, ink, &TriColor::PALETTE)
+        } else {
+            let entries = TriColor::palette_entries(ink);
+            TriColor::from_rgb_in(rgb, ink, &crate::palette::Palette::new(&entries))
+        }
+    }
+
+    /// The ideal colors map back, anything else to the nearest color of `palette`
+    pub(crate) fn from_rgb_in(
+        rgb: embedded_graphics_core::pixelcolor::Rgb888,
+        ink: u32,
+        palette: &crate::palette::Palette<'_, TriColor>,
+    ) -> Self {
         use embedded_graphics_core::pixelcolor::RgbColor;
-        if rgb == RgbColor::BLACK {
+        let rgb = (rgb.r(), rgb.g(), rgb.b());
+        if rgb == (0, 0, 0) {
             TriColor::Black
-        } else if rgb == RgbColor::WHITE {
+        } else if rgb == (0xFF, 0xFF, 0xFF) {
             TriColor::White
-        } else {
-            // there is no good approximation here since we don't know which color is 'chromatic'
+        } else if rgb == TriColor::ink_rgb(ink) {
             TriColor::Chromatic
+        } else {
+            // pick the color which looks the most alike on the panel
+            palette.nearest(rgb)
         }
     }
 }
@@ -482,7 +860,7 @@ impl From<TriColor> for embedded_graphics_core::pixelcolor::Rgb888 {
         match tri_color {
             TriColor::Black => embedded_graphics_core::pixelcolor::Rgb888::BLACK,
             TriColor::White => embedded_graphics_core::pixelcolor::Rgb888::WHITE,
-            // assume chromatic is red
+            // assume chromatic is red, `Display::CHROMATIC_RGB` is the ink of a display
             TriColor::Chromatic => embedded_graphics_core::pixelcolor::Rgb888::new(255, 0, 0),
         }
     }
@@ -494,30 +872,83 @@ mod tests {
 
     #[test]
     fn from_u8() {
-        assert_eq!(Color::Black, Color::from(0u8));
-        assert_eq!(Color::White, Color::from(1u8));
+        assert_eq!(Color::try_from(0u8), Ok(Color::Black));
+        assert_eq!(Color::try_from(1u8), Ok(Color::White));
     }
 
-    // test all values aside from 0 and 1 which all should panic
+    // test all values aside from 0 and 1 which all should fail
     #[test]
-    fn from_u8_panic() {
+    fn from_u8_out_of_range() {
         for val in 2..=u8::MAX {
-            extern crate std;
-            let result = std::panic::catch_unwind(|| Color::from(val));
-            assert!(result.is_err());
+            assert_eq!(Color::try_from(val), Err(OutOfColorRangeParseError(val)));
         }
     }
 
     #[test]
     fn u8_conversion_black() {
-        assert_eq!(Color::from(Color::Black.get_bit_value()), Color::Black);
-        assert_eq!(Color::from(0u8).get_bit_value(), 0u8);
+        assert_eq!(
+            Color::try_from(Color::Black.get_bit_value()),
+            Ok(Color::Black)
+        );
+        assert_eq!(Color::try_from(0u8).unwrap().get_bit_value(), 0u8);
     }
 
     #[test]
     fn u8_conversion_white() {
-        assert_eq!(Color::from(Color::White.get_bit_value()), Color::White);
-        assert_eq!(Color::from(1u8).get_bit_value(), 1u8);
+        assert_eq!(
+            Color::try_from(Color::White.get_bit_value()),
+            Ok(Color::White)
+        );
+        assert_eq!(Color::try_from(1u8).unwrap().get_bit_value(), 1u8);
+    }
+
+    #[test]
+    fn try_from_u8_all_colors() {
+        for val in 0..=u8::MAX {
+            let tri = TriColor::try_from(val);
+            assert_eq!(tri.is_ok(), val < 3, "{}", val);
+            let quad = QuadColor::try_from(val);
+            assert_eq!(quad.ok(), QuadColor::ALL.get(val as usize).copied());
+            let oct = OctColor::try_from(val);
+            assert_eq!(oct.is_ok(), val < 8, "{}", val);
+            if let Ok(oct) = oct {
+                assert_eq!(oct.get_nibble(), val);
+            }
+            let six = SixColor::try_from(val);
+            assert_eq!(
+                six.ok(),
+                SixColor::ALL
+                    .iter()
+                    .copied()
+                    .find(|c| c.get_nibble() == val)
+            );
+        }
+        // the parsers only look at the value itself, the nibble parsers at its lower 4 bits
+        assert_eq!(
+            OctColor::try_from(0x12),
+            Err(OutOfColorRangeParseError(0x12))
+        );
+        assert_eq!(OctColor::from_nibble(0x12), Ok(OctColor::Green));
+        assert_eq!(
+            SixColor::split_byte(0x47),
+            Err(OutOfColorRangeParseError(0x07))
+        );
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn try_from_raw() {
+        use embedded_graphics_core::pixelcolor::raw::RawU4;
+        assert_eq!(OctColor::try_from(RawU4::new(0x06)), Ok(OctColor::Orange));
+        assert_eq!(
+            OctColor::try_from(RawU4::new(0x08)),
+            Err(OutOfColorRangeParseError(0x08))
+        );
+        assert_eq!(SixColor::try_from(RawU4::new(0x05)), Ok(SixColor::Blue));
+        assert_eq!(
+            SixColor::try_from(RawU4::new(0x04)),
+            Err(OutOfColorRangeParseError(0x04))
+        );
     }
 
     #[test]
@@ -530,6 +961,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_quad() {
+        let colors = [
+            QuadColor::Red,
+            QuadColor::Black,
+            QuadColor::White,
+            QuadColor::Yellow,
+        ];
+        let byte = QuadColor::colors_byte(colors[0], colors[1], colors[2], colors[3]);
+        assert_eq!(byte, 0b11_00_01_10);
+        assert_eq!(QuadColor::split_byte(byte), colors);
+        assert_eq!(QuadColor::Red.bitmask(false, 0), (0b00111111, 0b11000000));
+        assert_eq!(
+            QuadColor::Yellow.bitmask(false, 6),
+            (0b11110011, 0b00001000)
+        );
+    }
+
+    #[test]
+    fn test_six() {
+        let left = SixColor::Blue;
+        let right = SixColor::Yellow;
+        assert_eq!(SixColor::colors_byte(left, right), 0x52);
+        assert_eq!(
+            SixColor::split_byte(SixColor::colors_byte(left, right)),
+            Ok((left, right))
+        );
+        // 0x04 is not a color of the Spectra palette
+        assert_eq!(
+            SixColor::from_nibble(0x04),
+            Err(OutOfColorRangeParseError(0x04))
+        );
+        assert_eq!(SixColor::Green.bitmask(false, 1), (0xF0, 0x06));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn six_color_rgb() {
+        use embedded_graphics_core::pixelcolor::Rgb888;
+        for color in SixColor::ALL {
+            assert_eq!(SixColor::from(Rgb888::from(color)), color);
+        }
+        assert_eq!(SixColor::from(Rgb888::new(0xF0, 0x30, 0x20)), SixColor::Red);
+        assert_eq!(
+            SixColor::from(Rgb888::new(0xFF, 0x80, 0x00)),
+            SixColor::Yellow
+        );
+    }
+
     #[test]
     fn test_tricolor_bitmask() {
         assert_eq!(
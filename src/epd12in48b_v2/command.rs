@@ -23,6 +23,7 @@ pub enum Command {
     LutKK_LutK = 0x24,
     LutBD = 0x25,
     KWLUTOption = 0x2B,
+    TemperatureSensorCalibration = 0x40,
     VcomAndDataIntervalSetting = 0x50,
     TconSetting = 0x60,
     TconResolution = 0x61,
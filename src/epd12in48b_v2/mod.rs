@@ -131,6 +131,7 @@ where
     peris: Peripherals<INPUT, OUTPUT, SPI>,
     delay: DELAY,
     control_state: CS,
+    temperature: Option<i8>,
 }
 
 impl<INPUT, OUTPUT, SPI, DELAY> EpdDriver<INPUT, OUTPUT, SPI, DELAY>
@@ -151,6 +152,7 @@ where
             peris,
             delay,
             control_state: 0,
+            temperature: None,
         }
     }
 
@@ -210,7 +212,7 @@ where
         self.cmd_with_data(CS_ALL, Command::TconSetting, &[0x22])?;
         self.cmd_with_data(CS_ALL, Command::PowerSaving, &[0x00])?;
         self.cmd_with_data(CS_ALL, Command::CascadeSetting, &[0x03])?;
-        self.cmd_with_data(CS_ALL, Command::ForceTemperature, &[25])?;
+        self.send_temperature()?;
 
         self.set_mode(config)?;
 
@@ -253,6 +255,45 @@ where
         self.flush()
     }
 
+    /// Refresh for a panel at `celsius`, or at the temperature measured by the sensor of M1
+    /// for `None`, as with [`Temperature`](crate::traits::Temperature)
+    ///
+    /// The four controllers are always given the same temperature to refresh with the same
+    /// waveform, so the other sensors aren't used. The measurement is taken here and by
+    /// [`init()`](EpdDriver::init), which keeps a given temperature.
+    pub fn set_temperature(&mut self, celsius: Option<i8>) -> Result<(), SPI::Error> {
+        self.temperature = celsius;
+        self.send_temperature()?;
+        self.flush()
+    }
+
+    /// The temperature given to [`set_temperature()`](EpdDriver::set_temperature)
+    pub fn temperature(&self) -> Option<i8> {
+        self.temperature
+    }
+
+    fn send_temperature(&mut self) -> Result<(), SPI::Error> {
+        let celsius = match self.temperature {
+            Some(celsius) => celsius,
+            None => self.measure_temperature()?,
+        };
+        self.cmd_with_data(CS_ALL, Command::ForceTemperature, &[celsius as u8])
+    }
+
+    /// Temperature measured by the sensor of M1, the first byte of the result holds the whole
+    /// degrees
+    fn measure_temperature(&mut self) -> Result<i8, SPI::Error> {
+        self.cmd(CS_M1, Command::TemperatureSensorCalibration)?;
+        self.flush()?;
+        drop(self.wait_ready(CS_M1));
+
+        let mut celsius = [0u8; 2];
+        self.select(CS_M1 | CS_DATA)?;
+        self.peris.spi.read(&mut celsius)?;
+        self.flush()?;
+        Ok(celsius[0] as i8)
+    }
+
     /// Fill data1 buffer with pixels:
     /// - data1 containes the black/white image channel,
     /// - data2 contains the red/not red channel.
@@ -558,6 +599,12 @@ where
 
     // Set control pins to the specified state, then send data via SPI.
     fn spi_write(&mut self, control: CS, data: &[u8]) -> Result<(), SPI::Error> {
+        self.select(control)?;
+        self.peris.spi.write(data)
+    }
+
+    // Set control pins to the specified state.
+    fn select(&mut self, control: CS) -> Result<(), SPI::Error> {
         if self.control_state != control {
             fn pin_state(high: bool) -> PinState {
                 if high {
@@ -584,8 +631,7 @@ where
             self.delay.delay_ns(100); // Tcss = 60ns, Tsds = 30ns
             self.control_state = control;
         }
-
-        self.peris.spi.write(data)
+        Ok(())
     }
 
     // Flush SPI, reset control pins to the default state.
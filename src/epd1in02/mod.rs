@@ -13,6 +13,7 @@ use embedded_hal::{
 use crate::color::Color;
 use crate::interface::DisplayInterface;
 use crate::prelude::WaveshareDisplay;
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
 use crate::traits::{InternalWiAdditions, QuickRefresh, RefreshLut};
 
 pub(crate) mod command;
@@ -33,6 +34,7 @@ pub type Display1in02 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Width of the display
@@ -58,6 +60,10 @@ pub struct Epd1in02<SPI, BUSY, DC, RST, DELAY> {
     refresh_mode: RefreshLut,
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd1in02<SPI, BUSY, DC, RST, DELAY> {
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 10);
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd1in02<SPI, BUSY, DC, RST, DELAY>
 where
@@ -202,7 +208,9 @@ where
         refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
         let (white_lut, black_lut) = match refresh_rate {
-            Some(RefreshLut::Full) => (&LUT_FULL_UPDATE_WHITE, &LUT_FULL_UPDATE_BLACK),
+            Some(RefreshLut::Full) | Some(RefreshLut::Fast) => {
+                (&LUT_FULL_UPDATE_WHITE, &LUT_FULL_UPDATE_BLACK)
+            }
             Some(RefreshLut::Quick) => (&LUT_PARTIAL_UPDATE_WHITE, &LUT_PARTIAL_UPDATE_BLACK),
             None => return Ok(()),
         };
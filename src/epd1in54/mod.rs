@@ -63,11 +63,19 @@ use crate::type_a::{
 
 use crate::color::Color;
 
-use crate::traits::{RefreshLut, WaveshareDisplay};
+use crate::lut::{self, LutBand, Waveform};
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
+use crate::traits::{RefreshLut, Temperature, WaveshareDisplay};
 
 use crate::buffer_len;
 use crate::interface::DisplayInterface;
 
+/// Full waveforms by temperature, Waveshare only publishes the one for room temperature
+const FULL_LUTS: [LutBand<&[u8]>; 1] = [LutBand {
+    celsius: lut::HOST_LUT_CELSIUS,
+    lut: &LUT_FULL_UPDATE,
+}];
+
 /// Full size buffer for use with the 1in54b EPD
 #[cfg(feature = "graphics")]
 pub type Display1in54 = crate::graphics::Display<
@@ -76,6 +84,7 @@ pub type Display1in54 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd1in54 driver
@@ -88,6 +97,10 @@ pub struct Epd1in54<SPI, BUSY, DC, RST, DELAY> {
     refresh: RefreshLut,
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd1in54<SPI, BUSY, DC, RST, DELAY> {
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 10);
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> Epd1in54<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
@@ -282,9 +295,17 @@ where
         if let Some(refresh_lut) = refresh_rate {
             self.refresh = refresh_lut;
         }
-        match self.refresh {
-            RefreshLut::Full => self.set_lut_helper(spi, delay, &LUT_FULL_UPDATE),
-            RefreshLut::Quick => self.set_lut_helper(spi, delay, &LUT_PARTIAL_UPDATE),
+        match (
+            self.refresh,
+            lut::select(&FULL_LUTS, self.interface.temperature()),
+        ) {
+            (RefreshLut::Full | RefreshLut::Fast, Waveform::Host(full)) => {
+                self.set_lut_helper(spi, delay, full)
+            }
+            (RefreshLut::Full | RefreshLut::Fast, Waveform::Otp(celsius)) => self
+                .interface
+                .load_otp_waveform(spi, delay, IS_BUSY_LOW, celsius),
+            (RefreshLut::Quick, _) => self.set_lut_helper(spi, delay, &LUT_PARTIAL_UPDATE),
         }
     }
 
@@ -386,6 +407,32 @@ where
     }
 }
 
+/// Full refreshes of a panel outside of the band of the full LUT take the waveform of the OTP
+/// for its temperature, the quick LUT is used at any temperature.
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd1in54<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.set_lut(spi, delay, None)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -396,4 +443,39 @@ mod tests {
         assert_eq!(HEIGHT, 200);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn temperature_bands() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd1in54::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        assert_eq!(simulator.lut(), LUT_FULL_UPDATE);
+
+        // the waveform of the OTP is loaded outside of the band, also after a wake up
+        epd.set_temperature(&mut spi, &mut delay, Some(35)).unwrap();
+        assert!(simulator.lut().is_empty());
+        assert_eq!(simulator.register(0x1A), Some(std::vec![35, 0x00]));
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        assert!(simulator.lut().is_empty());
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))
+            .unwrap();
+        assert_eq!(simulator.lut(), LUT_PARTIAL_UPDATE);
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        epd.set_temperature(&mut spi, &mut delay, None).unwrap();
+        assert_eq!(simulator.lut(), LUT_FULL_UPDATE);
+    }
 }
@@ -19,11 +19,19 @@ mod constants;
 use crate::epd1in54_v2::constants::{LUT_FULL_UPDATE, LUT_PARTIAL_UPDATE};
 
 use crate::color::Color;
+use crate::lut::{self, LutBand, Ssd1680Lut, Waveform};
 
-use crate::traits::{RefreshLut, WaveshareDisplay};
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
+use crate::traits::{CustomLut, HardwareMirror, RefreshLut, Temperature, WaveshareDisplay};
 
 use crate::interface::DisplayInterface;
 
+/// Full waveforms by temperature, Waveshare only publishes the one for room temperature
+const FULL_LUTS: [LutBand<&[u8]>; 1] = [LutBand {
+    celsius: lut::HOST_LUT_CELSIUS,
+    lut: &LUT_FULL_UPDATE,
+}];
+
 #[cfg(feature = "graphics")]
 pub use crate::epd1in54::Display1in54;
 
@@ -36,6 +44,12 @@ pub struct Epd1in54<SPI, BUSY, DC, RST, DELAY> {
 
     /// Refresh LUT
     refresh: RefreshLut,
+    /// RAM is written bottom up
+    mirror_y: bool,
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd1in54<SPI, BUSY, DC, RST, DELAY> {
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 10);
 }
 
 impl<SPI, BUSY, DC, RST, DELAY> Epd1in54<SPI, BUSY, DC, RST, DELAY>
@@ -63,8 +77,11 @@ where
             &[(HEIGHT - 1) as u8, 0x0, 0x00],
         )?;
 
-        self.interface
-            .cmd_with_data(spi, Command::DataEntryModeSetting, &[0x3])?;
+        self.interface.cmd_with_data(
+            spi,
+            Command::DataEntryModeSetting,
+            &[self.data_entry_mode()],
+        )?;
 
         self.set_ram_area(spi, delay, 0, 0, WIDTH - 1, HEIGHT - 1)?;
 
@@ -119,6 +136,7 @@ where
             interface,
             background_color: DEFAULT_BACKGROUND_COLOR,
             refresh: RefreshLut::Full,
+            mirror_y: false,
         };
 
         epd.init(spi, delay)?;
@@ -162,7 +180,7 @@ where
         height: u32,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        self.set_ram_area(spi, delay, x, y, x + width, y + height)?;
+        self.set_ram_area(spi, delay, x, y, x + width - 1, y + height - 1)?;
         self.set_ram_counter(spi, delay, x, y)?;
 
         self.interface
@@ -172,13 +190,13 @@ where
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        if self.refresh == RefreshLut::Full {
-            self.interface
-                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xC7])?;
-        } else if self.refresh == RefreshLut::Quick {
-            self.interface
-                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xCF])?;
-        }
+        // the LUT is loaded by set_lut, the quick refresh uses display mode 2
+        let control = match self.refresh {
+            RefreshLut::Full | RefreshLut::Fast => 0xC7,
+            RefreshLut::Quick => 0xCF,
+        };
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[control])?;
 
         self.interface.cmd(spi, Command::MasterActivation)?;
         // MASTER Activation should not be interupted to avoid currption of panel images
@@ -232,8 +250,18 @@ where
             self.refresh = refresh_lut;
         }
         match self.refresh {
-            RefreshLut::Full => self.set_lut_helper(spi, delay, &LUT_FULL_UPDATE),
+            RefreshLut::Full => match lut::select(&FULL_LUTS, self.interface.temperature()) {
+                Waveform::Host(full) => self.set_lut_helper(spi, delay, full),
+                Waveform::Otp(celsius) => {
+                    self.interface
+                        .load_otp_waveform(spi, delay, IS_BUSY_LOW, celsius)
+                }
+            },
             RefreshLut::Quick => self.set_lut_helper(spi, delay, &LUT_PARTIAL_UPDATE),
+            RefreshLut::Fast => {
+                self.interface
+                    .load_otp_waveform(spi, delay, IS_BUSY_LOW, lut::FAST_CELSIUS)
+            }
         }?;
 
         // Additional configuration required only for partial updates
@@ -291,8 +319,9 @@ where
         end_y: u32,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        assert!(start_x < end_x);
-        assert!(start_y < end_y);
+        assert!(start_x <= end_x);
+        assert!(start_y <= end_y);
+        let (start_y, end_y) = (self.ram_y(start_y), self.ram_y(end_y));
 
         // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
         // aren't relevant
@@ -324,6 +353,7 @@ where
         y: u32,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
+        let y = self.ram_y(y);
         // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
         // aren't relevant
         self.interface
@@ -338,6 +368,24 @@ where
         Ok(())
     }
 
+    /// X increment, Y increment or decrement when mirrored, address counter updated in X direction
+    fn data_entry_mode(&self) -> u8 {
+        if self.mirror_y {
+            0x01
+        } else {
+            0x03
+        }
+    }
+
+    /// RAM row of a frame row, the frame is stored bottom up when mirrored
+    fn ram_y(&self, y: u32) -> u32 {
+        if self.mirror_y {
+            HEIGHT - 1 - y
+        } else {
+            y
+        }
+    }
+
     fn set_lut_helper(
         &mut self,
         spi: &mut SPI,
@@ -370,6 +418,100 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd1in54<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.set_lut(spi, delay, None)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> CustomLut<SPI, BUSY, DC, RST, DELAY>
+    for Epd1in54<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type Lut = Ssd1680Lut;
+
+    fn set_custom_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: &Ssd1680Lut,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteLutRegister, lut.waveform())?;
+        if let Some(voltages) = lut.voltages() {
+            self.interface.cmd_with_data(
+                spi,
+                Command::WriteLutRegisterEnd,
+                &[voltages.end_option],
+            )?;
+            self.wait_until_idle(spi, delay)?;
+            self.interface
+                .cmd_with_data(spi, Command::GateDrivingVoltage, &[voltages.gate])?;
+            self.interface
+                .cmd_with_data(spi, Command::SourceDrivingVoltage, &voltages.source)?;
+            self.interface
+                .cmd_with_data(spi, Command::WriteVcomRegister, &[voltages.vcom])?;
+        }
+        Ok(())
+    }
+}
+
+/// Only the Y address counter can run backwards, mirroring the X direction would need the
+/// bits of each byte to be reversed as well.
+impl<SPI, BUSY, DC, RST, DELAY> HardwareMirror<SPI, BUSY, DC, RST, DELAY>
+    for Epd1in54<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    const HORIZONTAL: bool = false;
+    const VERTICAL: bool = true;
+
+    fn set_mirror(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        _horizontal: bool,
+        vertical: bool,
+    ) -> Result<(), SPI::Error> {
+        self.mirror_y = vertical;
+        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd_with_data(
+            spi,
+            Command::DataEntryModeSetting,
+            &[self.data_entry_mode()],
+        )?;
+        self.use_full_frame(spi, delay)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -380,4 +522,80 @@ mod tests {
         assert_eq!(HEIGHT, 200);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[test]
+    fn builtin_luts_round_trip() {
+        for lut in [&LUT_FULL_UPDATE, &LUT_PARTIAL_UPDATE] {
+            lut::assert_round_trip(lut);
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn partial_frame_window() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd1in54::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        // 16 x 2 pixels, the window ends at the last pixel of the frame
+        epd.update_partial_frame(&mut spi, &mut delay, &[0x00; 4], 16, 10, 16, 2)
+            .unwrap();
+        let ram = simulator.ram();
+        for (x, y) in [(16, 10), (31, 10), (16, 11), (31, 11)] {
+            assert_eq!(ram.pixel(x, y), Some(Rgb888::BLACK));
+        }
+        for (x, y) in [(15, 10), (32, 10), (16, 12), (16, 9)] {
+            assert_eq!(ram.pixel(x, y), Some(Rgb888::WHITE));
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fast_refresh() {
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd1in54::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        // the waveform of the OTP is loaded for the forced high temperature
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Fast))
+            .unwrap();
+        assert_eq!(
+            simulator.register(0x1A),
+            Some(std::vec![lut::FAST_CELSIUS as u8, 0x00])
+        );
+        assert_eq!(simulator.register(0x22), Some(std::vec![0x91]));
+        assert!(simulator.lut().is_empty());
+
+        // and refreshes in display mode 1 like the full LUT
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xC7]));
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        assert_eq!(simulator.lut(), LUT_FULL_UPDATE[..153]);
+    }
 }
@@ -29,6 +29,10 @@ pub(crate) enum Command {
     VcomAndDataIntervalSetting = 0x50,
     ResolutionSetting = 0x61,
     VcmDcSetting = 0x82,
+
+    PartialWindow = 0x90,
+    PartialIn = 0x91,
+    PartialOut = 0x92,
     PowerSaving = 0xE3,
 }
 
@@ -3,8 +3,10 @@
 use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
 
 use crate::interface::DisplayInterface;
+use crate::rect::Rect;
 use crate::traits::{
     InternalWiAdditions, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+    WaveshareThreeColorPartialDisplay,
 };
 
 //The Lookup Tables for the Display
@@ -35,6 +37,7 @@ pub type Display1in54b = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd1in54b driver
@@ -139,6 +142,88 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareThreeColorPartialDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd1in54b<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn update_partial_achromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        achromatic: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            false,
+        )?;
+
+        self.interface.cmd(spi, Command::DataStartTransmission1)?;
+        for b in achromatic {
+            // Two bits per pixel
+            let expanded = expand_bits(*b);
+            self.interface.data(spi, &expanded)?;
+        }
+
+        self.command(spi, Command::PartialOut)
+    }
+
+    fn update_partial_chromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        _delay: &mut DELAY,
+        chromatic: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            false,
+        )?;
+        self.cmd_with_data(spi, Command::DataStartTransmission2, chromatic)?;
+        self.command(spi, Command::PartialOut)
+    }
+
+    fn display_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            false,
+        )?;
+        self.command(spi, Command::DisplayRefresh)?;
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialOut)
+    }
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd1in54b<SPI, BUSY, DC, RST, DELAY>
 where
@@ -237,7 +322,9 @@ where
         Ok(())
     }
 
-    #[allow(unused)]
+    /// Updates a window of the black/white data, the red data is kept
+    ///
+    /// See [`WaveshareThreeColorPartialDisplay::update_partial_achromatic_frame`].
     fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
@@ -248,7 +335,7 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        unimplemented!()
+        self.update_partial_achromatic_frame(spi, delay, buffer, x, y, width, height)
     }
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -272,7 +359,7 @@ where
         self.wait_until_idle(spi, delay)?;
         self.send_resolution(spi)?;
 
-        let color = DEFAULT_BACKGROUND_COLOR.get_byte_value();
+        let color = self.color.get_byte_value();
 
         // Clear the black
         self.interface.cmd(spi, Command::DataStartTransmission1)?;
@@ -281,10 +368,9 @@ where
         self.interface
             .data_x_times(spi, color, 2 * (WIDTH / 8 * HEIGHT))?;
 
-        // Clear the red
+        // Clear the red, whatever the background color
         self.interface.cmd(spi, Command::DataStartTransmission2)?;
-        self.interface
-            .data_x_times(spi, color, WIDTH / 8 * HEIGHT)?;
+        self.interface.data_x_times(spi, 0xFF, WIDTH / 8 * HEIGHT)?;
         Ok(())
     }
 
@@ -376,4 +462,57 @@ mod tests {
         assert_eq!(HEIGHT, 200);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn partial_planes() {
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        simulator.set_two_bit_old_data(true);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd1in54b::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        epd.clear_frame(&mut spi, &mut delay).unwrap();
+
+        epd.update_partial_achromatic_frame(&mut spi, &mut delay, &[0x00, 0xFF], 16, 30, 8, 2)
+            .unwrap();
+        epd.update_partial_chromatic_frame(&mut spi, &mut delay, &[0x00, 0xFF], 32, 60, 16, 1)
+            .unwrap();
+        epd.display_partial_frame(&mut spi, &mut delay, 16, 30, 32, 31)
+            .unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+        assert_eq!(simulator.writes_while_busy(), 0);
+
+        // the black/white data is the first transmission with 2 bits per pixel
+        let (achromatic, chromatic) = (simulator.second_ram(), simulator.ram());
+        for x in 16..24 {
+            assert_eq!(achromatic.pixel(x, 30), Some(Rgb888::BLACK));
+            assert_eq!(achromatic.pixel(x, 31), Some(Rgb888::WHITE));
+        }
+        assert_eq!(achromatic.pixel(24, 30), Some(Rgb888::WHITE));
+        for x in 32..40 {
+            assert_eq!(chromatic.pixel(x, 60), Some(Rgb888::BLACK));
+            assert_eq!(chromatic.pixel(x + 8, 60), Some(Rgb888::WHITE));
+        }
+        assert_eq!(chromatic.pixel(32, 61), Some(Rgb888::WHITE));
+
+        // the next full frame is written outside of partial mode again
+        epd.update_achromatic_frame(
+            &mut spi,
+            &mut delay,
+            &[0xFF; buffer_len(WIDTH as usize, HEIGHT as usize)],
+        )
+        .unwrap();
+        assert_eq!(simulator.second_ram().pixel(16, 30), Some(Rgb888::WHITE));
+    }
 }
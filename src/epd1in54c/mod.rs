@@ -32,6 +32,21 @@ pub type Display1in54c = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
+>;
+
+/// Full size buffer with the black/white and the yellow pixels of the 1in54c EPD, to be sent with
+/// [`Epd1in54c::update_tricolor_frame`]
+#[cfg(feature = "graphics")]
+pub type TriColorDisplay1in54c = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    true,
+    { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
+    crate::color::TriColor,
+    { crate::color::TriColor::White.get_byte_value() as u16 },
+    false,
+    { crate::color::TriColor::YELLOW_INK },
 >;
 
 /// Epd1in54c driver
@@ -231,15 +246,15 @@ where
 
     fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        let color = DEFAULT_BACKGROUND_COLOR.get_byte_value();
+        let color = self.color.get_byte_value();
 
         // Clear the black
         self.command(spi, Command::DataStartTransmission1)?;
         self.interface.data_x_times(spi, color, NUM_DISPLAY_BITS)?;
 
-        // Clear the chromatic
+        // Clear the chromatic, whatever the background color
         self.command(spi, Command::DataStartTransmission2)?;
-        self.interface.data_x_times(spi, color, NUM_DISPLAY_BITS)?;
+        self.interface.data_x_times(spi, 0xFF, NUM_DISPLAY_BITS)?;
 
         Ok(())
     }
@@ -303,4 +318,78 @@ where
         // we follow upstream code.
         self.send_data(spi, &[h as u8])
     }
+
+    /// Updates both frames with the buffers of a [`TriColorDisplay1in54c`]
+    ///
+    /// The panel shows the cleared bits of the chromatic frame yellow, the chromatic buffer of
+    /// the display is inverted on the way.
+    pub fn update_tricolor_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        black: &[u8],
+        chromatic: &[u8],
+    ) -> Result<(), SPI::Error> {
+        self.update_achromatic_frame(spi, delay, black)?;
+        self.command(spi, Command::DataStartTransmission2)?;
+        for byte in chromatic {
+            self.send_data(spi, &[!byte])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "graphics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tricolor_frame() {
+        use crate::color::TriColor;
+        use crate::simulator::{Controller, Simulator};
+        use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd1in54c::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        epd.clear_frame(&mut spi, &mut delay).unwrap();
+        let cleared = (simulator.second_ram(), simulator.ram());
+
+        let mut display = TriColorDisplay1in54c::default();
+        epd.update_tricolor_frame(
+            &mut spi,
+            &mut delay,
+            display.bw_buffer(),
+            display.chromatic_buffer(),
+        )
+        .unwrap();
+        assert_eq!((simulator.second_ram(), simulator.ram()), cleared);
+
+        let yellow = TriColorDisplay1in54c::color_from_rgb(Rgb888::YELLOW);
+        assert_eq!(yellow, TriColor::Chromatic);
+        display.set_pixel(Pixel(Point::new(3, 1), yellow));
+        display.set_pixel(Pixel(Point::new(4, 1), TriColor::Black));
+        epd.update_tricolor_frame(
+            &mut spi,
+            &mut delay,
+            display.bw_buffer(),
+            display.chromatic_buffer(),
+        )
+        .unwrap();
+        // cleared bits are shown black by the simulator
+        let (black, chromatic) = (simulator.second_ram(), simulator.ram());
+        assert_eq!(chromatic.pixel(3, 1), Some(Rgb888::BLACK));
+        assert_eq!(chromatic.pixel(4, 1), Some(Rgb888::WHITE));
+        assert_eq!(black.pixel(4, 1), Some(Rgb888::BLACK));
+        assert_eq!(black.pixel(5, 1), Some(Rgb888::WHITE));
+    }
 }
@@ -44,6 +44,7 @@ pub(crate) enum Command {
     SetDummyLinePeriod = 0x3A,
     SetGateLineWidth = 0x3B,
     BorderWaveformControl = 0x3C,
+    WriteLutRegisterEnd = 0x3F,
     ReadRamOption = 0x41,
     SetRamXAddressStartEndPosition = 0x44,
     SetRamYAddressStartEndPosition = 0x45,
@@ -111,6 +112,11 @@ impl DisplayUpdateControl2 {
         self
     }
 
+    pub fn display_mode_2(mut self) -> Self {
+        self.0.set_bit(3, true);
+        self
+    }
+
     pub fn load_lut(mut self) -> Self {
         self.0.set_bit(4, true);
         self
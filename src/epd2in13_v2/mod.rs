@@ -24,7 +24,13 @@ use embedded_hal::{
 use crate::buffer_len;
 use crate::color::Color;
 use crate::interface::DisplayInterface;
-use crate::traits::{InternalWiAdditions, RefreshLut, WaveshareDisplay};
+#[cfg(feature = "epd2in13_v3")]
+use crate::lut::Ssd1680Lut;
+use crate::lut::{self, LutBand, Waveform};
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
+#[cfg(feature = "epd2in13_v3")]
+use crate::traits::CustomLut;
+use crate::traits::{InternalWiAdditions, QuickRefresh, RefreshLut, Temperature, WaveshareDisplay};
 
 pub(crate) mod command;
 use self::command::{
@@ -36,6 +42,12 @@ use self::command::{
 pub(crate) mod constants;
 
 use self::constants::{LUT_FULL_UPDATE, LUT_PARTIAL_UPDATE};
+
+/// Full waveforms by temperature, Waveshare only publishes the one for room temperature
+const FULL_LUTS: [LutBand<&[u8]>; 1] = [LutBand {
+    celsius: lut::HOST_LUT_CELSIUS,
+    lut: &LUT_FULL_UPDATE,
+}];
 #[cfg(all(feature = "epd2in13_v2", feature = "epd2in13_v3"))]
 compile_error!(
     "feature \"epd2in13_v2\" and feature \"epd2in13_v3\" cannot be enabled at the same time"
@@ -53,6 +65,7 @@ pub type Display2in13 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Width of the display.
@@ -80,6 +93,10 @@ pub struct Epd2in13<SPI, BUSY, DC, RST, DELAY> {
     refresh: RefreshLut,
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd2in13<SPI, BUSY, DC, RST, DELAY> {
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 10);
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
     for Epd2in13<SPI, BUSY, DC, RST, DELAY>
 where
@@ -236,7 +253,7 @@ where
 
         self.cmd_with_data(spi, Command::WriteRam, buffer)?;
 
-        if self.refresh == RefreshLut::Full {
+        if self.refresh != RefreshLut::Quick {
             // Always keep the base buffer equal to current if not doing partial refresh.
             self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
             self.set_ram_address_counters(spi, delay, 0, 0)?;
@@ -246,9 +263,9 @@ where
         Ok(())
     }
 
-    /// Updating only a part of the frame is not supported when using the
-    /// partial refresh feature. The function will panic if called when set to
-    /// use partial refresh.
+    /// When using the partial refresh feature, only the RAM of the new frame is
+    /// updated. The base RAM keeps the frame on the screen, which the controller
+    /// compares the new frame with.
     fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
@@ -261,34 +278,29 @@ where
     ) -> Result<(), SPI::Error> {
         assert!((width * height / 8) as usize == buffer.len());
 
-        // This should not be used when doing partial refresh. The RAM_RED must
-        // be updated with the last buffer having been displayed. Doing partial
-        // update directly in RAM makes this update impossible (we can't read
-        // RAM content). Using this function will most probably make the actual
-        // display incorrect as the controler will compare with something
-        // incorrect.
-        assert!(self.refresh == RefreshLut::Full);
-
-        self.set_ram_area(spi, x, y, x + width, y + height)?;
-        self.set_ram_address_counters(spi, delay, x, y)?;
-
-        self.cmd_with_data(spi, Command::WriteRam, buffer)?;
+        self.set_partial_ram(spi, delay, Command::WriteRam, buffer, x, y, width, height)?;
 
-        if self.refresh == RefreshLut::Full {
+        if self.refresh != RefreshLut::Quick {
             // Always keep the base buffer equals to current if not doing partial refresh.
-            self.set_ram_area(spi, x, y, x + width, y + height)?;
-            self.set_ram_address_counters(spi, delay, x, y)?;
-
-            self.cmd_with_data(spi, Command::WriteRamRed, buffer)?;
+            self.set_partial_ram(
+                spi,
+                delay,
+                Command::WriteRamRed,
+                buffer,
+                x,
+                y,
+                width,
+                height,
+            )?;
         }
 
         Ok(())
     }
 
-    /// Never use directly this function when using partial refresh, or also
-    /// keep the base buffer in syncd using `set_partial_base_buffer` function.
+    /// When using partial refresh, the controller copies the displayed frame
+    /// into the base RAM, which keeps it in sync for the next refresh.
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        if self.refresh == RefreshLut::Full {
+        if self.refresh != RefreshLut::Quick {
             self.set_display_update_control_2(
                 spi,
                 DisplayUpdateControl2::new()
@@ -299,7 +311,11 @@ where
                     .disable_clock(),
             )?;
         } else {
-            self.set_display_update_control_2(spi, DisplayUpdateControl2::new().display())?;
+            // display mode 2 copies the new frame into the base RAM afterwards
+            self.set_display_update_control_2(
+                spi,
+                DisplayUpdateControl2::new().display().display_mode_2(),
+            )?;
         }
         self.command(spi, Command::MasterActivation)?;
         self.wait_until_idle(spi, delay)?;
@@ -336,7 +352,7 @@ where
         )?;
 
         // Always keep the base buffer equals to current if not doing partial refresh.
-        if self.refresh == RefreshLut::Full {
+        if self.refresh != RefreshLut::Quick {
             self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
             self.set_ram_address_counters(spi, delay, 0, 0)?;
 
@@ -366,18 +382,34 @@ where
         HEIGHT
     }
 
+    /// Changing the LUT changes the refresh mode as well, see [`Epd2in13::set_refresh`]
     fn set_lut(
         &mut self,
         spi: &mut SPI,
-        _delay: &mut DELAY,
+        delay: &mut DELAY,
         refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        let buffer = match refresh_rate {
-            Some(RefreshLut::Full) | None => &LUT_FULL_UPDATE,
-            Some(RefreshLut::Quick) => &LUT_PARTIAL_UPDATE,
+        if let Some(refresh) = refresh_rate {
+            if refresh != self.refresh {
+                // the init of the new mode loads its LUT
+                return self.set_refresh(spi, delay, refresh);
+            }
+        }
+        let celsius = match self.refresh {
+            RefreshLut::Full => match lut::select(&FULL_LUTS, self.interface.temperature()) {
+                Waveform::Host(full) => {
+                    return self.cmd_with_data(spi, Command::WriteLutRegister, full)
+                }
+                Waveform::Otp(celsius) => celsius,
+            },
+            RefreshLut::Quick => {
+                return self.cmd_with_data(spi, Command::WriteLutRegister, &LUT_PARTIAL_UPDATE)
+            }
+            RefreshLut::Fast => lut::FAST_CELSIUS,
         };
 
-        self.cmd_with_data(spi, Command::WriteLutRegister, buffer)
+        self.interface
+            .load_otp_waveform(spi, delay, IS_BUSY_LOW, celsius)
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -410,6 +442,25 @@ where
         Ok(())
     }
 
+    /// Writes a window of one of the RAMs
+    #[allow(clippy::too_many_arguments)]
+    fn set_partial_ram(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        ram: Command,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.set_ram_area(spi, x, y, x + width - 1, y + height - 1)?;
+        self.set_ram_address_counters(spi, delay, x, y)?;
+
+        self.cmd_with_data(spi, ram, buffer)
+    }
+
     /// Selects which sleep mode will be used when triggering the deep sleep.
     pub fn set_deep_sleep_mode(&mut self, mode: DeepSleepMode) {
         self.sleep_mode = mode;
@@ -586,6 +637,169 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.set_lut(spi, delay, None)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
+/// Only the V3 takes the waveform of the SSD1680, the SSD1675 of the V2 has a different layout.
+#[cfg(feature = "epd2in13_v3")]
+impl<SPI, BUSY, DC, RST, DELAY> CustomLut<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type Lut = Ssd1680Lut;
+
+    fn set_custom_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: &Ssd1680Lut,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.cmd_with_data(spi, Command::WriteLutRegister, lut.waveform())?;
+        if let Some(voltages) = lut.voltages() {
+            self.cmd_with_data(spi, Command::WriteLutRegisterEnd, &[voltages.end_option])?;
+            self.cmd_with_data(spi, Command::GateDrivingVoltageCtrl, &[voltages.gate])?;
+            self.cmd_with_data(spi, Command::SourceDrivingVoltageCtrl, &voltages.source)?;
+            self.cmd_with_data(spi, Command::WriteVcomRegister, &[voltages.vcom])?;
+        }
+        Ok(())
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Writes the base RAM the controller compares the new frame with, same as
+    /// `set_partial_base_buffer`
+    fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.set_partial_base_buffer(spi, delay, buffer)
+    }
+
+    /// Same as `update_frame`
+    fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_frame(spi, buffer, delay)
+    }
+
+    /// Same as `display_frame`, which keeps the base RAM in sync
+    fn display_new_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.display_frame(spi, delay)
+    }
+
+    fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_new_frame(spi, buffer, delay)?;
+        self.display_new_frame(spi, delay)
+    }
+
+    fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        assert!((width * height / 8) as usize == buffer.len());
+        self.set_partial_ram(
+            spi,
+            delay,
+            Command::WriteRamRed,
+            buffer,
+            x,
+            y,
+            width,
+            height,
+        )
+    }
+
+    /// Same as `update_partial_frame`
+    fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.update_partial_frame(spi, delay, buffer, x, y, width, height)
+    }
+
+    fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        let color = self.background_color.get_byte_value();
+        let rams: &[Command] = if self.refresh != RefreshLut::Quick {
+            &[Command::WriteRam, Command::WriteRamRed]
+        } else {
+            &[Command::WriteRam]
+        };
+        for &ram in rams {
+            self.set_ram_area(spi, x, y, x + width - 1, y + height - 1)?;
+            self.set_ram_address_counters(spi, delay, x, y)?;
+            self.command(spi, ram)?;
+            self.interface
+                .data_x_times(spi, color, width / 8 * height)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -596,4 +810,124 @@ mod tests {
         assert_eq!(HEIGHT, 250);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[cfg(feature = "epd2in13_v3")]
+    #[test]
+    fn builtin_luts_round_trip() {
+        for lut in [&LUT_FULL_UPDATE, &LUT_PARTIAL_UPDATE] {
+            lut::assert_round_trip(lut);
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn quick_refresh_windows() {
+        use crate::graphics::DoubleBufferedDisplay;
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use crate::snapshot::Snapshot;
+        use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in13::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut display: DoubleBufferedDisplay<
+            WIDTH,
+            HEIGHT,
+            false,
+            { buffer_len(WIDTH as usize, HEIGHT as usize) },
+            Color,
+            { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
+        > = DoubleBufferedDisplay::default();
+        Pixel(Point::new(0, 0), Color::Black)
+            .draw(&mut display)
+            .unwrap();
+        epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+            .unwrap();
+        display.mark_displayed();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))
+            .unwrap();
+        for point in [Point::new(20, 30), Point::new(100, 200), Point::new(20, 31)] {
+            Pixel(point, Color::Black).draw(&mut display).unwrap();
+            let window = display.flush(&mut epd, &mut spi, &mut delay).unwrap();
+            assert_eq!(window.map(|window| (window.w, window.h)), Some((8, 1)));
+            assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+            assert_eq!(
+                simulator.displayed(),
+                Snapshot::from_display(display.display())
+            );
+        }
+        // only the changed pixels were driven
+        assert_eq!(simulator.ghosting(20, 30), Some(1));
+        assert_eq!(simulator.ghosting(20, 31), Some(1));
+        assert_eq!(simulator.ghosting(0, 0), Some(0));
+        assert_eq!(simulator.partial_refreshes(), 3);
+
+        epd.clear_partial_frame(&mut spi, &mut delay, 16, 30, 8, 2)
+            .unwrap();
+        epd.display_new_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.displayed().pixel(20, 31), Some(Rgb888::WHITE));
+        assert_eq!(simulator.displayed().pixel(100, 200), Some(Rgb888::BLACK));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+        assert_eq!(simulator.writes_while_busy(), 0);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fast_refresh() {
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use crate::snapshot::Snapshot;
+        use embedded_graphics::prelude::*;
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in13::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut display = Display2in13::default();
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Fast))
+            .unwrap();
+        for point in [Point::new(20, 30), Point::new(100, 200)] {
+            Pixel(point, Color::Black).draw(&mut display).unwrap();
+            epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+                .unwrap();
+            // all pixels are driven, like with the full LUT
+            assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+            assert_eq!(simulator.max_ghosting(), 0);
+            assert_eq!(simulator.displayed(), Snapshot::from_display(&display));
+        }
+
+        // the init after waking up keeps the mode
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        Pixel(Point::new(0, 0), Color::Black)
+            .draw(&mut display)
+            .unwrap();
+        epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+            .unwrap();
+        assert_eq!(simulator.displayed(), Snapshot::from_display(&display));
+        assert_eq!(simulator.refreshes(), 3);
+        assert_eq!(simulator.writes_while_busy(), 0);
+    }
 }
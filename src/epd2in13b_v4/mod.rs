@@ -61,7 +61,8 @@ use crate::buffer_len;
 use crate::color::TriColor;
 use crate::interface::DisplayInterface;
 use crate::traits::{
-    InternalWiAdditions, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+    InternalWiAdditions, RefreshLut, Temperature, WaveshareDisplay, WaveshareThreeColorDisplay,
+    WaveshareThreeColorPartialDisplay,
 };
 
 pub(crate) mod command;
@@ -81,6 +82,7 @@ pub type Display2in13b = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) * 2 },
     TriColor,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Width of the display.
@@ -118,6 +120,9 @@ where
         self.wait_until_idle(spi, delay)?;
         self.interface.cmd(spi, Command::SwReset)?;
         self.wait_until_idle(spi, delay)?;
+        if self.interface.temperature().is_some() {
+            self.send_temperature(spi)?;
+        }
 
         self.set_driver_output(
             spi,
@@ -186,9 +191,10 @@ where
     fn update_achromatic_frame(
         &mut self,
         spi: &mut SPI,
-        _delay: &mut DELAY,
+        delay: &mut DELAY,
         black: &[u8],
     ) -> Result<(), SPI::Error> {
+        self.use_full_frame(spi, delay)?;
         self.interface.cmd(spi, Command::WriteRam)?;
         self.interface.data(spi, black)?;
         Ok(())
@@ -197,9 +203,10 @@ where
     fn update_chromatic_frame(
         &mut self,
         spi: &mut SPI,
-        _delay: &mut DELAY,
+        delay: &mut DELAY,
         chromatic: &[u8],
     ) -> Result<(), SPI::Error> {
+        self.use_full_frame(spi, delay)?;
         self.interface.cmd(spi, Command::WriteRamRed)?;
         self.interface.data(spi, chromatic)?;
         Ok(())
@@ -246,9 +253,10 @@ where
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
-        _delay: &mut DELAY,
+        delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
         assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
+        self.use_full_frame(spi, delay)?;
         self.cmd_with_data(spi, Command::WriteRam, buffer)?;
 
         self.command(spi, Command::WriteRamRed)?;
@@ -260,17 +268,20 @@ where
         Ok(())
     }
 
+    /// Updates a window of the black/white frame, the chromatic frame is kept
+    ///
+    /// See [`WaveshareThreeColorPartialDisplay::update_partial_achromatic_frame`].
     fn update_partial_frame(
         &mut self,
-        _spi: &mut SPI,
-        _delay: &mut DELAY,
-        _buffer: &[u8],
-        _x: u32,
-        _y: u32,
-        _width: u32,
-        _height: u32,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        self.update_partial_achromatic_frame(spi, delay, buffer, x, y, width, height)
     }
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -291,7 +302,8 @@ where
         Ok(())
     }
 
-    fn clear_frame(&mut self, spi: &mut SPI, _delay: &mut DELAY) -> Result<(), SPI::Error> {
+    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.use_full_frame(spi, delay)?;
         self.clear_achromatic_frame(spi)?;
         self.clear_chromatic_frame(spi)
     }
@@ -312,13 +324,14 @@ where
         HEIGHT
     }
 
+    /// Only the full refresh of the OTP is supported, this does nothing
     fn set_lut(
         &mut self,
         _spi: &mut SPI,
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!()
+        Ok(())
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -327,6 +340,57 @@ where
     }
 }
 
+/// The controller has no windowed refresh, the windows are shown by refreshing the whole screen
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareThreeColorPartialDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13b<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn update_partial_achromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        achromatic: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.set_ram_window(spi, delay, x, y, width, height)?;
+        self.cmd_with_data(spi, Command::WriteRam, achromatic)
+    }
+
+    fn update_partial_chromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        chromatic: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.set_ram_window(spi, delay, x, y, width, height)?;
+        self.cmd_with_data(spi, Command::WriteRamRed, chromatic)
+    }
+
+    fn display_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.display_frame(spi, delay)
+    }
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> Epd2in13b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
@@ -335,6 +399,25 @@ where
     RST: OutputPin,
     DELAY: DelayNs,
 {
+    fn use_full_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.set_ram_window(spi, delay, 0, 0, WIDTH, HEIGHT)
+    }
+
+    /// Both RAMs are written through this window, starting at its top left corner
+    fn set_ram_window(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.set_ram_area(spi, x, y, x + width - 1, y + height - 1)?;
+        self.set_ram_address_counters(spi, delay, x, y)
+    }
+
     fn set_display_update_control(
         &mut self,
         spi: &mut SPI,
@@ -359,6 +442,16 @@ where
         )
     }
 
+    /// Writes the temperature of the panel, and the update sequence of
+    /// [`display_frame`](WaveshareDisplay::display_frame) (0xFF after a reset) without the step
+    /// which replaces it by the one of the sensor
+    fn send_temperature(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+        let celsius = self.interface.temperature();
+        self.interface.cmd_temperature_register(spi, celsius)?;
+        let control = self.interface.ssd168x_update_control(0xFF);
+        self.cmd_with_data(spi, Command::DisplayUpdateControl2, &[control])
+    }
+
     /// Triggers the deep sleep mode
     fn set_sleep_mode(&mut self, spi: &mut SPI, mode: DeepSleepMode) -> Result<(), SPI::Error> {
         self.cmd_with_data(spi, Command::DeepSleepMode, &[mode as u8])
@@ -473,7 +566,7 @@ where
     fn clear_chromatic_frame(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
         match self.background_color {
             TriColor::White => {
-                self.command(spi, Command::WriteRam)?;
+                self.command(spi, Command::WriteRamRed)?;
                 self.interface.data_x_times(
                     spi,
                     0x00,
@@ -481,7 +574,7 @@ where
                 )?;
             }
             TriColor::Chromatic => {
-                self.command(spi, Command::WriteRam)?;
+                self.command(spi, Command::WriteRamRed)?;
                 self.interface.data_x_times(
                     spi,
                     0xFF,
@@ -489,7 +582,7 @@ where
                 )?;
             }
             TriColor::Black => {
-                self.command(spi, Command::WriteRam)?;
+                self.command(spi, Command::WriteRamRed)?;
                 self.interface.data_x_times(
                     spi,
                     0x00,
@@ -502,6 +595,32 @@ where
     }
 }
 
+/// The refreshes take the waveform of the OTP for the temperature of the panel.
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13b<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.send_temperature(spi)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -512,4 +631,93 @@ mod tests {
         assert_eq!(HEIGHT, 250);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, TriColor::White);
     }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn partial_planes() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in13b::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut display = Display2in13b::default();
+        Pixel(Point::new(20, 30), TriColor::Black)
+            .draw(&mut display)
+            .unwrap();
+        Pixel(Point::new(40, 60), TriColor::Chromatic)
+            .draw(&mut display)
+            .unwrap();
+        epd.update_color_frame(
+            &mut spi,
+            &mut delay,
+            display.bw_buffer(),
+            display.chromatic_buffer(),
+        )
+        .unwrap();
+        assert_eq!(simulator.ram().pixel(20, 30), Some(Rgb888::BLACK));
+        // the chromatic bits are set
+        assert_eq!(simulator.second_ram().pixel(40, 60), Some(Rgb888::WHITE));
+
+        epd.update_partial_achromatic_frame(&mut spi, &mut delay, &[0x00, 0xFF], 16, 30, 8, 2)
+            .unwrap();
+        epd.update_partial_chromatic_frame(&mut spi, &mut delay, &[0xFF, 0x00], 32, 60, 16, 1)
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        let (ram, chromatic) = (simulator.ram(), simulator.second_ram());
+        for x in 16..24 {
+            assert_eq!(ram.pixel(x, 30), Some(Rgb888::BLACK));
+            assert_eq!(ram.pixel(x, 31), Some(Rgb888::WHITE));
+        }
+        assert_eq!(ram.pixel(24, 30), Some(Rgb888::WHITE));
+        assert_eq!(chromatic.pixel(32, 60), Some(Rgb888::WHITE));
+        assert_eq!(chromatic.pixel(40, 60), Some(Rgb888::BLACK));
+        assert_eq!(chromatic.pixel(32, 61), Some(Rgb888::BLACK));
+
+        // the full frame is written through the whole RAM again
+        epd.clear_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.ram().pixel(20, 30), Some(Rgb888::WHITE));
+        assert_eq!(simulator.second_ram().pixel(32, 60), Some(Rgb888::BLACK));
+        assert_eq!(simulator.writes_while_busy(), 0);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn temperature_register() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in13b::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        assert_eq!(simulator.register(0x22), None);
+
+        epd.set_temperature(&mut spi, &mut delay, Some(40)).unwrap();
+        assert_eq!(simulator.register(0x1A), Some(std::vec![40, 0x00]));
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xDF]));
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xDF]));
+        assert_eq!(epd.temperature(), Some(40));
+
+        // the sensor takes over again
+        epd.set_temperature(&mut spi, &mut delay, None).unwrap();
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xFF]));
+    }
 }
@@ -53,8 +53,10 @@
 use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
 
 use crate::interface::DisplayInterface;
+use crate::rect::Rect;
 use crate::traits::{
     InternalWiAdditions, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+    WaveshareThreeColorPartialDisplay,
 };
 
 /// Width of epd2in13bc in pixels
@@ -89,6 +91,21 @@ pub type Display2in13bc = crate::graphics::Display<
     true,
     { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
     TriColor,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
+>;
+
+/// Full size buffer for the yellow 2.13" (C) EPD, the same as [`Display2in13bc`] except for the
+/// color of its chromatic pixels in the conversions from and to RGB
+#[cfg(feature = "graphics")]
+pub type Display2in13c = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    true,
+    { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
+    TriColor,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
+    false,
+    { TriColor::YELLOW_INK },
 >;
 
 /// Epd2in13bc driver
@@ -191,6 +208,82 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareThreeColorPartialDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13bc<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn update_partial_achromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        _delay: &mut DELAY,
+        achromatic: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            false,
+        )?;
+        self.cmd_with_data(spi, Command::DataStartTransmission1, achromatic)?;
+        self.command(spi, Command::PartialOut)
+    }
+
+    fn update_partial_chromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        chromatic: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            false,
+        )?;
+        self.cmd_with_data(spi, Command::DataStartTransmission2, chromatic)?;
+        self.command(spi, Command::PartialOut)?;
+
+        self.wait_until_idle(spi, delay)?;
+        Ok(())
+    }
+
+    fn display_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            false,
+        )?;
+        self.command(spi, Command::DisplayRefresh)?;
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialOut)
+    }
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd2in13bc<SPI, BUSY, DC, RST, DELAY>
 where
@@ -266,8 +359,12 @@ where
 
         self.interface.data(spi, buffer)?;
 
-        // Clear the chromatic layer
-        let color = self.color.get_byte_value();
+        // Clear the chromatic layer, set bits are chromatic as in `Display2in13bc`
+        let color = if self.color == TriColor::Chromatic {
+            0xFF
+        } else {
+            0x00
+        };
 
         self.interface.cmd(spi, Command::DataStartTransmission2)?;
         self.interface.data_x_times(spi, color, NUM_DISPLAY_BITS)?;
@@ -276,7 +373,9 @@ where
         Ok(())
     }
 
-    #[allow(unused)]
+    /// Updates a window of the black/white data, the chromatic data is kept
+    ///
+    /// See [`WaveshareThreeColorPartialDisplay::update_partial_achromatic_frame`].
     fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
@@ -287,7 +386,7 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        Ok(())
+        self.update_partial_achromatic_frame(spi, delay, buffer, x, y, width, height)
     }
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -311,16 +410,22 @@ where
     fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
         self.send_resolution(spi)?;
 
-        let color = DEFAULT_BACKGROUND_COLOR.get_byte_value();
+        let color = self.color.get_byte_value();
 
         // Clear the black
         self.interface.cmd(spi, Command::DataStartTransmission1)?;
 
         self.interface.data_x_times(spi, color, NUM_DISPLAY_BITS)?;
 
-        // Clear the chromatic
+        // Clear the chromatic, set bits are chromatic as in `Display2in13bc`
+        let chromatic = if self.color == TriColor::Chromatic {
+            0xFF
+        } else {
+            0x00
+        };
         self.interface.cmd(spi, Command::DataStartTransmission2)?;
-        self.interface.data_x_times(spi, color, NUM_DISPLAY_BITS)?;
+        self.interface
+            .data_x_times(spi, chromatic, NUM_DISPLAY_BITS)?;
 
         self.wait_until_idle(spi, delay)?;
         Ok(())
@@ -391,3 +496,93 @@ where
         )
     }
 }
+
+#[cfg(all(test, feature = "graphics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_planes() {
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in13bc::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        epd.clear_frame(&mut spi, &mut delay).unwrap();
+
+        epd.update_partial_achromatic_frame(&mut spi, &mut delay, &[0x00, 0xFF], 16, 30, 8, 2)
+            .unwrap();
+        epd.update_partial_chromatic_frame(&mut spi, &mut delay, &[0x00, 0xFF], 32, 60, 16, 1)
+            .unwrap();
+        epd.display_partial_frame(&mut spi, &mut delay, 16, 30, 32, 31)
+            .unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+        assert_eq!(simulator.writes_while_busy(), 0);
+
+        // the black/white data is the first transmission, cleared bits are shown black
+        let (achromatic, chromatic) = (simulator.second_ram(), simulator.ram());
+        for x in 16..24 {
+            assert_eq!(achromatic.pixel(x, 30), Some(Rgb888::BLACK));
+            assert_eq!(achromatic.pixel(x, 31), Some(Rgb888::WHITE));
+        }
+        assert_eq!(achromatic.pixel(24, 30), Some(Rgb888::WHITE));
+        for x in 32..40 {
+            assert_eq!(chromatic.pixel(x, 60), Some(Rgb888::BLACK));
+            assert_eq!(chromatic.pixel(x + 8, 60), Some(Rgb888::WHITE));
+        }
+        assert_eq!(chromatic.pixel(32, 61), Some(Rgb888::BLACK));
+
+        // the next full frame is written outside of partial mode again
+        epd.update_achromatic_frame(
+            &mut spi,
+            &mut delay,
+            &[0xFF; buffer_len(WIDTH as usize, HEIGHT as usize)],
+        )
+        .unwrap();
+        assert_eq!(simulator.second_ram().pixel(16, 30), Some(Rgb888::WHITE));
+    }
+
+    #[test]
+    fn clear_frame_matches_default_display() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in13bc::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        epd.clear_frame(&mut spi, &mut delay).unwrap();
+        let cleared = (simulator.second_ram(), simulator.ram());
+
+        let display = Display2in13bc::default();
+        epd.update_color_frame(
+            &mut spi,
+            &mut delay,
+            display.bw_buffer(),
+            display.chromatic_buffer(),
+        )
+        .unwrap();
+        assert_eq!((simulator.second_ram(), simulator.ram()), cleared);
+
+        epd.update_frame(&mut spi, display.bw_buffer(), &mut delay)
+            .unwrap();
+        assert_eq!((simulator.second_ram(), simulator.ram()), cleared);
+    }
+}
@@ -200,6 +200,7 @@ pub type Display2in66b = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) * 2 },
     TriColor,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// The EPD 2in66-B driver.
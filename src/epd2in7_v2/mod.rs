@@ -14,11 +14,12 @@ use embedded_hal::{
     spi::SpiDevice,
 };
 
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
 use crate::{
     buffer_len,
     color::Color,
     interface::DisplayInterface,
-    traits::{InternalWiAdditions, RefreshLut, WaveshareDisplay},
+    traits::{InternalWiAdditions, RefreshLut, Temperature, WaveshareDisplay},
     type_a::command::Command,
 };
 
@@ -41,6 +42,7 @@ pub type Display2in7 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd2in7b driver
@@ -52,6 +54,10 @@ pub struct Epd2in7<SPI, BUSY, DC, RST, DELAY> {
     refresh: RefreshLut,
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd2in7<SPI, BUSY, DC, RST, DELAY> {
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 10);
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
     for Epd2in7<SPI, BUSY, DC, RST, DELAY>
 where
@@ -69,6 +75,9 @@ where
         self.command(spi, Command::SwReset)?;
         self.wait_until_idle(spi, delay)?;
 
+        let celsius = self.interface.temperature();
+        self.interface.cmd_temperature_register(spi, celsius)?;
+
         self.use_full_frame(spi, delay)?;
 
         self.interface
@@ -156,13 +165,12 @@ where
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        if self.refresh == RefreshLut::Full {
-            self.interface
-                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xF7])?;
-        } else if self.refresh == RefreshLut::Quick {
-            self.interface
-                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xC7])?;
-        }
+        let control = match self.refresh {
+            RefreshLut::Full | RefreshLut::Fast => self.interface.ssd168x_update_control(0xF7),
+            RefreshLut::Quick => 0xC7,
+        };
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[control])?;
 
         self.interface.cmd(spi, Command::MasterActivation)?;
         self.wait_until_idle(spi, delay)?;
@@ -297,6 +305,33 @@ where
     }
 }
 
+/// The full refresh takes the waveform of the OTP for the temperature of the panel, the quick
+/// one keeps the waveform of the last full refresh.
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in7<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd_temperature_register(spi, celsius)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,4 +342,38 @@ mod tests {
         assert_eq!(HEIGHT, 264);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn temperature_register() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in7::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xF7]));
+
+        // the written temperature isn't replaced by the one of the sensor, also after a wake up
+        epd.set_temperature(&mut spi, &mut delay, Some(-10))
+            .unwrap();
+        assert_eq!(simulator.register(0x1A), Some(std::vec![0xF6, 0x00]));
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xD7]));
+        assert_eq!(epd.temperature(), Some(-10));
+
+        epd.set_temperature(&mut spi, &mut delay, None).unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xF7]));
+    }
 }
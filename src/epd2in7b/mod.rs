@@ -7,6 +7,7 @@ use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
 use crate::interface::DisplayInterface;
 use crate::traits::{
     InternalWiAdditions, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+    WaveshareThreeColorPartialDisplay,
 };
 
 // The Lookup Tables for the Display
@@ -37,6 +38,7 @@ pub type Display2in7b = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd2in7b driver
@@ -329,7 +331,8 @@ where
     }
 }
 
-impl<SPI, BUSY, DC, RST, DELAY> Epd2in7b<SPI, BUSY, DC, RST, DELAY>
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareThreeColorPartialDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in7b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
     BUSY: InputPin,
@@ -337,34 +340,7 @@ where
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn command(&mut self, spi: &mut SPI, command: Command) -> Result<(), SPI::Error> {
-        self.interface.cmd(spi, command)
-    }
-
-    fn send_data(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), SPI::Error> {
-        self.interface.data(spi, data)
-    }
-
-    fn send_buffer_helper(&mut self, spi: &mut SPI, buffer: &[u8]) -> Result<(), SPI::Error> {
-        // Based on the waveshare implementation, all data for color values is flipped. This helper
-        // method makes that transmission easier
-        for b in buffer.iter() {
-            self.send_data(spi, &[!b])?;
-        }
-        Ok(())
-    }
-
-    fn cmd_with_data(
-        &mut self,
-        spi: &mut SPI,
-        command: Command,
-        data: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.interface.cmd_with_data(spi, command, data)
-    }
-
-    /// Refresh display for partial frame
-    pub fn display_partial_frame(
+    fn display_partial_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -386,9 +362,7 @@ where
         Ok(())
     }
 
-    /// Update black/achromatic frame
-    #[allow(clippy::too_many_arguments)]
-    pub fn update_partial_achromatic_frame(
+    fn update_partial_achromatic_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -418,9 +392,7 @@ where
         Ok(())
     }
 
-    /// Update partial chromatic/red frame
-    #[allow(clippy::too_many_arguments)]
-    pub fn update_partial_chromatic_frame(
+    fn update_partial_chromatic_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -451,6 +423,41 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> Epd2in7b<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn command(&mut self, spi: &mut SPI, command: Command) -> Result<(), SPI::Error> {
+        self.interface.cmd(spi, command)
+    }
+
+    fn send_data(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), SPI::Error> {
+        self.interface.data(spi, data)
+    }
+
+    fn send_buffer_helper(&mut self, spi: &mut SPI, buffer: &[u8]) -> Result<(), SPI::Error> {
+        // Based on the waveshare implementation, all data for color values is flipped. This helper
+        // method makes that transmission easier
+        for b in buffer.iter() {
+            self.send_data(spi, &[!b])?;
+        }
+        Ok(())
+    }
+
+    fn cmd_with_data(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+        data: &[u8],
+    ) -> Result<(), SPI::Error> {
+        self.interface.cmd_with_data(spi, command, data)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,6 +59,7 @@ use crate::type_a::{
 
 use crate::color::Color;
 
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
 use crate::traits::*;
 
 use crate::buffer_len;
@@ -72,6 +73,7 @@ pub type Display2in9 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd2in9 driver
@@ -85,6 +87,10 @@ pub struct Epd2in9<SPI, BUSY, DC, RST, DELAY> {
     refresh: RefreshLut,
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd2in9<SPI, BUSY, DC, RST, DELAY> {
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 10);
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> Epd2in9<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
@@ -279,7 +285,9 @@ where
             self.refresh = refresh_lut;
         }
         match self.refresh {
-            RefreshLut::Full => self.set_lut_helper(spi, delay, &LUT_FULL_UPDATE),
+            RefreshLut::Full | RefreshLut::Fast => {
+                self.set_lut_helper(spi, delay, &LUT_FULL_UPDATE)
+            }
             RefreshLut::Quick => self.set_lut_helper(spi, delay, &LUT_PARTIAL_UPDATE),
         }
     }
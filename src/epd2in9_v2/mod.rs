@@ -92,13 +92,21 @@ use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
 use crate::type_a::command::Command;
 
 use crate::color::Color;
+use crate::lut::{self, LutBand, Ssd1680Lut, Waveform};
 
 use crate::traits::*;
 
 use crate::buffer_len;
 use crate::interface::DisplayInterface;
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
 use crate::traits::QuickRefresh;
 
+/// Full waveforms by temperature, Waveshare only publishes the one for room temperature
+const FULL_LUTS: [LutBand<&[u8; 159]>; 1] = [LutBand {
+    celsius: lut::HOST_LUT_CELSIUS,
+    lut: &WS_20_30,
+}];
+
 /// Display with Fullsize buffer for use with the 2in9 EPD V2
 #[cfg(feature = "graphics")]
 pub type Display2in9 = crate::graphics::Display<
@@ -107,6 +115,7 @@ pub type Display2in9 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd2in9 driver
@@ -118,6 +127,12 @@ pub struct Epd2in9<SPI, BUSY, DC, RST, DELAY> {
     background_color: Color,
     /// Refresh LUT
     refresh: RefreshLut,
+    /// RAM is written bottom up
+    mirror_y: bool,
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd2in9<SPI, BUSY, DC, RST, DELAY> {
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 10);
 }
 
 impl<SPI, BUSY, DC, RST, DELAY> Epd2in9<SPI, BUSY, DC, RST, DELAY>
@@ -145,8 +160,12 @@ where
 
         // One Databyte with default value 0x03
         //  -> address: x increment, y increment, address counter is updated in x direction
-        self.interface
-            .cmd_with_data(spi, Command::DataEntryModeSetting, &[0x03])?;
+        //  y decrements instead when mirrored
+        self.interface.cmd_with_data(
+            spi,
+            Command::DataEntryModeSetting,
+            &[self.data_entry_mode()],
+        )?;
 
         self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
 
@@ -157,17 +176,54 @@ where
 
         self.wait_until_idle(spi, delay)?;
 
+        self.load_lut(spi, delay)
+    }
+
+    fn load_lut(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        let celsius = match self.refresh {
+            RefreshLut::Fast => lut::FAST_CELSIUS,
+            RefreshLut::Full => match lut::select(&FULL_LUTS, self.interface.temperature()) {
+                Waveform::Host(full) => return self.set_full_lut(spi, delay, full),
+                Waveform::Otp(celsius) => celsius,
+            },
+            RefreshLut::Quick => return self.set_full_lut(spi, delay, &LUT_PARTIAL_2IN9),
+        };
+        self.interface
+            .load_otp_waveform(spi, delay, IS_BUSY_LOW, celsius)
+    }
+
+    /// Sends a waveform of Waveshare's 159 byte layout
+    fn set_full_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: &[u8; 159],
+    ) -> Result<(), SPI::Error> {
         // set LUT by host
-        self.set_lut_helper(spi, delay, &WS_20_30[0..153])?;
+        self.set_lut_helper(spi, delay, &lut[0..153])?;
         self.interface
-            .cmd_with_data(spi, Command::WriteLutRegisterEnd, &WS_20_30[153..154])?;
+            .cmd_with_data(spi, Command::WriteLutRegisterEnd, &lut[153..154])?;
         self.interface
-            .cmd_with_data(spi, Command::GateDrivingVoltage, &WS_20_30[154..155])?;
+            .cmd_with_data(spi, Command::GateDrivingVoltage, &lut[154..155])?;
         self.interface
-            .cmd_with_data(spi, Command::SourceDrivingVoltage, &WS_20_30[155..158])?;
+            .cmd_with_data(spi, Command::SourceDrivingVoltage, &lut[155..158])?;
         self.interface
-            .cmd_with_data(spi, Command::WriteVcomRegister, &WS_20_30[158..159])?;
-
+            .cmd_with_data(spi, Command::WriteVcomRegister, &lut[158..159])?;
+
+        // Additional configuration required only for partial updates
+        if self.refresh == RefreshLut::Quick {
+            self.interface.cmd_with_data(
+                spi,
+                Command::WriteOtpSelection,
+                &[0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00],
+            )?;
+            self.interface
+                .cmd_with_data(spi, Command::BorderWaveformControl, &[0x80])?;
+            self.interface
+                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xC0])?;
+            self.interface.cmd(spi, Command::MasterActivation)?;
+            self.wait_until_idle(spi, delay)?;
+        }
         Ok(())
     }
 }
@@ -204,6 +260,7 @@ where
             interface,
             background_color: DEFAULT_BACKGROUND_COLOR,
             refresh: RefreshLut::Full,
+            mirror_y: false,
         };
 
         epd.init(spi, delay)?;
@@ -231,7 +288,17 @@ where
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        self.interface.cmd_with_data(spi, Command::WriteRam, buffer)
+        self.use_full_frame(spi, delay)?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam, buffer)?;
+
+        if self.refresh != RefreshLut::Quick {
+            // keep the old frame of the quick refresh equal to the displayed one
+            self.use_full_frame(spi, delay)?;
+            self.interface
+                .cmd_with_data(spi, Command::WriteRam2, buffer)?;
+        }
+        Ok(())
     }
 
     fn update_partial_frame(
@@ -244,22 +311,26 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        //TODO This is copied from epd2in9 but it seems not working. Partial refresh supported by version 2?
-        self.wait_until_idle(spi, delay)?;
-        self.set_ram_area(spi, x, y, x + width, y + height)?;
-        self.set_ram_counter(spi, delay, x, y)?;
+        self.set_partial_ram(spi, delay, Command::WriteRam, buffer, x, y, width, height)?;
 
-        self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
+        if self.refresh != RefreshLut::Quick {
+            // keep the old frame of the quick refresh equal to the displayed one
+            self.set_partial_ram(spi, delay, Command::WriteRam2, buffer, x, y, width, height)?;
+        }
         Ok(())
     }
 
     /// actually is the "Turn on Display" sequence
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        // Enable clock signal, Enable Analog, Load temperature value, DISPLAY with DISPLAY Mode 1, Disable Analog, Disable OSC
+        // Enable clock signal, Enable Analog, DISPLAY with DISPLAY Mode 1 (2 for the quick
+        // refresh, which copies the new frame to the old one), Disable Analog, Disable OSC
+        let control = match self.refresh {
+            RefreshLut::Full | RefreshLut::Fast => 0xC7,
+            RefreshLut::Quick => 0xCF,
+        };
         self.interface
-            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xC7])?;
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[control])?;
         self.interface.cmd(spi, Command::MasterActivation)?;
         self.wait_until_idle(spi, delay)?;
         Ok(())
@@ -282,9 +353,11 @@ where
         // clear the ram with the background color
         let color = self.background_color.get_byte_value();
 
+        self.use_full_frame(spi, delay)?;
         self.interface.cmd(spi, Command::WriteRam)?;
         self.interface
             .data_x_times(spi, color, WIDTH / 8 * HEIGHT)?;
+        self.use_full_frame(spi, delay)?;
         self.interface.cmd(spi, Command::WriteRam2)?;
         self.interface.data_x_times(spi, color, WIDTH / 8 * HEIGHT)
     }
@@ -299,14 +372,14 @@ where
 
     fn set_lut(
         &mut self,
-        _spi: &mut SPI,
-        _delay: &mut DELAY,
+        spi: &mut SPI,
+        delay: &mut DELAY,
         refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
         if let Some(refresh_lut) = refresh_rate {
             self.refresh = refresh_lut;
         }
-        Ok(())
+        self.load_lut(spi, delay)
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -331,6 +404,25 @@ where
         self.set_ram_counter(spi, delay, 0, 0)
     }
 
+    /// Writes `buffer` to the window of `ram`
+    #[allow(clippy::too_many_arguments)]
+    fn set_partial_ram(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        ram: Command,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.set_ram_area(spi, x, y, x + width - 1, y + height - 1)?;
+        self.set_ram_counter(spi, delay, x, y)?;
+        self.interface.cmd_with_data(spi, ram, buffer)
+    }
+
     fn set_ram_area(
         &mut self,
         spi: &mut SPI,
@@ -339,8 +431,9 @@ where
         end_x: u32,
         end_y: u32,
     ) -> Result<(), SPI::Error> {
-        assert!(start_x < end_x);
-        assert!(start_y < end_y);
+        assert!(start_x <= end_x);
+        assert!(start_y <= end_y);
+        let (start_y, end_y) = (self.ram_y(start_y), self.ram_y(end_y));
 
         // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
         // aren't relevant
@@ -371,10 +464,11 @@ where
         y: u32,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
+        let y = self.ram_y(y);
         // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
         // aren't relevant
         self.interface
-            .cmd_with_data(spi, Command::SetRamXAddressCounter, &[x as u8])?;
+            .cmd_with_data(spi, Command::SetRamXAddressCounter, &[(x >> 3) as u8])?;
 
         // 2 Databytes: A[7:0] & 0..A[8]
         self.interface.cmd_with_data(
@@ -385,6 +479,24 @@ where
         Ok(())
     }
 
+    /// X increment, Y increment or decrement when mirrored, address counter updated in X direction
+    fn data_entry_mode(&self) -> u8 {
+        if self.mirror_y {
+            0x01
+        } else {
+            0x03
+        }
+    }
+
+    /// RAM row of a frame row, the frame is stored bottom up when mirrored
+    fn ram_y(&self, y: u32) -> u32 {
+        if self.mirror_y {
+            HEIGHT - 1 - y
+        } else {
+            y
+        }
+    }
+
     /// Set your own LUT, this function is also used internally for set_lut
     fn set_lut_helper(
         &mut self,
@@ -400,6 +512,66 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in9<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.load_lut(spi, delay)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
+/// The custom LUT is replaced by the quick one in [`QuickRefresh::update_new_frame`].
+impl<SPI, BUSY, DC, RST, DELAY> CustomLut<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in9<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type Lut = Ssd1680Lut;
+
+    fn set_custom_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: &Ssd1680Lut,
+    ) -> Result<(), SPI::Error> {
+        self.set_lut_helper(spi, delay, lut.waveform())?;
+        if let Some(voltages) = lut.voltages() {
+            self.interface.cmd_with_data(
+                spi,
+                Command::WriteLutRegisterEnd,
+                &[voltages.end_option],
+            )?;
+            self.interface
+                .cmd_with_data(spi, Command::GateDrivingVoltage, &[voltages.gate])?;
+            self.interface
+                .cmd_with_data(spi, Command::SourceDrivingVoltage, &voltages.source)?;
+            self.interface
+                .cmd_with_data(spi, Command::WriteVcomRegister, &[voltages.vcom])?;
+        }
+        Ok(())
+    }
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
     for Epd2in9<SPI, BUSY, DC, RST, DELAY>
 where
@@ -417,12 +589,15 @@ where
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
+        self.use_full_frame(spi, delay)?;
         self.interface
             .cmd_with_data(spi, Command::WriteRam, buffer)?;
+        self.use_full_frame(spi, delay)?;
         self.interface
             .cmd_with_data(spi, Command::WriteRam2, buffer)
     }
 
+    /// Switches to the quick LUT, which stays in use until `set_lut` selects another one.
     /// To be used immediately after `update_old_frame`.
     fn update_new_frame(
         &mut self,
@@ -430,28 +605,8 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.interface.reset(delay, 10_000, 2_000);
-
-        self.set_lut_helper(spi, delay, &LUT_PARTIAL_2IN9)?;
-        self.interface.cmd_with_data(
-            spi,
-            Command::WriteOtpSelection,
-            &[0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00],
-        )?;
-        self.interface
-            .cmd_with_data(spi, Command::BorderWaveformControl, &[0x80])?;
-        self.interface
-            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xC0])?;
-        self.interface.cmd(spi, Command::MasterActivation)?;
-
-        self.wait_until_idle(spi, delay)?;
-
-        self.use_full_frame(spi, delay)?;
-
-        self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
-        Ok(())
+        self.set_lut(spi, delay, Some(RefreshLut::Quick))?;
+        self.update_frame(spi, buffer, delay)
     }
 
     /// For a quick refresh of the new updated frame. To be used immediately after `update_new_frame`
@@ -476,8 +631,6 @@ where
         Ok(())
     }
 
-    /// Partial quick refresh not supported yet
-    #[allow(unused)]
     fn update_partial_old_frame(
         &mut self,
         spi: &mut SPI,
@@ -488,12 +641,10 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        //TODO supported by display?
-        unimplemented!()
+        self.set_partial_ram(spi, delay, Command::WriteRam2, buffer, x, y, width, height)
     }
 
-    /// Partial quick refresh not supported yet
-    #[allow(unused)]
+    /// Switches to the quick LUT like `update_new_frame`
     fn update_partial_new_frame(
         &mut self,
         spi: &mut SPI,
@@ -504,12 +655,10 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        //TODO supported by display?
-        unimplemented!()
+        self.set_lut(spi, delay, Some(RefreshLut::Quick))?;
+        self.update_partial_frame(spi, delay, buffer, x, y, width, height)
     }
 
-    /// Partial quick refresh not supported yet
-    #[allow(unused)]
     fn clear_partial_frame(
         &mut self,
         spi: &mut SPI,
@@ -519,8 +668,53 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        //TODO supported by display?
-        unimplemented!()
+        let color = self.background_color.get_byte_value();
+        let rams: &[Command] = if self.refresh != RefreshLut::Quick {
+            &[Command::WriteRam, Command::WriteRam2]
+        } else {
+            &[Command::WriteRam]
+        };
+        for &ram in rams {
+            self.wait_until_idle(spi, delay)?;
+            self.set_ram_area(spi, x, y, x + width - 1, y + height - 1)?;
+            self.set_ram_counter(spi, delay, x, y)?;
+            self.interface.cmd(spi, ram)?;
+            self.interface
+                .data_x_times(spi, color, width / 8 * height)?;
+        }
+        Ok(())
+    }
+}
+
+/// Only the Y address counter can run backwards, mirroring the X direction would need the
+/// bits of each byte to be reversed as well.
+impl<SPI, BUSY, DC, RST, DELAY> HardwareMirror<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in9<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    const HORIZONTAL: bool = false;
+    const VERTICAL: bool = true;
+
+    fn set_mirror(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        _horizontal: bool,
+        vertical: bool,
+    ) -> Result<(), SPI::Error> {
+        self.mirror_y = vertical;
+        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd_with_data(
+            spi,
+            Command::DataEntryModeSetting,
+            &[self.data_entry_mode()],
+        )?;
+        self.use_full_frame(spi, delay)
     }
 }
 
@@ -534,4 +728,243 @@ mod tests {
         assert_eq!(HEIGHT, 296);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[test]
+    fn builtin_luts_round_trip() {
+        for lut in [&WS_20_30, &LUT_PARTIAL_2IN9] {
+            assert!(Ssd1680Lut::from_bytes(lut).unwrap().voltages().is_some());
+            lut::assert_round_trip(lut);
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn custom_lut() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in9::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        let mut waveform = *Ssd1680Lut::from_bytes(&WS_20_30).unwrap().waveform();
+        waveform[0] = 0x80;
+        let lut = Ssd1680Lut::new(waveform);
+        epd.set_custom_lut(&mut spi, &mut delay, &lut).unwrap();
+        assert_eq!(simulator.lut(), waveform);
+
+        epd.set_lut(&mut spi, &mut delay, None).unwrap();
+        assert_eq!(simulator.lut(), WS_20_30[..153]);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn temperature_bands() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in9::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        // the waveform of the crate is made for room temperature
+        epd.set_temperature(&mut spi, &mut delay, Some(25)).unwrap();
+        assert_eq!(simulator.lut(), WS_20_30[..153]);
+
+        // the one of the OTP is used outside of it and survives a wake up
+        epd.set_temperature(&mut spi, &mut delay, Some(0)).unwrap();
+        assert!(simulator.lut().is_empty());
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        assert!(simulator.lut().is_empty());
+        assert_eq!(epd.temperature(), Some(0));
+
+        epd.set_temperature(&mut spi, &mut delay, None).unwrap();
+        assert_eq!(simulator.lut(), WS_20_30[..153]);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn partial_frame_window() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in9::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        // 16 x 2 pixels, the window ends at the last pixel of the frame
+        epd.update_partial_frame(&mut spi, &mut delay, &[0x00; 4], 16, 10, 16, 2)
+            .unwrap();
+        let ram = simulator.ram();
+        for (x, y) in [(16, 10), (31, 10), (16, 11), (31, 11)] {
+            assert_eq!(ram.pixel(x, y), Some(Rgb888::BLACK));
+        }
+        for (x, y) in [(15, 10), (32, 10), (16, 12), (16, 9)] {
+            assert_eq!(ram.pixel(x, y), Some(Rgb888::WHITE));
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn scheduled_quick_refresh() {
+        use crate::graphics::Display;
+        use crate::scheduler::RefreshScheduler;
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use embedded_graphics::{
+            pixelcolor::{Rgb888, RgbColor},
+            prelude::*,
+        };
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let epd = Epd2in9::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut epd = RefreshScheduler::new(epd);
+
+        let mut display: Display2in9 = Display::default();
+        let mut refreshes = std::vec::Vec::new();
+        for x in 0..3 {
+            Pixel(Point::new(x * 8, 0), Color::Black)
+                .draw(&mut display)
+                .unwrap();
+            let refresh = epd
+                .update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+                .unwrap();
+            assert_eq!(
+                simulator.last_refresh(),
+                Some(match refresh {
+                    RefreshLut::Quick => Refresh::Partial,
+                    _ => Refresh::Full,
+                })
+            );
+            refreshes.push(refresh);
+        }
+        assert_eq!(
+            refreshes,
+            [RefreshLut::Full, RefreshLut::Quick, RefreshLut::Quick]
+        );
+        assert_eq!(simulator.lut(), LUT_PARTIAL_2IN9[..153]);
+        // the old frame of the first quick refresh is the one shown by the full refresh
+        for x in 0..3 {
+            assert_eq!(simulator.displayed().pixel(x * 8, 0), Some(Rgb888::BLACK));
+        }
+        assert_eq!(simulator.displayed().pixel(24, 0), Some(Rgb888::WHITE));
+
+        // the partial frames are compared with the old frame as well
+        let refresh = epd
+            .update_and_display_partial_frame(&mut spi, &mut delay, &[0x00; 2], 64, 8, 8, 2)
+            .unwrap();
+        assert_eq!(refresh, RefreshLut::Quick);
+        assert_eq!(simulator.displayed().pixel(64, 9), Some(Rgb888::BLACK));
+        assert_eq!(simulator.second_ram().pixel(64, 9), Some(Rgb888::BLACK));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn quick_refresh_partial_frames() {
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in9::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        epd.clear_frame(&mut spi, &mut delay).unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+
+        epd.update_partial_old_frame(&mut spi, &mut delay, &[0x00; 2], 8, 4, 8, 2)
+            .unwrap();
+        epd.update_partial_new_frame(&mut spi, &mut delay, &[0xFF; 2], 8, 4, 8, 2)
+            .unwrap();
+        assert_eq!(simulator.second_ram().pixel(8, 4), Some(Rgb888::BLACK));
+        assert_eq!(simulator.ram().pixel(8, 4), Some(Rgb888::WHITE));
+
+        epd.set_background_color(Color::Black);
+        epd.clear_partial_frame(&mut spi, &mut delay, 16, 4, 8, 2)
+            .unwrap();
+        epd.display_new_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+        assert_eq!(simulator.displayed().pixel(16, 5), Some(Rgb888::BLACK));
+        // only the new frame is cleared in quick mode
+        assert_eq!(simulator.ram().pixel(16, 5), Some(Rgb888::BLACK));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fast_refresh() {
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd2in9::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        // the waveform of the OTP is loaded for the forced high temperature
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Fast))
+            .unwrap();
+        assert_eq!(
+            simulator.register(0x1A),
+            Some(std::vec![lut::FAST_CELSIUS as u8, 0x00])
+        );
+        assert_eq!(simulator.register(0x22), Some(std::vec![0x91]));
+        assert!(simulator.lut().is_empty());
+
+        // and refreshes in display mode 1 like the full LUT
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xC7]));
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        assert_eq!(simulator.lut(), WS_20_30[..153]);
+    }
 }
@@ -8,7 +8,7 @@ use crate::{
     buffer_len,
     color::TriColor,
     interface::DisplayInterface,
-    traits::{InternalWiAdditions, WaveshareDisplay, WaveshareThreeColorDisplay},
+    traits::{InternalWiAdditions, Temperature, WaveshareDisplay, WaveshareThreeColorDisplay},
 };
 use embedded_hal::{
     delay::DelayNs,
@@ -38,6 +38,7 @@ pub type Display2in9b = crate::graphics::Display<
     true,
     { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
     TriColor,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd2in9b (v4) driver
@@ -117,10 +118,10 @@ where
         self.command(spi, Command::TurnOnDisplay)?;
 
         let data = match mode {
-            DisplayMode::Default => 0xf7,
+            DisplayMode::Default => self.interface.ssd168x_update_control(0xf7),
             DisplayMode::Partial => 0x1c,
             DisplayMode::Fast => 0xc7,
-            DisplayMode::Base => 0xf4,
+            DisplayMode::Base => self.interface.ssd168x_update_control(0xf4),
         };
 
         self.send_data(spi, &[data])?;
@@ -176,6 +177,8 @@ where
 
         self.command(spi, Command::ReadBuiltInTemperatureSensor)?;
         self.send_data(spi, &[0x80])?;
+        let celsius = self.interface.temperature();
+        self.interface.cmd_temperature_register(spi, celsius)?;
 
         self.command(spi, Command::RamXAddressCount)?;
         self.send_data(spi, &[0x00])?; // set RAM x address count to 0
@@ -243,6 +246,7 @@ where
     DELAY: DelayNs,
 {
     type DisplayColor = TriColor;
+    const CLEAR_FRAME_REFRESHES: bool = true;
 
     fn new(
         spi: &mut SPI,
@@ -393,11 +397,18 @@ where
     fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
         const SIZE: u32 = WIDTH / 8 * HEIGHT;
 
+        let red = if self.background_color == TriColor::Chromatic {
+            0xff
+        } else {
+            0
+        };
+
         self.command(spi, Command::WriteBlackData)?;
-        self.interface.data_x_times(spi, 0xff, SIZE)?;
+        self.interface
+            .data_x_times(spi, self.background_color.get_byte_value(), SIZE)?;
 
         self.command(spi, Command::WriteRedData)?;
-        self.interface.data_x_times(spi, 0, SIZE)?;
+        self.interface.data_x_times(spi, red, SIZE)?;
 
         self.display_frame(spi, delay)?;
         Ok(())
@@ -417,3 +428,30 @@ where
         Ok(())
     }
 }
+
+/// The full refreshes take the waveform of the OTP for the temperature of the panel, the
+/// partial and the fast ones keep the waveform of the last full refresh.
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in9b<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd_temperature_register(spi, celsius)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
@@ -14,7 +14,7 @@ use embedded_hal::{
 };
 
 use crate::interface::DisplayInterface;
-use crate::traits::{InternalWiAdditions, RefreshLut, WaveshareDisplay};
+use crate::traits::{InternalWiAdditions, QuickRefresh, RefreshLut, Temperature, WaveshareDisplay};
 
 //The Lookup Tables for the Display
 mod constants;
@@ -28,7 +28,7 @@ pub const HEIGHT: u32 = 296;
 /// WIDTH / 8 * HEIGHT
 pub const EPD_ARRAY: u32 = 4736;
 /// Default Background Color (white)
-pub const DEFAULT_BACKGROUND_COLOR: Color = Color::Black;
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
 const IS_BUSY_LOW: bool = false;
 const SINGLE_BYTE_WRITE: bool = true;
 
@@ -46,6 +46,7 @@ pub type Display2in9d = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd2in9d driver
@@ -80,6 +81,8 @@ where
         //LUT from OTP，KW-BF   KWR-AF	BWROTP 0f	BWOTP 1f
         self.interface
             .cmd_with_data(spi, Command::PanelSetting, &[0x1f, 0x0D])?;
+        let celsius = self.interface.temperature();
+        self.interface.cmd_forced_temperature(spi, celsius)?;
 
         //resolution setting
         self.interface
@@ -106,6 +109,7 @@ where
     DELAY: DelayNs,
 {
     type DisplayColor = Color;
+    const CLEAR_FRAME_REFRESHES: bool = true;
     fn new(
         spi: &mut SPI,
         busy: BUSY,
@@ -253,8 +257,9 @@ where
         self.interface.cmd(spi, Command::DataStartTransmission1)?;
         self.interface.data_x_times(spi, 0x00, EPD_ARRAY)?;
 
+        let color_value = self.color.get_byte_value();
         self.interface.cmd(spi, Command::DataStartTransmission2)?;
-        self.interface.data_x_times(spi, 0xFF, EPD_ARRAY)?;
+        self.interface.data_x_times(spi, color_value, EPD_ARRAY)?;
 
         self.display_frame(spi, delay)?;
 
@@ -371,6 +376,41 @@ where
         Ok(())
     }
 
+    /// Start a partial update of the given window, initialising partial refreshes if needed
+    fn start_partial(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        if !self.is_partial_refresh {
+            // Initialize only on first call
+            self.set_part_reg(spi, delay)?;
+            self.is_partial_refresh = true;
+        }
+        self.interface.cmd(spi, Command::PartialIn)?;
+
+        // x is positioned in bytes, the last 3 bits are ignored
+        let x_end = (x + width - 1) | 0x07;
+        let y_end = y + height - 1;
+        self.interface.cmd_with_data(
+            spi,
+            Command::PartialWindow,
+            &[
+                (x & 0xF8) as u8,
+                x_end as u8,
+                (y >> 8) as u8,
+                y as u8,
+                (y_end >> 8) as u8,
+                y_end as u8,
+                0x28,
+            ],
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn set_lut_helper(
         &mut self,
@@ -405,3 +445,131 @@ where
         Ok(())
     }
 }
+
+/// Quick refreshes use the partial refresh waveform and the old frame data sent here,
+/// instead of the old data borrowed by [`update_partial_frame`](WaveshareDisplay::update_partial_frame).
+/// The full refreshes take the waveform of the OTP for the temperature of the panel, the
+/// partial ones drive it with the LUTs of the registers at any temperature.
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in9d<'_, SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd_forced_temperature(spi, celsius)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in9d<'_, SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// To be followed immediately by `update_new_frame`.
+    fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_partial_old_frame(spi, delay, buffer, 0, 0, WIDTH, HEIGHT)
+    }
+
+    /// To be used immediately after `update_old_frame`.
+    fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_partial_new_frame(spi, delay, buffer, 0, 0, WIDTH, HEIGHT)
+    }
+
+    fn display_new_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.display_frame(spi, delay)
+    }
+
+    fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_new_frame(spi, buffer, delay)?;
+        self.display_frame(spi, delay)
+    }
+
+    /// To be followed immediately by `update_partial_new_frame` with the same window.
+    fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.start_partial(spi, delay, x, y, width, height)?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+    }
+
+    /// To be used immediately after `update_partial_old_frame`.
+    fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+    }
+
+    fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.start_partial(spi, delay, x, y, width, height)?;
+
+        let color_value = self.color.get_byte_value();
+        let len = ((x + width - 1) / 8 + 1 - x / 8) * height;
+
+        self.interface.cmd(spi, Command::DataStartTransmission1)?;
+        self.interface.data_x_times(spi, color_value, len)?;
+
+        self.interface.cmd(spi, Command::DataStartTransmission2)?;
+        self.interface.data_x_times(spi, color_value, len)
+    }
+}
@@ -0,0 +1,237 @@
+//! A simple Driver for the Waveshare 3inch e-Paper (G) Display via SPI
+//!
+//! The panel shows black, white, yellow and red with 2 bits per pixel, see [`QuadColor`].
+//!
+//! # References
+//!
+//! - [Datasheet](https://www.waveshare.com/wiki/3inch_e-Paper_Module_(G)_Manual)
+//! - [Waveshare C driver](https://github.com/waveshareteam/e-Paper/blob/master/RaspberryPi_JetsonNano/c/lib/e-Paper/EPD_3in0g.c)
+//! - [Waveshare Python driver](https://github.com/waveshareteam/e-Paper/blob/master/RaspberryPi_JetsonNano/python/lib/waveshare_epd/epd3in0g.py)
+
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+    spi::SpiDevice,
+};
+
+#[cfg(feature = "graphics")]
+use crate::buffer_len;
+use crate::{
+    color::QuadColor,
+    rect::Rect,
+    traits::{InternalWiAdditions, WaveshareDisplay},
+    type_g::{command::Command, QuadColorPanel},
+};
+
+/// Full size buffer for use with the 3in0g EPD
+#[cfg(feature = "graphics")]
+pub type Display3in0g = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
+    QuadColor,
+    {
+        QuadColor::colors_byte(
+            DEFAULT_BACKGROUND_COLOR,
+            DEFAULT_BACKGROUND_COLOR,
+            DEFAULT_BACKGROUND_COLOR,
+            DEFAULT_BACKGROUND_COLOR,
+        ) as u16
+    },
+>;
+
+/// Width of the display
+pub const WIDTH: u32 = 168;
+/// Height of the display
+pub const HEIGHT: u32 = 400;
+/// Default Background Color
+pub const DEFAULT_BACKGROUND_COLOR: QuadColor = QuadColor::White;
+
+/// Epd3in0g driver
+pub struct Epd3in0g<SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface and Background Color
+    panel: QuadColorPanel<SPI, BUSY, DC, RST, DELAY, WIDTH, HEIGHT>,
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Epd3in0g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.reset(delay);
+
+        self.cmd_with_data(spi, Command::Ox66, &[0x49, 0x55, 0x13, 0x5D, 0x05, 0x10])?;
+        self.cmd_with_data(spi, Command::OxB0, &[0x00])?;
+        self.cmd_with_data(spi, Command::PowerSetting, &[0x0F, 0x00])?;
+        self.cmd_with_data(spi, Command::PanelSetting, &[0x4F, 0x6B])?;
+        self.cmd_with_data(spi, Command::BoosterSoftStart2, &[0xD7, 0xDE, 0x12])?;
+        self.panel.set_resolution(spi)?;
+        self.cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x37])?;
+        self.cmd_with_data(spi, Command::TconSetting, &[0x0C, 0x05])?;
+        self.cmd_with_data(spi, Command::PowerSaving, &[0xFF])?;
+        self.cmd_with_data(spi, Command::Ox84, &[0x00])?;
+
+        self.panel.power_on(spi, delay)
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd3in0g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = QuadColor;
+    const CLEAR_FRAME_REFRESHES: bool = true;
+
+    fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, <SPI>::Error>
+    where
+        Self: Sized,
+    {
+        let panel = QuadColorPanel::new(busy, dc, rst, delay_us, DEFAULT_BACKGROUND_COLOR);
+
+        let mut epd = Epd3in0g { panel };
+
+        epd.init(spi, delay)?;
+
+        Ok(epd)
+    }
+
+    fn sleep(&mut self, spi: &mut SPI, _delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.sleep(spi)
+    }
+
+    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.init(spi, delay)
+    }
+
+    fn set_background_color(&mut self, color: Self::DisplayColor) {
+        self.panel.color = color;
+    }
+
+    fn background_color(&self) -> &Self::DisplayColor {
+        &self.panel.color
+    }
+
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), <SPI>::Error> {
+        self.panel.update_frame(spi, buffer, delay)
+    }
+
+    fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), <SPI>::Error> {
+        self.panel
+            .update_partial_frame(spi, delay, buffer, Rect::new(x, y, width, height))
+    }
+
+    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.display_frame(spi, delay)
+    }
+
+    fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), <SPI>::Error> {
+        self.update_frame(spi, buffer, delay)?;
+        self.display_frame(spi, delay)
+    }
+
+    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.clear_frame(spi, delay)
+    }
+
+    fn set_lut(
+        &mut self,
+        _spi: &mut SPI,
+        _delay: &mut DELAY,
+        _refresh_rate: Option<crate::traits::RefreshLut>,
+    ) -> Result<(), <SPI>::Error> {
+        Ok(())
+    }
+
+    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.wait_busy_low(delay);
+        Ok(())
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> Epd3in0g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn cmd_with_data(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+        data: &[u8],
+    ) -> Result<(), SPI::Error> {
+        self.panel.cmd_with_data(spi, command, data)
+    }
+
+    /// Show 4 stripes of color, used for quick testing
+    pub fn show_4stripes(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.panel.show_4stripes(spi, delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 168);
+        assert_eq!(HEIGHT, 400);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, QuadColor::White);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn display_default_is_white() {
+        let display = Display3in0g::default();
+        assert_eq!(display.buffer().len(), WIDTH as usize * HEIGHT as usize / 4);
+        assert!(display.buffer().iter().all(|&byte| byte == 0x55));
+    }
+}
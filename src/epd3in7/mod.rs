@@ -17,7 +17,11 @@ use self::constants::*;
 use crate::buffer_len;
 use crate::color::Color;
 use crate::interface::DisplayInterface;
-use crate::traits::{InternalWiAdditions, RefreshLut, WaveshareDisplay};
+use crate::lut::{self, LutBand, Ssd1677Lut, Waveform};
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
+use crate::traits::{
+    CustomLut, InternalWiAdditions, QuickRefresh, RefreshLut, Temperature, WaveshareDisplay,
+};
 
 /// Width of the display.
 pub const WIDTH: u32 = 280;
@@ -32,6 +36,16 @@ const IS_BUSY_LOW: bool = false;
 
 const SINGLE_BYTE_WRITE: bool = true;
 
+/// Grey clear (GC) waveforms by temperature, Waveshare only publishes the one for room
+/// temperature
+const GC_LUTS: [LutBand<&[u8]>; 1] = [LutBand {
+    celsius: lut::HOST_LUT_CELSIUS,
+    lut: &LUT_1GRAY_GC,
+}];
+
+/// Update sequence of the refreshes, it drives the panel with the loaded LUT
+const DISPLAY_SEQUENCE: u8 = 0xCF;
+
 /// Display with Fullsize buffer for use with the 3in7 EPD
 #[cfg(feature = "graphics")]
 pub type Display3in7 = crate::graphics::Display<
@@ -40,6 +54,7 @@ pub type Display3in7 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// EPD3in7 driver
@@ -48,6 +63,13 @@ pub struct EPD3in7<SPI, BUSY, DC, RST, DELAY> {
     interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
     /// Background Color
     background_color: Color,
+    /// Refresh LUT
+    refresh: RefreshLut,
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for EPD3in7<SPI, BUSY, DC, RST, DELAY> {
+    // the quick refresh is a direct update (DU) which ghosts more than the partial LUTs
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 5);
 }
 
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
@@ -104,21 +126,15 @@ where
             &[0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0x4F, 0xFF, 0xFF, 0xFF, 0xFF],
         )?;
 
+        self.set_ram_area(spi, 0, 0, WIDTH, HEIGHT)?;
+
         self.interface.cmd_with_data(
             spi,
-            Command::SetRamXAddressStartEndPosition,
-            &[0x00, 0x00, 0x17, 0x01],
-        )?;
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamYAddressStartEndPosition,
-            &[0x00, 0x00, 0xDF, 0x01],
+            Command::DisplayUpdateSequenceSetting,
+            &[DISPLAY_SEQUENCE],
         )?;
 
-        self.interface
-            .cmd_with_data(spi, Command::DisplayUpdateSequenceSetting, &[0xCF])?;
-
-        self.set_lut(spi, delay, Some(RefreshLut::Full))?;
+        self.set_lut(spi, delay, None)?;
         Ok(())
     }
 }
@@ -145,6 +161,7 @@ where
         let mut epd = EPD3in7 {
             interface: DisplayInterface::new(busy, dc, rst, delay_us),
             background_color: DEFAULT_BACKGROUND_COLOR,
+            refresh: RefreshLut::Full,
         };
 
         epd.init(spi, delay)?;
@@ -186,10 +203,7 @@ where
         _delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
         assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
-        self.interface
-            .cmd_with_data(spi, Command::SetRamXAddressCounter, &[0x00, 0x00])?;
-        self.interface
-            .cmd_with_data(spi, Command::SetRamYAddressCounter, &[0x00, 0x00])?;
+        self.set_ram_area(spi, 0, 0, WIDTH, HEIGHT)?;
 
         self.interface
             .cmd_with_data(spi, Command::WriteRam, buffer)?;
@@ -197,18 +211,22 @@ where
         Ok(())
     }
 
-    #[allow(unused)]
+    /// Writes the window into the frame, `x` and `width` have to be multiples of 8
+    ///
+    /// `display_frame` refreshes the whole screen with the LUT chosen by `set_lut`, the direct
+    /// update LUT of [`RefreshLut::Quick`] only drives the changed pixels.
     fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
-        delay: &mut DELAY,
+        _delay: &mut DELAY,
         buffer: &[u8],
         x: u32,
         y: u32,
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        todo!()
+        self.set_ram_area(spi, x, y, width, height)?;
+        self.interface.cmd_with_data(spi, Command::WriteRam, buffer)
     }
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -231,27 +249,48 @@ where
     }
 
     fn clear_frame(&mut self, spi: &mut SPI, _delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface
-            .cmd_with_data(spi, Command::SetRamXAddressCounter, &[0x00, 0x00])?;
-        self.interface
-            .cmd_with_data(spi, Command::SetRamYAddressCounter, &[0x00, 0x00])?;
+        self.set_ram_area(spi, 0, 0, WIDTH, HEIGHT)?;
 
         let color = self.background_color.get_byte_value();
         self.interface.cmd(spi, Command::WriteRam)?;
-        self.interface.data_x_times(spi, color, WIDTH * HEIGHT)?;
+        self.interface.data_x_times(
+            spi,
+            color,
+            buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
+        )?;
 
         Ok(())
     }
 
+    /// The grey clear (GC) LUT for full refreshes or the direct update (DU) LUT for quick ones
+    ///
+    /// Full refreshes of a panel outside of the bands of the GC LUT take the waveform of the
+    /// OTP for its temperature instead.
     fn set_lut(
         &mut self,
         spi: &mut SPI,
-        _delay: &mut DELAY,
+        delay: &mut DELAY,
         refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        let buffer = match refresh_rate {
-            Some(RefreshLut::Full) | None => &LUT_1GRAY_GC,
-            Some(RefreshLut::Quick) => &LUT_1GRAY_DU,
+        if let Some(refresh_lut) = refresh_rate {
+            self.refresh = refresh_lut;
+        }
+        let buffer = match (
+            self.refresh,
+            lut::select(&GC_LUTS, self.interface.temperature()),
+        ) {
+            (RefreshLut::Quick, _) => &LUT_1GRAY_DU,
+            (RefreshLut::Full | RefreshLut::Fast, Waveform::Host(gc)) => gc,
+            (RefreshLut::Full | RefreshLut::Fast, Waveform::Otp(celsius)) => {
+                self.interface
+                    .load_otp_waveform(spi, delay, IS_BUSY_LOW, celsius)?;
+                // the loading replaced the update sequence of the refreshes
+                return self.interface.cmd_with_data(
+                    spi,
+                    Command::DisplayUpdateSequenceSetting,
+                    &[DISPLAY_SEQUENCE],
+                );
+            }
         };
 
         self.interface
@@ -264,3 +303,334 @@ where
         Ok(())
     }
 }
+
+impl<SPI, BUSY, DC, RST, DELAY> EPD3in7<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sets the RAM window and moves the address counters to its start, the x addresses of
+    /// this controller are in pixels
+    fn set_ram_area(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        let (x_end, y_end) = (x + width - 1, y + height - 1);
+        self.interface.cmd_with_data(
+            spi,
+            Command::SetRamXAddressStartEndPosition,
+            &[x as u8, (x >> 8) as u8, x_end as u8, (x_end >> 8) as u8],
+        )?;
+        self.interface.cmd_with_data(
+            spi,
+            Command::SetRamYAddressStartEndPosition,
+            &[y as u8, (y >> 8) as u8, y_end as u8, (y_end >> 8) as u8],
+        )?;
+
+        self.interface.cmd_with_data(
+            spi,
+            Command::SetRamXAddressCounter,
+            &[x as u8, (x >> 8) as u8],
+        )?;
+        self.interface.cmd_with_data(
+            spi,
+            Command::SetRamYAddressCounter,
+            &[y as u8, (y >> 8) as u8],
+        )
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> CustomLut<SPI, BUSY, DC, RST, DELAY>
+    for EPD3in7<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type Lut = Ssd1677Lut;
+
+    fn set_custom_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: &Ssd1677Lut,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteLutRegister, lut.waveform())
+    }
+}
+
+/// Only the full refreshes depend on the temperature, the direct update (DU) LUT of the quick
+/// ones is used at any temperature.
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for EPD3in7<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.set_lut(spi, delay, None)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
+/// Quick refreshes with the direct update (DU) LUT, select it with
+/// [`set_lut`](WaveshareDisplay::set_lut) and [`RefreshLut::Quick`] first.
+impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
+    for EPD3in7<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// To be followed immediately by `update_new_frame`.
+    fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        _delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
+        self.set_ram_area(spi, 0, 0, WIDTH, HEIGHT)?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam2, buffer)
+    }
+
+    /// To be used immediately after `update_old_frame`.
+    fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_frame(spi, buffer, delay)
+    }
+
+    fn display_new_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.display_frame(spi, delay)
+    }
+
+    /// To be used immediately after `update_old_frame`.
+    fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_frame(spi, buffer, delay)?;
+        self.display_frame(spi, delay)
+    }
+
+    fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        _delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.set_ram_area(spi, x, y, width, height)?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam2, buffer)
+    }
+
+    /// Always call `update_partial_old_frame` before this, with buffer-updating code
+    /// between the calls.
+    fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.update_partial_frame(spi, delay, buffer, x, y, width, height)
+    }
+
+    fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        _delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        // only the new frame, the old one has to keep what the panel shows for the direct
+        // update LUT to drive the cleared pixels
+        let color = self.background_color.get_byte_value();
+        self.set_ram_area(spi, x, y, width, height)?;
+        self.interface.cmd(spi, Command::WriteRam)?;
+        self.interface.data_x_times(spi, color, width / 8 * height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 280);
+        assert_eq!(HEIGHT, 480);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn partial_frame_window() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd1677, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = EPD3in7::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        // 16 x 2 pixels past the first 256 pixels, the x addresses take two bytes
+        epd.update_partial_frame(&mut spi, &mut delay, &[0x00; 4], 256, 10, 16, 2)
+            .unwrap();
+        let ram = simulator.ram();
+        for (x, y) in [(256, 10), (271, 10), (256, 11), (271, 11)] {
+            assert_eq!(ram.pixel(x, y), Some(Rgb888::BLACK));
+        }
+        for (x, y) in [(255, 10), (272, 10), (256, 12), (256, 9), (0, 10)] {
+            assert_eq!(ram.pixel(x, y), Some(Rgb888::WHITE));
+        }
+
+        // the whole frame again, up to the last column
+        let mut buffer = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+        buffer[buffer.len() - 1] = 0xFE;
+        epd.update_frame(&mut spi, &buffer, &mut delay).unwrap();
+        let ram = simulator.ram();
+        assert_eq!(ram.pixel(WIDTH - 1, HEIGHT - 1), Some(Rgb888::BLACK));
+        assert_eq!(ram.pixel(256, 10), Some(Rgb888::WHITE));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn quick_refresh() {
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd1677, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = EPD3in7::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        epd.clear_frame(&mut spi, &mut delay).unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+
+        // the direct update only drives the changed pixels
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))
+            .unwrap();
+        epd.update_partial_frame(&mut spi, &mut delay, &[0x00; 2], 8, 4, 8, 2)
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+        assert_eq!(simulator.displayed().pixel(8, 5), Some(Rgb888::BLACK));
+        assert_eq!(simulator.ghosting(8, 5), Some(1));
+        assert_eq!(simulator.ghosting(16, 5), Some(0));
+
+        // the cleared window differs from the old frame, so it is driven back to white
+        epd.clear_partial_frame(&mut spi, &mut delay, 8, 4, 8, 2)
+            .unwrap();
+        epd.display_new_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+        assert_eq!(simulator.displayed().pixel(8, 5), Some(Rgb888::WHITE));
+        assert_eq!(simulator.ghosting(8, 5), Some(2));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+        assert_eq!(simulator.max_ghosting(), 0);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn temperature_bands() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Ssd1677, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = EPD3in7::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        epd.set_temperature(&mut spi, &mut delay, Some(25)).unwrap();
+        assert_eq!(simulator.lut(), LUT_1GRAY_GC);
+
+        // the waveform of the OTP is loaded outside of the band, also after a wake up, and the
+        // refreshes keep it
+        epd.set_temperature(&mut spi, &mut delay, Some(-10))
+            .unwrap();
+        assert!(simulator.lut().is_empty());
+        assert_eq!(simulator.register(0x1A), Some(std::vec![0xF6, 0x00]));
+        assert_eq!(simulator.register(0x22), Some(std::vec![0xCF]));
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        assert!(simulator.lut().is_empty());
+        assert_eq!(epd.temperature(), Some(-10));
+
+        // the direct update is used at any temperature
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))
+            .unwrap();
+        assert_eq!(simulator.lut(), LUT_1GRAY_DU);
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        epd.set_temperature(&mut spi, &mut delay, None).unwrap();
+        assert_eq!(simulator.lut(), LUT_1GRAY_GC);
+    }
+}
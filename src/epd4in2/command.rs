@@ -152,6 +152,11 @@ pub(crate) enum Command {
     /// from positive to negative, the power saving mechanism will be activated. The active period width is defined by the following two
     /// parameters.
     PowerSaving = 0xE3,
+    /// Cascade setting, TSFIX (bit 1) uses the temperature of `ForceTemperature` instead of
+    /// the sensor
+    CascadeSetting = 0xE0,
+    /// Temperature used with TSFIX, it selects the waveform from the OTP
+    ForceTemperature = 0xE5,
 }
 
 impl traits::Command for Command {
@@ -52,7 +52,12 @@
 use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
 
 use crate::interface::DisplayInterface;
-use crate::traits::{InternalWiAdditions, QuickRefresh, RefreshLut, WaveshareDisplay};
+use crate::lut::{self, LutBand, Uc8176Lut, Waveform};
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
+use crate::traits::{
+    CustomLut, HardwareMirror, InternalWiAdditions, QuickRefresh, RefreshLut, Temperature,
+    WaveshareDisplay,
+};
 
 //The Lookup Tables for the Display
 mod constants;
@@ -73,6 +78,13 @@ pub(crate) mod command;
 use self::command::Command;
 use crate::buffer_len;
 
+/// Full waveforms by temperature (VCOM, WW, BW, WB and BB), Waveshare only publishes the one for
+/// room temperature
+const FULL_LUTS: [LutBand<[&[u8]; 5]>; 1] = [LutBand {
+    celsius: lut::HOST_LUT_CELSIUS,
+    lut: [&LUT_VCOM0, &LUT_WW, &LUT_BW, &LUT_WB, &LUT_BB],
+}];
+
 /// Full size buffer for use with the 4in2 EPD
 #[cfg(feature = "graphics")]
 pub type Display4in2 = crate::graphics::Display<
@@ -81,6 +93,7 @@ pub type Display4in2 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Epd4in2 driver
@@ -92,6 +105,17 @@ pub struct Epd4in2<SPI, BUSY, DC, RST, DELAY> {
     color: Color,
     /// Refresh LUT
     refresh: RefreshLut,
+    /// Source (left/right) scan direction reversed
+    mirror_x: bool,
+    /// Gate (top/bottom) scan direction reversed
+    mirror_y: bool,
+    /// A LUT of [`CustomLut::set_custom_lut`] is loaded, it is used at any temperature
+    custom_lut: bool,
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd4in2<SPI, BUSY, DC, RST, DELAY> {
+    // the quick LUT leaves a lot of ghosting on this panel
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 5);
 }
 
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
@@ -124,7 +148,7 @@ where
         self.wait_until_idle(spi, delay)?;
 
         // set the panel settings
-        self.cmd_with_data(spi, Command::PanelSetting, &[0x3F])?;
+        self.cmd_with_data(spi, Command::PanelSetting, &[self.panel_setting()])?;
 
         // Set Frequency, 200 Hz didn't work on my board
         // 150Hz and 171Hz wasn't tested yet
@@ -173,6 +197,9 @@ where
             interface,
             color,
             refresh: RefreshLut::Full,
+            mirror_x: false,
+            mirror_y: false,
+            custom_lut: false,
         };
 
         epd.init(spi, delay)?;
@@ -326,11 +353,16 @@ where
         if let Some(refresh_lut) = refresh_rate {
             self.refresh = refresh_lut;
         }
-        match self.refresh {
-            RefreshLut::Full => {
-                self.set_lut_helper(spi, delay, &LUT_VCOM0, &LUT_WW, &LUT_BW, &LUT_WB, &LUT_BB)
+        self.custom_lut = false;
+        match (self.refresh, self.full_lut()) {
+            (RefreshLut::Full | RefreshLut::Fast, Waveform::Host([vcom, ww, bw, wb, bb])) => {
+                self.set_lut_helper(spi, delay, vcom, ww, bw, wb, bb)
             }
-            RefreshLut::Quick => self.set_lut_helper(
+            // the panel setting switches to the LUT of the OTP
+            (RefreshLut::Full | RefreshLut::Fast, Waveform::Otp(_)) => {
+                self.wait_until_idle(spi, delay)
+            }
+            (RefreshLut::Quick, _) => self.set_lut_helper(
                 spi,
                 delay,
                 &LUT_VCOM0_QUICK,
@@ -339,7 +371,11 @@ where
                 &LUT_WB_QUICK,
                 &LUT_BB_QUICK,
             ),
-        }
+        }?;
+        // the temperature decides between these LUTs and the ones of the OTP
+        self.cmd_with_data(spi, Command::PanelSetting, &[self.panel_setting()])?;
+        let celsius = self.interface.temperature();
+        self.interface.cmd_forced_temperature(spi, celsius)
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -373,6 +409,32 @@ where
         self.interface.cmd_with_data(spi, command, data)
     }
 
+    /// Panel setting with LUTs from register, UD (bit 3) and SHL (bit 2) choose the scan directions
+    ///
+    /// Full refreshes of a panel outside of the bands of [`FULL_LUTS`] take the LUT of the OTP
+    /// for its temperature instead (REG_EN, bit 5), unless a custom LUT is loaded.
+    fn panel_setting(&self) -> u8 {
+        let mut value = 0x3F;
+        if !self.custom_lut
+            && self.refresh != RefreshLut::Quick
+            && matches!(self.full_lut(), Waveform::Otp(_))
+        {
+            value &= !0x20;
+        }
+        if self.mirror_x {
+            value &= !0x04;
+        }
+        if self.mirror_y {
+            value &= !0x08;
+        }
+        value
+    }
+
+    /// Full waveform for the temperature of the panel
+    fn full_lut(&self) -> Waveform<[&'static [u8]; 5]> {
+        lut::select(&FULL_LUTS, self.interface.temperature())
+    }
+
     fn send_resolution(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
         let w = self.width();
         let h = self.height();
@@ -442,6 +504,60 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd4in2<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        if self.custom_lut {
+            // the custom LUT stays loaded, only the waveform of the OTP depends on the band
+            self.wait_until_idle(spi, delay)?;
+            self.interface.cmd_forced_temperature(spi, celsius)
+        } else {
+            self.set_lut(spi, delay, None)
+        }
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> CustomLut<SPI, BUSY, DC, RST, DELAY>
+    for Epd4in2<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type Lut = Uc8176Lut;
+
+    fn set_custom_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: &Uc8176Lut,
+    ) -> Result<(), SPI::Error> {
+        self.set_lut_helper(spi, delay, &lut.vcom, &lut.ww, &lut.bw, &lut.wb, &lut.bb)?;
+        // LUTs from register, whatever the temperature
+        self.custom_lut = true;
+        self.cmd_with_data(spi, Command::PanelSetting, &[self.panel_setting()])
+    }
+}
+
 impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
     for Epd4in2<SPI, BUSY, DC, RST, DELAY>
 where
@@ -551,6 +667,7 @@ where
             //return Err("Wrong buffersize");
         }
 
+        self.interface.cmd(spi, Command::PartialWindow)?;
         self.shift_display(spi, x, y, width, height)?;
 
         self.interface.cmd(spi, Command::DataStartTransmission2)?;
@@ -593,6 +710,32 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> HardwareMirror<SPI, BUSY, DC, RST, DELAY>
+    for Epd4in2<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    const HORIZONTAL: bool = true;
+    const VERTICAL: bool = true;
+
+    fn set_mirror(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        horizontal: bool,
+        vertical: bool,
+    ) -> Result<(), SPI::Error> {
+        self.mirror_x = horizontal;
+        self.mirror_y = vertical;
+        self.wait_until_idle(spi, delay)?;
+        self.cmd_with_data(spi, Command::PanelSetting, &[self.panel_setting()])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -603,4 +746,75 @@ mod tests {
         assert_eq!(HEIGHT, 300);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn custom_lut() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd4in2::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        let mut vcom = LUT_VCOM0;
+        vcom[1] = 0x10;
+        let lut = Uc8176Lut::from_bytes(&vcom, &LUT_WW, &LUT_BW, &LUT_WB, &LUT_BB).unwrap();
+        epd.set_custom_lut(&mut spi, &mut delay, &lut).unwrap();
+        assert_eq!(simulator.lut(), vcom);
+
+        // outside of the band of the built-in LUTs the custom one is still taken from the registers
+        epd.set_temperature(&mut spi, &mut delay, Some(40)).unwrap();
+        assert_eq!(simulator.lut(), vcom);
+        assert_eq!(simulator.register(0x00), Some(std::vec![0x3F]));
+        epd.set_mirror(&mut spi, &mut delay, true, false).unwrap();
+        assert_eq!(simulator.register(0x00), Some(std::vec![0x3B]));
+
+        // the OTP takes over outside of the band again, and the LUT of the band replaces it
+        // within
+        epd.set_lut(&mut spi, &mut delay, None).unwrap();
+        assert_eq!(simulator.register(0x00), Some(std::vec![0x1B]));
+        epd.set_temperature(&mut spi, &mut delay, Some(25)).unwrap();
+        assert_eq!(simulator.lut(), LUT_VCOM0);
+        assert_eq!(simulator.register(0x00), Some(std::vec![0x3B]));
+        assert_eq!(simulator.register(0xE5), Some(std::vec![25]));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn panel_setting() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd4in2::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        // LUTs from register, scanning up and to the right as before
+        assert_eq!(simulator.register(0x00), Some(std::vec![0x3F]));
+
+        epd.set_mirror(&mut spi, &mut delay, true, false).unwrap();
+        assert_eq!(simulator.register(0x00), Some(std::vec![0x3B]));
+        epd.set_mirror(&mut spi, &mut delay, false, true).unwrap();
+        assert_eq!(simulator.register(0x00), Some(std::vec![0x37]));
+
+        // the mirroring survives the init of a wake up
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.register(0x00), Some(std::vec![0x37]));
+    }
 }
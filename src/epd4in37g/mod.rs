@@ -0,0 +1,245 @@
+//! A simple Driver for the Waveshare 4.37inch e-Paper (G) Display via SPI
+//!
+//! The panel shows black, white, yellow and red with 2 bits per pixel, see [`QuadColor`].
+//!
+//! # References
+//!
+//! - [Datasheet](https://www.waveshare.com/wiki/4.37inch_e-Paper_Module_(G)_Manual)
+//! - [Waveshare C driver](https://github.com/waveshareteam/e-Paper/blob/master/RaspberryPi_JetsonNano/c/lib/e-Paper/EPD_4in37g.c)
+//! - [Waveshare Python driver](https://github.com/waveshareteam/e-Paper/blob/master/RaspberryPi_JetsonNano/python/lib/waveshare_epd/epd4in37g.py)
+
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+    spi::SpiDevice,
+};
+
+#[cfg(feature = "graphics")]
+use crate::buffer_len;
+use crate::{
+    color::QuadColor,
+    rect::Rect,
+    traits::{InternalWiAdditions, WaveshareDisplay},
+    type_g::{command::Command, QuadColorPanel},
+};
+
+/// Full size buffer for use with the 4in37g EPD
+#[cfg(feature = "graphics")]
+pub type Display4in37g = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
+    QuadColor,
+    {
+        QuadColor::colors_byte(
+            DEFAULT_BACKGROUND_COLOR,
+            DEFAULT_BACKGROUND_COLOR,
+            DEFAULT_BACKGROUND_COLOR,
+            DEFAULT_BACKGROUND_COLOR,
+        ) as u16
+    },
+>;
+
+/// Width of the display
+pub const WIDTH: u32 = 512;
+/// Height of the display
+pub const HEIGHT: u32 = 368;
+/// Default Background Color
+pub const DEFAULT_BACKGROUND_COLOR: QuadColor = QuadColor::White;
+
+/// Epd4in37g driver
+pub struct Epd4in37g<SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface and Background Color
+    panel: QuadColorPanel<SPI, BUSY, DC, RST, DELAY, WIDTH, HEIGHT>,
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Epd4in37g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.reset(delay);
+
+        self.cmd_with_data(spi, Command::CMDH, &[0x49, 0x55, 0x20, 0x08, 0x09, 0x18])?;
+        self.cmd_with_data(spi, Command::PowerSetting, &[0x3F])?;
+        self.cmd_with_data(spi, Command::PanelSetting, &[0x4F, 0x69])?;
+        self.cmd_with_data(spi, Command::BoosterSoftStart1, &[0x40, 0x1F, 0x1F, 0x2C])?;
+        self.cmd_with_data(spi, Command::BoosterSoftStart2, &[0x6F, 0x1F, 0x16, 0x25])?;
+        self.cmd_with_data(spi, Command::BoosterSoftStart3, &[0x6F, 0x1F, 0x1F, 0x22])?;
+        self.cmd_with_data(spi, Command::IPC, &[0x00, 0x04])?;
+        self.cmd_with_data(spi, Command::PllControl, &[0x02])?;
+        self.cmd_with_data(spi, Command::TSE, &[0x00])?;
+        self.cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x3F])?;
+        self.cmd_with_data(spi, Command::TconSetting, &[0x02, 0x00])?;
+        self.panel.set_resolution(spi)?;
+        self.cmd_with_data(spi, Command::Ox82, &[0x1E])?;
+        self.cmd_with_data(spi, Command::Ox84, &[0x01])?;
+        self.cmd_with_data(spi, Command::AGID, &[0x00])?;
+        self.cmd_with_data(spi, Command::PowerSaving, &[0x2F])?;
+        self.cmd_with_data(spi, Command::CCSET, &[0x00])?;
+        self.cmd_with_data(spi, Command::TSSET, &[0x00])?;
+
+        self.panel.power_on(spi, delay)
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd4in37g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = QuadColor;
+    const CLEAR_FRAME_REFRESHES: bool = true;
+
+    fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, <SPI>::Error>
+    where
+        Self: Sized,
+    {
+        let panel = QuadColorPanel::new(busy, dc, rst, delay_us, DEFAULT_BACKGROUND_COLOR);
+
+        let mut epd = Epd4in37g { panel };
+
+        epd.init(spi, delay)?;
+
+        Ok(epd)
+    }
+
+    fn sleep(&mut self, spi: &mut SPI, _delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.sleep(spi)
+    }
+
+    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.init(spi, delay)
+    }
+
+    fn set_background_color(&mut self, color: Self::DisplayColor) {
+        self.panel.color = color;
+    }
+
+    fn background_color(&self) -> &Self::DisplayColor {
+        &self.panel.color
+    }
+
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), <SPI>::Error> {
+        self.panel.update_frame(spi, buffer, delay)
+    }
+
+    fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), <SPI>::Error> {
+        self.panel
+            .update_partial_frame(spi, delay, buffer, Rect::new(x, y, width, height))
+    }
+
+    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.display_frame(spi, delay)
+    }
+
+    fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), <SPI>::Error> {
+        self.update_frame(spi, buffer, delay)?;
+        self.display_frame(spi, delay)
+    }
+
+    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.clear_frame(spi, delay)
+    }
+
+    fn set_lut(
+        &mut self,
+        _spi: &mut SPI,
+        _delay: &mut DELAY,
+        _refresh_rate: Option<crate::traits::RefreshLut>,
+    ) -> Result<(), <SPI>::Error> {
+        Ok(())
+    }
+
+    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+        self.panel.wait_busy_low(delay);
+        Ok(())
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> Epd4in37g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn cmd_with_data(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+        data: &[u8],
+    ) -> Result<(), SPI::Error> {
+        self.panel.cmd_with_data(spi, command, data)
+    }
+
+    /// Show 4 stripes of color, used for quick testing
+    pub fn show_4stripes(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.panel.show_4stripes(spi, delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 512);
+        assert_eq!(HEIGHT, 368);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, QuadColor::White);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn display_default_is_white() {
+        let display = Display4in37g::default();
+        assert_eq!(display.buffer().len(), WIDTH as usize * HEIGHT as usize / 4);
+        assert!(display.buffer().iter().all(|&byte| byte == 0x55));
+    }
+}
@@ -28,6 +28,7 @@ pub type Display5in65f = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize * 4) },
     OctColor,
+    { OctColor::colors_byte(DEFAULT_BACKGROUND_COLOR, DEFAULT_BACKGROUND_COLOR) as u16 },
 >;
 
 /// Width of the display
@@ -90,6 +91,7 @@ where
     DELAY: DelayNs,
 {
     type DisplayColor = OctColor;
+    const CLEAR_FRAME_REFRESHES: bool = true;
     fn new(
         spi: &mut SPI,
         busy: BUSY,
@@ -198,7 +200,7 @@ where
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        Ok(())
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -112,6 +112,11 @@ pub(crate) enum Command {
     PartialIn = 0x91,
     /// Quits partial update mode
     PartialOut = 0x92,
+    /// Cascade setting, TSFIX (bit 1) uses the temperature of `ForceTemperature` instead of
+    /// the sensor
+    CascadeSetting = 0xE0,
+    /// Temperature used with TSFIX, it selects the waveform from the OTP
+    ForceTemperature = 0xE5,
 }
 
 impl traits::Command for Command {
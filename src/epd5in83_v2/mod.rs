@@ -15,7 +15,8 @@ use embedded_hal::{
 use crate::color::Color;
 use crate::interface::DisplayInterface;
 use crate::prelude::WaveshareDisplay;
-use crate::traits::{InternalWiAdditions, RefreshLut};
+use crate::rect::Rect;
+use crate::traits::{InternalWiAdditions, RefreshLut, Temperature};
 
 pub(crate) mod command;
 use self::command::Command;
@@ -29,6 +30,8 @@ pub type Display5in83 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
+    true,
 >;
 
 /// Width of the display
@@ -73,6 +76,8 @@ where
 
         // Set the panel settings: BWOTP
         self.cmd_with_data(spi, Command::PanelSetting, &[0x1F])?;
+        let celsius = self.interface.temperature();
+        self.interface.cmd_forced_temperature(spi, celsius)?;
 
         // Set the real resolution
         self.send_resolution(spi)?;
@@ -165,17 +170,30 @@ where
         Ok(())
     }
 
+    /// Writes the window into the new frame, `x` and `width` have to be multiples of 8
+    ///
+    /// `display_frame` refreshes the whole screen, [`Epd5in83::display_partial_frame`] only the
+    /// window.
     fn update_partial_frame(
         &mut self,
-        _spi: &mut SPI,
-        _delay: &mut DELAY,
-        _buffer: &[u8],
-        _x: u32,
-        _y: u32,
-        _width: u32,
-        _height: u32,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
     ) -> Result<(), SPI::Error> {
-        unimplemented!()
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            true,
+        )?;
+        self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
+        self.command(spi, Command::PartialOut)
     }
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -201,8 +219,11 @@ where
         self.command(spi, Command::DataStartTransmission1)?;
         self.interface.data_x_times(spi, 0xFF, NUM_DISPLAY_BITS)?;
 
+        // the device stores black as 1
+        let color_value = !self.color.get_byte_value();
         self.command(spi, Command::DataStartTransmission2)?;
-        self.interface.data_x_times(spi, 0x00, NUM_DISPLAY_BITS)?;
+        self.interface
+            .data_x_times(spi, color_value, NUM_DISPLAY_BITS)?;
 
         Ok(())
     }
@@ -213,7 +234,7 @@ where
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        Ok(())
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -247,6 +268,29 @@ where
         self.interface.cmd_with_data(spi, command, data)
     }
 
+    /// Refreshes only the window, `x` and `width` have to be multiples of 8
+    pub fn display_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            true,
+        )?;
+        self.command(spi, Command::DisplayRefresh)?;
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialOut)
+    }
+
     fn send_resolution(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
         let w = self.width();
         let h = self.height();
@@ -259,6 +303,31 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd5in83<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd_forced_temperature(spi, celsius)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -269,4 +338,62 @@ mod tests {
         assert_eq!(HEIGHT, 480);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn partial_window() {
+        use crate::graphics::Display;
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use crate::snapshot::Snapshot;
+        use embedded_graphics::{pixelcolor::Rgb888, prelude::*, primitives::*};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd5in83::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut display = Display5in83::default();
+        epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+            .unwrap();
+        let blank = simulator.displayed();
+
+        // a 16x4 window at (320, 100)
+        let mut window: Display<
+            16,
+            4,
+            false,
+            { buffer_len(16, 4) },
+            Color,
+            { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
+            true,
+        > = Display::default();
+        Rectangle::new(Point::new(0, 0), Size::new(8, 4))
+            .into_styled(PrimitiveStyle::with_fill(Color::Black))
+            .draw(&mut window)
+            .unwrap();
+        epd.update_partial_frame(&mut spi, &mut delay, window.buffer(), 320, 100, 16, 4)
+            .unwrap();
+        assert_eq!(simulator.displayed(), blank);
+        epd.display_partial_frame(&mut spi, &mut delay, 320, 100, 16, 4)
+            .unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+
+        Rectangle::new(Point::new(320, 100), Size::new(8, 4))
+            .into_styled(PrimitiveStyle::with_fill(Color::Black))
+            .draw(&mut display)
+            .unwrap();
+        // the simulator shows the bits as they are in the RAM, where white is 0
+        let expected = Snapshot::from_bitmap(&display.bitmap(), false, Rgb888::RED);
+        assert_eq!(simulator.displayed(), expected);
+        assert_eq!(simulator.ghosting(320, 100), Some(1));
+        assert_eq!(simulator.ghosting(0, 0), Some(0));
+        assert_eq!(simulator.writes_while_busy(), 0);
+    }
 }
@@ -15,7 +15,7 @@ use embedded_hal::{
 use crate::color::Color;
 use crate::interface::DisplayInterface;
 use crate::prelude::{TriColor, WaveshareDisplay, WaveshareThreeColorDisplay};
-use crate::traits::{InternalWiAdditions, RefreshLut};
+use crate::traits::{InternalWiAdditions, RefreshLut, Temperature};
 
 pub(crate) mod command;
 use self::command::Command;
@@ -29,6 +29,7 @@ pub type Display5in83 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
     TriColor,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Width of the display
@@ -76,6 +77,8 @@ where
 
         // Set the panel settings: BWROTP
         self.cmd_with_data(spi, Command::PanelSetting, &[0x0F])?;
+        let celsius = self.interface.temperature();
+        self.interface.cmd_forced_temperature(spi, celsius)?;
 
         // Set the real resolution
         self.send_resolution(spi)?;
@@ -279,7 +282,8 @@ where
 
         // The Waveshare controllers all implement clear using 0x33
         self.command(spi, Command::DataStartTransmission1)?;
-        self.interface.data_x_times(spi, 0xFF, NUM_DISPLAY_BITS)?;
+        self.interface
+            .data_x_times(spi, self.color.get_byte_value(), NUM_DISPLAY_BITS)?;
 
         self.command(spi, Command::DataStartTransmission2)?;
         self.interface.data_x_times(spi, 0x00, NUM_DISPLAY_BITS)?;
@@ -293,7 +297,7 @@ where
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        Ok(())
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -339,6 +343,32 @@ where
     }
 }
 
+/// The refreshes take the waveform of the OTP for the temperature of the panel.
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd5in83<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd_forced_temperature(spi, celsius)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
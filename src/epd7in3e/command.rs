@@ -0,0 +1,42 @@
+//! SPI Commands for the Waveshare 7.3" (E) E-Ink Display
+
+use crate::traits;
+
+#[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Command {
+    PanelSetting = 0x00,
+    PowerSetting = 0x01,
+    PowerOff = 0x02,
+    PowerOffSequenceSetting = 0x03,
+    PowerOn = 0x04,
+    BoosterSoftStart1 = 0x05,
+    BoosterSoftStart2 = 0x06,
+    DeepSleep = 0x07,
+    BoosterSoftStart3 = 0x08,
+
+    DataStartTransmission = 0x10,
+    DisplayRefresh = 0x12,
+
+    PllControl = 0x30,
+    VcomAndDataIntervalSetting = 0x50,
+    TconSetting = 0x60,
+    ResolutionSetting = 0x61,
+
+    Ox84 = 0x84,
+
+    PartialWindow = 0x90,
+    PartialIn = 0x91,
+    PartialOut = 0x92,
+
+    CMDH = 0xAA,
+
+    PowerSaving = 0xE3,
+}
+
+impl traits::Command for Command {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
@@ -31,6 +31,7 @@ pub type Display7in3f = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize * 4) },
     OctColor,
+    { OctColor::colors_byte(DEFAULT_BACKGROUND_COLOR, DEFAULT_BACKGROUND_COLOR) as u16 },
 >;
 
 /// Width of the display
@@ -96,6 +97,7 @@ where
     DELAY: DelayNs,
 {
     type DisplayColor = OctColor;
+    const CLEAR_FRAME_REFRESHES: bool = true;
 
     fn new(
         spi: &mut SPI,
@@ -204,7 +206,7 @@ where
         _delay: &mut DELAY,
         _refresh_rate: Option<crate::traits::RefreshLut>,
     ) -> Result<(), <SPI>::Error> {
-        unimplemented!()
+        Ok(())
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
@@ -28,6 +28,7 @@ pub type Display7in5 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Width of the display
@@ -215,10 +216,14 @@ where
         self.wait_until_idle(spi, delay)?;
         self.send_resolution(spi)?;
 
-        // The Waveshare controllers all implement clear using 0x33
+        // The Waveshare controllers all implement clear using 0x33, two white pixels of 4 bits
+        let color_value = match self.color {
+            Color::White => 0x33,
+            Color::Black => 0x00,
+        };
         self.command(spi, Command::DataStartTransmission1)?;
         self.interface
-            .data_x_times(spi, 0x33, WIDTH / 8 * HEIGHT * 4)?;
+            .data_x_times(spi, color_value, WIDTH / 8 * HEIGHT * 4)?;
         Ok(())
     }
 
@@ -228,7 +233,7 @@ where
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        Ok(())
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
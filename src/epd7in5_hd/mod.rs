@@ -31,6 +31,7 @@ pub type Display7in5 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Width of the display
@@ -113,6 +114,7 @@ where
     DELAY: DelayNs,
 {
     type DisplayColor = Color;
+    const CLEAR_FRAME_REFRESHES: bool = true;
     fn new(
         spi: &mut SPI,
         busy: BUSY,
@@ -225,7 +227,7 @@ where
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        Ok(())
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
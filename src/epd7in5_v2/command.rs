@@ -128,9 +128,20 @@ pub(crate) enum Command {
     ReadVcomValue = 0x81,
     /// This command sets `VCOM_DC` value.
     VcmDcSetting = 0x82,
-    // /// This is in all the Waveshare controllers for Epd7in5, but it's not documented
-    // /// anywhere in the datasheet `¯\_(ツ)_/¯`
-    // FlashMode = 0xE5,
+
+    /// Sets window size for the partial update
+    PartialWindow = 0x90,
+    /// Sets chip into partial update mode
+    PartialIn = 0x91,
+    /// Quits partial update mode
+    PartialOut = 0x92,
+
+    /// Cascade setting, TSFIX (bit 1) uses the temperature of `ForceTemperature` instead of
+    /// the sensor
+    CascadeSetting = 0xE0,
+    /// Temperature used with TSFIX, it selects the waveform from the OTP (0x6E is the fast one
+    /// of the Waveshare partial refresh)
+    ForceTemperature = 0xE5,
 }
 
 impl traits::Command for Command {
@@ -18,7 +18,11 @@ use embedded_hal::{
 
 use crate::color::Color;
 use crate::interface::DisplayInterface;
-use crate::traits::{InternalWiAdditions, RefreshLut, WaveshareDisplay};
+use crate::rect::Rect;
+use crate::scheduler::{DefaultRefreshPolicy, RefreshPolicy};
+use crate::traits::{
+    HardwareMirror, InternalWiAdditions, QuickRefresh, RefreshLut, Temperature, WaveshareDisplay,
+};
 
 pub(crate) mod command;
 use self::command::Command;
@@ -32,6 +36,8 @@ pub type Display7in5 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize) },
     Color,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
+    true,
 >;
 
 /// Width of the display
@@ -42,6 +48,8 @@ pub const HEIGHT: u32 = 480;
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
 const IS_BUSY_LOW: bool = true;
 const SINGLE_BYTE_WRITE: bool = false;
+/// Forced temperature selecting the fast waveform of the OTP for the quick refresh
+const QUICK_CELSIUS: i8 = 110;
 
 /// Epd7in5 (V2) driver
 ///
@@ -50,6 +58,17 @@ pub struct Epd7in5<SPI, BUSY, DC, RST, DELAY> {
     interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
     /// Background Color
     color: Color,
+    /// Source (left/right) scan direction reversed
+    mirror_x: bool,
+    /// Gate (top/bottom) scan direction reversed
+    mirror_y: bool,
+    /// Refresh LUT
+    refresh: RefreshLut,
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> DefaultRefreshPolicy for Epd7in5<SPI, BUSY, DC, RST, DELAY> {
+    // the fast waveform leaves a lot of ghosting on this panel
+    const REFRESH_POLICY: RefreshPolicy = RefreshPolicy::for_panel(WIDTH, HEIGHT, 5);
 }
 
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
@@ -75,10 +94,10 @@ where
         self.command(spi, Command::PowerOn)?;
         delay.delay_ms(100);
         self.wait_until_idle(spi, delay)?;
-        self.cmd_with_data(spi, Command::PanelSetting, &[0x1F])?;
+        self.cmd_with_data(spi, Command::PanelSetting, &[self.panel_setting()])?;
         self.cmd_with_data(spi, Command::TconResolution, &[0x03, 0x20, 0x01, 0xE0])?;
         self.cmd_with_data(spi, Command::DualSpi, &[0x00])?;
-        self.cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x10, 0x07])?;
+        self.send_refresh_mode(spi)?;
         self.cmd_with_data(spi, Command::TconSetting, &[0x22])?;
         Ok(())
     }
@@ -94,6 +113,7 @@ where
     DELAY: DelayNs,
 {
     type DisplayColor = Color;
+    const CLEAR_FRAME_REFRESHES: bool = true;
     fn new(
         spi: &mut SPI,
         busy: BUSY,
@@ -105,7 +125,13 @@ where
         let interface = DisplayInterface::new(busy, dc, rst, delay_us);
         let color = DEFAULT_BACKGROUND_COLOR;
 
-        let mut epd = Epd7in5 { interface, color };
+        let mut epd = Epd7in5 {
+            interface,
+            color,
+            mirror_x: false,
+            mirror_y: false,
+            refresh: RefreshLut::Full,
+        };
 
         epd.init(spi, delay)?;
 
@@ -131,21 +157,43 @@ where
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
+        if self.refresh != RefreshLut::Quick {
+            // keep the old data equal to the displayed frame for the next quick refresh
+            self.cmd_with_data(spi, Command::DataStartTransmission1, buffer)?;
+        }
         self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
         Ok(())
     }
 
+    /// Writes the window into the new frame, and into the old one outside of quick mode. `x`
+    /// and `width` have to be multiples of 8
+    ///
+    /// The whole panel is refreshed by `display_frame`, in quick mode only the changed pixels
+    /// are driven.
     fn update_partial_frame(
         &mut self,
-        _spi: &mut SPI,
-        _delay: &mut DELAY,
-        _buffer: &[u8],
-        _x: u32,
-        _y: u32,
-        _width: u32,
-        _height: u32,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            true,
+        )?;
+        if self.refresh != RefreshLut::Quick {
+            // keep the old data equal to the displayed frame for the next quick refresh
+            self.cmd_with_data(spi, Command::DataStartTransmission1, buffer)?;
+        }
+        self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
+        self.command(spi, Command::PartialOut)
     }
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -168,12 +216,16 @@ where
     fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
         self.send_resolution(spi)?;
+        // the device stores black as 1
+        let color_value = !self.color.get_byte_value();
 
         self.command(spi, Command::DataStartTransmission1)?;
-        self.interface.data_x_times(spi, 0x00, WIDTH / 8 * HEIGHT)?;
+        self.interface
+            .data_x_times(spi, color_value, WIDTH / 8 * HEIGHT)?;
 
         self.command(spi, Command::DataStartTransmission2)?;
-        self.interface.data_x_times(spi, 0x00, WIDTH / 8 * HEIGHT)?;
+        self.interface
+            .data_x_times(spi, color_value, WIDTH / 8 * HEIGHT)?;
 
         self.command(spi, Command::DisplayRefresh)?;
         Ok(())
@@ -195,13 +247,22 @@ where
         HEIGHT
     }
 
+    /// Switches between the full waveform and the fast waveform of Waveshare's partial refresh
+    ///
+    /// In quick mode the controller copies the new frame into the old one after each refresh.
+    /// In the other modes the frames are written to the old data as well, so the first quick
+    /// refresh compares with the frame on the screen.
     fn set_lut(
         &mut self,
-        _spi: &mut SPI,
-        _delay: &mut DELAY,
-        _refresh_rate: Option<RefreshLut>,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        if let Some(refresh_lut) = refresh_rate {
+            self.refresh = refresh_lut;
+        }
+        self.wait_until_idle(spi, delay)?;
+        self.send_refresh_mode(spi)
     }
 
     fn wait_until_idle(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -235,6 +296,36 @@ where
         self.interface.cmd_with_data(spi, command, data)
     }
 
+    /// KW mode with LUTs from OTP, UD (bit 3) and SHL (bit 2) choose the scan directions
+    fn panel_setting(&self) -> u8 {
+        let mut value = 0x1F;
+        if self.mirror_x {
+            value &= !0x04;
+        }
+        if self.mirror_y {
+            value &= !0x08;
+        }
+        value
+    }
+
+    /// The quick mode forces the temperature which selects the fast waveform of the OTP, and
+    /// copies the new data to the old data after a refresh (N2OCP). The full mode forces the
+    /// temperature of the panel if it is set.
+    fn send_refresh_mode(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+        match self.refresh {
+            RefreshLut::Full | RefreshLut::Fast => {
+                let celsius = self.interface.temperature();
+                self.interface.cmd_forced_temperature(spi, celsius)?;
+                self.cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x10, 0x07])
+            }
+            RefreshLut::Quick => {
+                self.interface
+                    .cmd_forced_temperature(spi, Some(QUICK_CELSIUS))?;
+                self.cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0xA9, 0x07])
+            }
+        }
+    }
+
     fn send_resolution(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
         let w = self.width();
         let h = self.height();
@@ -247,6 +338,168 @@ where
     }
 }
 
+impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
+    for Epd7in5<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// To be followed immediately by `update_new_frame`.
+    fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+    }
+
+    /// To be used immediately after `update_old_frame`.
+    fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_frame(spi, buffer, delay)
+    }
+
+    fn display_new_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.display_frame(spi, delay)
+    }
+
+    /// To be used immediately after `update_old_frame`.
+    fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_new_frame(spi, buffer, delay)?;
+        self.display_frame(spi, delay)
+    }
+
+    fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            true,
+        )?;
+        self.cmd_with_data(spi, Command::DataStartTransmission1, buffer)?;
+        self.command(spi, Command::PartialOut)
+    }
+
+    /// Always call `update_partial_old_frame` before this, with buffer-updating code
+    /// between the calls.
+    fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.update_partial_frame(spi, delay, buffer, x, y, width, height)
+    }
+
+    fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        // the device stores white as 0
+        let color_value = !self.color.get_byte_value();
+
+        // only the new frame, the old one has to keep what the panel shows for the quick
+        // waveform to drive the cleared pixels
+        self.command(spi, Command::PartialIn)?;
+        self.interface.cmd_partial_window(
+            spi,
+            Command::PartialWindow,
+            Rect::new(x, y, width, height),
+            true,
+        )?;
+        self.command(spi, Command::DataStartTransmission2)?;
+        self.interface
+            .data_x_times(spi, color_value, width / 8 * height)?;
+        self.command(spi, Command::PartialOut)
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd7in5<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.send_refresh_mode(spi)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY> HardwareMirror<SPI, BUSY, DC, RST, DELAY>
+    for Epd7in5<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    const HORIZONTAL: bool = true;
+    const VERTICAL: bool = true;
+
+    fn set_mirror(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        horizontal: bool,
+        vertical: bool,
+    ) -> Result<(), SPI::Error> {
+        self.mirror_x = horizontal;
+        self.mirror_y = vertical;
+        self.wait_until_idle(spi, delay)?;
+        self.cmd_with_data(spi, Command::PanelSetting, &[self.panel_setting()])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,4 +510,232 @@ mod tests {
         assert_eq!(HEIGHT, 480);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    // clear_frame sends 0x00 for white
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn display_default_is_blank() {
+        use embedded_graphics_core::prelude::*;
+
+        let mut display = Display7in5::default();
+        assert!(display.buffer().iter().all(|&byte| byte == 0x00));
+        display.clear(Color::Black).unwrap();
+        assert!(display.buffer().iter().all(|&byte| byte == 0xFF));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn quick_refresh_windows() {
+        use crate::graphics::DoubleBufferedDisplay;
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use crate::snapshot::Snapshot;
+        use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd7in5::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut display: DoubleBufferedDisplay<
+            WIDTH,
+            HEIGHT,
+            false,
+            { buffer_len(WIDTH as usize, HEIGHT as usize) },
+            Color,
+            { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
+            true,
+        > = DoubleBufferedDisplay::default();
+        // the simulator shows the bits as they are in the RAM, where white is 0
+        let ram_image =
+            |display: &Display7in5| Snapshot::from_bitmap(&display.bitmap(), false, Rgb888::RED);
+
+        epd.clear_frame(&mut spi, &mut delay).unwrap();
+        Pixel(Point::new(0, 0), Color::Black)
+            .draw(&mut display)
+            .unwrap();
+        epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+            .unwrap();
+        display.mark_displayed();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))
+            .unwrap();
+        for point in [Point::new(20, 30), Point::new(700, 400), Point::new(20, 31)] {
+            Pixel(point, Color::Black).draw(&mut display).unwrap();
+            let window = display.flush(&mut epd, &mut spi, &mut delay).unwrap();
+            assert_eq!(window.map(|window| (window.w, window.h)), Some((8, 1)));
+            assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+            assert_eq!(simulator.displayed(), ram_image(display.display()));
+        }
+        // only the changed pixels were driven
+        assert_eq!(simulator.ghosting(20, 30), Some(1));
+        assert_eq!(simulator.ghosting(20, 31), Some(1));
+        assert_eq!(simulator.ghosting(0, 0), Some(0));
+        assert_eq!(simulator.partial_refreshes(), 3);
+
+        epd.clear_partial_frame(&mut spi, &mut delay, 16, 30, 8, 2)
+            .unwrap();
+        epd.display_new_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.displayed().pixel(20, 31), Some(Rgb888::BLACK));
+        assert_eq!(simulator.displayed().pixel(700, 400), Some(Rgb888::WHITE));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+        assert_eq!(simulator.writes_while_busy(), 0);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn forced_temperature() {
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd7in5::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+
+        // a cold panel keeps the full waveform, the quick mode the fast one
+        epd.set_temperature(&mut spi, &mut delay, Some(-5)).unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        assert_eq!(epd.temperature(), Some(-5));
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn scheduler_keeps_old_data() {
+        use crate::scheduler::RefreshScheduler;
+        use crate::simulator::{Controller, Refresh, Simulator};
+        use crate::snapshot::Snapshot;
+        use embedded_graphics::{
+            pixelcolor::Rgb888,
+            prelude::*,
+            primitives::{PrimitiveStyle, Rectangle},
+        };
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        // the old data isn't taken from the panel, the driver has to keep it current
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        simulator.set_keep_old_data(true);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let epd = Epd7in5::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut epd = RefreshScheduler::new(epd);
+        let ram_image =
+            |display: &Display7in5| Snapshot::from_bitmap(&display.bitmap(), false, Rgb888::RED);
+
+        epd.epd_mut().clear_frame(&mut spi, &mut delay).unwrap();
+        let mut display = Display7in5::default();
+        let mut refreshes = std::vec::Vec::new();
+        // every frame takes back the pixel of the one before
+        for x in 0..8 {
+            display.clear(Color::White).unwrap();
+            Pixel(Point::new(x * 8, 0), Color::Black)
+                .draw(&mut display)
+                .unwrap();
+            let refresh = epd
+                .update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+                .unwrap();
+            assert_eq!(
+                simulator.last_refresh(),
+                Some(match refresh {
+                    RefreshLut::Quick => Refresh::Partial,
+                    _ => Refresh::Full,
+                })
+            );
+            assert_eq!(simulator.displayed(), ram_image(&display));
+            refreshes.push(refresh);
+        }
+        use RefreshLut::{Full, Quick};
+        assert_eq!(
+            refreshes,
+            [Full, Quick, Quick, Quick, Full, Quick, Quick, Quick]
+        );
+
+        // the same for the partial frames, a full refresh of a window keeps the old data too
+        epd.request_full_refresh();
+        // the device stores black as 1
+        for (refresh, byte, color) in [(Full, 0xFF, Color::Black), (Quick, 0x00, Color::White)] {
+            let done = epd
+                .update_and_display_partial_frame(&mut spi, &mut delay, &[byte; 2], 64, 8, 8, 2)
+                .unwrap();
+            assert_eq!(done, refresh);
+            Rectangle::new(Point::new(64, 8), Size::new(8, 2))
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(&mut display)
+                .unwrap();
+            assert_eq!(simulator.displayed(), ram_image(&display));
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn deep_clean_fills() {
+        use crate::simulator::{Controller, Simulator};
+        use crate::snapshot::Snapshot;
+        use crate::traits::CleanStep;
+        use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd7in5::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let ram_image =
+            |display: &Display7in5| Snapshot::from_bitmap(&display.bitmap(), false, Rgb888::RED);
+        let mut black = Display7in5::default();
+        black.clear(Color::Black).unwrap();
+
+        let mut buffer = std::vec::Vec::from(Display7in5::default().buffer());
+        for (color, image) in [
+            (Color::Black, &black),
+            (Color::White, &Display7in5::default()),
+        ] {
+            epd.deep_clean(&mut spi, &mut delay, &mut buffer, &[CleanStep::Fill(color)])
+                .unwrap();
+            assert_eq!(simulator.displayed(), ram_image(image));
+        }
+        assert_eq!(epd.background_color(), &Color::White);
+    }
 }
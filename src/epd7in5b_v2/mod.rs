@@ -19,7 +19,7 @@ use embedded_hal::{
 use crate::color::TriColor;
 use crate::interface::DisplayInterface;
 use crate::traits::{
-    InternalWiAdditions, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+    InternalWiAdditions, RefreshLut, Temperature, WaveshareDisplay, WaveshareThreeColorDisplay,
 };
 
 pub(crate) mod command;
@@ -34,6 +34,7 @@ pub type Display7in5 = crate::graphics::Display<
     false,
     { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
     TriColor,
+    { DEFAULT_BACKGROUND_COLOR.get_byte_value() as u16 },
 >;
 
 /// Width of the display
@@ -83,6 +84,8 @@ where
         // Done, but this is also the default
         // 0x1F = B/W mode ? doesnt seem to work
         self.cmd_with_data(spi, Command::PanelSetting, &[0x0F])?;
+        let celsius = self.interface.temperature();
+        self.interface.cmd_forced_temperature(spi, celsius)?;
         // Not done in C driver, this is the default
         //self.cmd_with_data(spi, Command::PllControl, &[0x06])?;
         self.cmd_with_data(spi, Command::TconResolution, &[0x03, 0x20, 0x01, 0xE0])?;
@@ -169,6 +172,7 @@ where
     DELAY: DelayNs,
 {
     type DisplayColor = TriColor;
+    const CLEAR_FRAME_REFRESHES: bool = true;
     fn new(
         spi: &mut SPI,
         busy: BUSY,
@@ -255,11 +259,20 @@ where
         self.wait_until_idle(spi, delay)?;
         self.send_resolution(spi)?;
 
+        // set bits of the second plane are chromatic, they take precedence over the first one
+        let chromatic = if self.color == TriColor::Chromatic {
+            0xFF
+        } else {
+            0x00
+        };
+
         self.command(spi, Command::DataStartTransmission1)?;
-        self.interface.data_x_times(spi, 0xFF, WIDTH / 8 * HEIGHT)?;
+        self.interface
+            .data_x_times(spi, self.color.get_byte_value(), WIDTH / 8 * HEIGHT)?;
 
         self.command(spi, Command::DataStartTransmission2)?;
-        self.interface.data_x_times(spi, 0x00, WIDTH / 8 * HEIGHT)?;
+        self.interface
+            .data_x_times(spi, chromatic, WIDTH / 8 * HEIGHT)?;
 
         self.interface.cmd(spi, Command::DataStop)?;
 
@@ -290,7 +303,7 @@ where
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        Ok(())
     }
 
     /// wait
@@ -384,6 +397,32 @@ where
     }
 }
 
+/// The refreshes take the waveform of the OTP for the temperature of the panel.
+impl<SPI, BUSY, DC, RST, DELAY> Temperature<SPI, BUSY, DC, RST, DELAY>
+    for Epd7in5<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        self.interface.set_temperature(celsius);
+        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd_forced_temperature(spi, celsius)
+    }
+
+    fn temperature(&self) -> Option<i8> {
+        self.interface.temperature()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -394,4 +433,36 @@ mod tests {
         assert_eq!(HEIGHT, 480);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, TriColor::White);
     }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn forced_temperature() {
+        use crate::simulator::{Controller, Simulator};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let simulator = Simulator::new(Controller::Uc81xx, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let mut epd = Epd7in5::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        assert_eq!(simulator.register(0xE0), Some(std::vec![0x00]));
+
+        // TSFIX replaces the sensor by the forced temperature, also after a wake up
+        epd.set_temperature(&mut spi, &mut delay, Some(-5)).unwrap();
+        assert_eq!(simulator.register(0xE0), Some(std::vec![0x02]));
+        assert_eq!(simulator.register(0xE5), Some(std::vec![0xFB]));
+        epd.sleep(&mut spi, &mut delay).unwrap();
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.register(0xE0), Some(std::vec![0x02]));
+        assert_eq!(epd.temperature(), Some(-5));
+
+        epd.set_temperature(&mut spi, &mut delay, None).unwrap();
+        assert_eq!(simulator.register(0xE0), Some(std::vec![0x00]));
+    }
 }
@@ -1,11 +1,19 @@
 //! Graphics Support for EPDs
 
 use crate::color::{ColorType, TriColor};
+use crate::palette::Palette;
+use crate::rect::Rect;
+use crate::traits::QuickRefresh;
 use core::marker::PhantomData;
-use embedded_graphics_core::prelude::*;
+use embedded_graphics_core::{pixelcolor::Rgb888, prelude::*};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+    spi::SpiDevice,
+};
 
 /// Display rotation, only 90° increments supported
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
 pub enum DisplayRotation {
     /// No rotation
     #[default]
@@ -18,6 +26,116 @@ pub enum DisplayRotation {
     Rotate270,
 }
 
+impl DisplayRotation {
+    /// Rotation obtained by rotating this one further by 180 degrees
+    pub const fn flipped(self) -> DisplayRotation {
+        match self {
+            DisplayRotation::Rotate0 => DisplayRotation::Rotate180,
+            DisplayRotation::Rotate90 => DisplayRotation::Rotate270,
+            DisplayRotation::Rotate180 => DisplayRotation::Rotate0,
+            DisplayRotation::Rotate270 => DisplayRotation::Rotate90,
+        }
+    }
+}
+
+/// Display orientation covering all 8 combinations of rotation and mirroring
+///
+/// The drawing is first mirrored horizontally (if `mirror` is set) and then rotated.
+/// A vertical mirror is the same as a horizontal mirror followed by a rotation of 180 degrees,
+/// use [`DisplayTransform::mirror_vertical`] to get it without doing the math yourself.
+///
+/// Mirroring is needed for panels mounted behind a mirror or on flex cables folded the other way.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct DisplayTransform {
+    /// Rotation applied after mirroring
+    pub rotation: DisplayRotation,
+    /// Mirror the drawing horizontally (left and right are swapped)
+    pub mirror: bool,
+}
+
+impl DisplayTransform {
+    /// Create a new transform from a rotation and a horizontal mirroring
+    pub const fn new(rotation: DisplayRotation, mirror: bool) -> Self {
+        Self { rotation, mirror }
+    }
+
+    /// Additionally mirror the drawing horizontally (left and right are swapped)
+    pub const fn mirror_horizontal(self) -> Self {
+        Self {
+            rotation: self.rotation,
+            mirror: !self.mirror,
+        }
+    }
+
+    /// Additionally mirror the drawing vertically (top and bottom are swapped)
+    pub const fn mirror_vertical(self) -> Self {
+        Self {
+            rotation: self.rotation.flipped(),
+            mirror: !self.mirror,
+        }
+    }
+
+    /// Size of the drawing area for a buffer of `width` x `height` pixels
+    pub fn size(&self, width: u32, height: u32) -> Size {
+        match self.rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => Size::new(width, height),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => Size::new(height, width),
+        }
+    }
+
+    /// Map a point of the drawing to its position in a buffer of `width` x `height` pixels
+    ///
+    /// The result may be outside of the buffer if the point is outside of the drawing area.
+    pub fn apply(&self, point: Point, width: u32, height: u32) -> Point {
+        // as i32 = never use more than 2 billion pixel per line or per column
+        let (width, height) = (width as i32, height as i32);
+        let point = if self.mirror {
+            let drawing_width = self.size(width as u32, height as u32).width as i32;
+            Point::new(drawing_width - 1 - point.x, point.y)
+        } else {
+            point
+        };
+        match self.rotation {
+            DisplayRotation::Rotate0 => point,
+            DisplayRotation::Rotate90 => Point::new(width - 1 - point.y, point.x),
+            DisplayRotation::Rotate180 => Point::new(width - 1 - point.x, height - 1 - point.y),
+            DisplayRotation::Rotate270 => Point::new(point.y, height - 1 - point.x),
+        }
+    }
+
+    /// Map a position in a buffer of `width` x `height` pixels back to the point of the drawing
+    ///
+    /// This is the inverse of [`DisplayTransform::apply`].
+    pub fn apply_inverse(&self, point: Point, width: u32, height: u32) -> Point {
+        let (width, height) = (width as i32, height as i32);
+        let point = match self.rotation {
+            DisplayRotation::Rotate0 => point,
+            DisplayRotation::Rotate90 => Point::new(point.y, width - 1 - point.x),
+            DisplayRotation::Rotate180 => Point::new(width - 1 - point.x, height - 1 - point.y),
+            DisplayRotation::Rotate270 => Point::new(height - 1 - point.y, point.x),
+        };
+        if self.mirror {
+            let drawing_width = self.size(width as u32, height as u32).width as i32;
+            Point::new(drawing_width - 1 - point.x, point.y)
+        } else {
+            point
+        }
+    }
+}
+
+impl From<DisplayRotation> for DisplayTransform {
+    fn from(rotation: DisplayRotation) -> Self {
+        Self::new(rotation, false)
+    }
+}
+
+/// Error found when moving the contents of a display buffer around
+#[derive(Debug, PartialEq, Eq)]
+pub enum TransformError {
+    /// The drawing area would change its size, this needs a separate target buffer
+    SizeMismatch,
+}
+
 /// count the number of bytes per line knowing that it may contains padding bits
 const fn line_bytes(width: u32, bits_per_pixel: usize) -> usize {
     // round to upper 8 bit count
@@ -30,10 +148,18 @@ const fn line_bytes(width: u32, bits_per_pixel: usize) -> usize {
 /// - WIDTH: width in pixel when display is not rotated
 /// - HEIGHT: height in pixel when display is not rotated
 /// - BWRBIT: mandatory value of the B/W when chromatic bit is set, can be any value for non
-///           tricolor epd
+///   tricolor epd
 /// - COLOR: color type used by the target display
 /// - BYTECOUNT: This is redundant with previous data and should be removed when const generic
-///              expressions are stabilized
+///   expressions are stabilized
+/// - BACKGROUND: bytes filling the buffer of a blank frame, the low byte for the first (or only)
+///   buffer plane and the high byte for the chromatic plane of tricolor displays. The display
+///   type of each device uses its `DEFAULT_BACKGROUND_COLOR`.
+/// - INVERTED: polarity of the black/white bits, set for devices where white is sent as 0.
+///   BACKGROUND is inverted as well, so it is still given with the usual encoding.
+/// - CHROMATIC: ink of the chromatic pixels of tricolor displays as `0xRRGGBB`, e.g.
+///   [`TriColor::YELLOW_INK`]. It selects the palette of the Rgb888 conversion and the color of
+///   the exports, the buffer is the same for all inks.
 ///
 /// More on BWRBIT:
 ///
@@ -52,9 +178,12 @@ pub struct Display<
     const BWRBIT: bool,
     const BYTECOUNT: usize,
     COLOR: ColorType + PixelColor,
+    const BACKGROUND: u16 = 0,
+    const INVERTED: bool = false,
+    const CHROMATIC: u32 = { TriColor::RED_INK },
 > {
     buffer: [u8; BYTECOUNT],
-    rotation: DisplayRotation,
+    transform: DisplayTransform,
     _color: PhantomData<COLOR>,
 }
 
@@ -64,22 +193,30 @@ impl<
         const BWRBIT: bool,
         const BYTECOUNT: usize,
         COLOR: ColorType + PixelColor,
-    > Default for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR>
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    > Default
+    for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC>
 {
-    /// Initialize display with the color '0', which may not be the same on all device.
-    /// Many devices have a bit parameter polarity that should be changed if this is not the right
-    /// one.
-    /// However, every device driver should implement a DEFAULT_COLOR constant to indicate which
-    /// color this represents (TODO)
+    /// Initialize display with a blank frame, filled with BACKGROUND.
+    ///
+    /// The display types of the devices use their `DEFAULT_BACKGROUND_COLOR`, so this is the same
+    /// as what `WaveshareDisplay::clear_frame` shows. A `Display` declared by hand without
+    /// BACKGROUND is filled with the color '0', which may not be the same on all devices.
     ///
     /// If you want a specific default color, you can still call clear() to set one.
     // inline is necessary here to allow heap allocation via Box on stack limited programs
     #[inline(always)]
     fn default() -> Self {
+        let blank = Self::BLANK;
+        let mut buffer = [blank as u8; BYTECOUNT];
+        if COLOR::BUFFER_COUNT == 2 {
+            buffer[BYTECOUNT / 2..].fill((blank >> 8) as u8);
+        }
         Self {
-            // default color must be 0 for every bit in a pixel to make this work everywere
-            buffer: [0u8; BYTECOUNT],
-            rotation: DisplayRotation::default(),
+            buffer,
+            transform: DisplayTransform::default(),
             _color: PhantomData,
         }
     }
@@ -92,7 +229,11 @@ impl<
         const BWRBIT: bool,
         const BYTECOUNT: usize,
         COLOR: ColorType + PixelColor,
-    > DrawTarget for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR>
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    > DrawTarget
+    for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC>
 {
     type Color = COLOR;
     type Error = core::convert::Infallible;
@@ -115,13 +256,14 @@ impl<
         const BWRBIT: bool,
         const BYTECOUNT: usize,
         COLOR: ColorType + PixelColor,
-    > OriginDimensions for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR>
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    > OriginDimensions
+    for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC>
 {
     fn size(&self) -> Size {
-        match self.rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => Size::new(WIDTH, HEIGHT),
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => Size::new(HEIGHT, WIDTH),
-        }
+        self.transform.size(WIDTH, HEIGHT)
     }
 }
 
@@ -131,8 +273,18 @@ impl<
         const BWRBIT: bool,
         const BYTECOUNT: usize,
         COLOR: ColorType + PixelColor,
-    > Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR>
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    > Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC>
 {
+    /// Bytes of a blank frame as stored in the buffer, i.e. BACKGROUND with the polarity applied
+    const BLANK: u16 = if INVERTED {
+        BACKGROUND ^ 0xFF
+    } else {
+        BACKGROUND
+    };
+
     /// get internal buffer to use it (to draw in epd)
     pub fn buffer(&self) -> &[u8] {
         &self.buffer
@@ -141,14 +293,28 @@ impl<
     /// Set the display rotation.
     ///
     /// This only concerns future drawing made to it. Anything aready drawn
-    /// stays as it is in the buffer.
+    /// stays as it is in the buffer, use `rotate_contents` to move it along.
+    /// A mirroring set with `set_transform` is kept.
     pub fn set_rotation(&mut self, rotation: DisplayRotation) {
-        self.rotation = rotation;
+        self.transform.rotation = rotation;
     }
 
     /// Get current rotation
     pub fn rotation(&self) -> DisplayRotation {
-        self.rotation
+        self.transform.rotation
+    }
+
+    /// Set the display orientation, rotation and mirroring.
+    ///
+    /// This only concerns future drawing made to it. Anything aready drawn
+    /// stays as it is in the buffer.
+    pub fn set_transform(&mut self, transform: DisplayTransform) {
+        self.transform = transform;
+    }
+
+    /// Get current orientation
+    pub fn transform(&self) -> DisplayTransform {
+        self.transform
     }
 
     /// Set a specific pixel color on this display
@@ -157,16 +323,121 @@ impl<
             &mut self.buffer,
             WIDTH,
             HEIGHT,
-            self.rotation,
+            self.transform,
             BWRBIT,
+            INVERTED,
             pixel,
         );
     }
+
+    /// Change the rotation and move everything already drawn along with it.
+    ///
+    /// A mirroring set with `set_transform` is kept.
+    /// See [`Display::transform_contents`] for the details.
+    pub fn rotate_contents(&mut self, rotation: DisplayRotation) -> Result<(), TransformError> {
+        self.transform_contents(DisplayTransform::new(rotation, self.transform.mirror))
+    }
+
+    /// Change the orientation and move everything already drawn along with it,
+    /// as if it had been drawn with the new orientation in the first place.
+    ///
+    /// This is done in place and works for every orientation on square displays. Other displays
+    /// can only switch between orientations with the same drawing size (e.g. 0 and 180 degrees),
+    /// anything else fails with [`TransformError::SizeMismatch`] and leaves the display untouched.
+    /// Use [`Display::transform_contents_into`] for them.
+    pub fn transform_contents(
+        &mut self,
+        transform: DisplayTransform,
+    ) -> Result<(), TransformError> {
+        transform_contents::<COLOR>(&mut self.buffer, WIDTH, HEIGHT, self.transform, transform)?;
+        self.transform = transform;
+        Ok(())
+    }
+
+    /// Copy everything drawn on this display to `target`, as if it had been drawn there with the
+    /// orientation of `target`.
+    ///
+    /// Anything not fitting into `target` is cut off and the parts of `target` which aren't
+    /// covered keep their content, so clear it first if needed.
+    pub fn transform_contents_into(&self, target: &mut VarDisplay<'_, COLOR>) {
+        let size = target.buffer_size();
+        transform_contents_into::<COLOR>(
+            &self.buffer,
+            WIDTH,
+            HEIGHT,
+            self.transform,
+            &mut target.buffer[..size],
+            target.width,
+            target.height,
+            target.transform,
+        );
+    }
+
+    /// Get a view on a part of this display, see [`SubDisplay`]
+    ///
+    /// `area` is given in drawing coordinates (i.e. with the current orientation)
+    /// and is cut to the size of the display.
+    pub fn sub_display(&mut self, area: Rect) -> SubDisplay<'_, COLOR> {
+        SubDisplay::new(
+            &mut self.buffer,
+            WIDTH,
+            HEIGHT,
+            BWRBIT,
+            INVERTED,
+            self.transform,
+            area,
+        )
+    }
+
+    /// Get a read-only view on the buffer of this display, to be used as a source of [`Display::blit`]
+    pub fn bitmap(&self) -> Bitmap<'_, COLOR> {
+        Bitmap {
+            buffer: &self.buffer,
+            width: WIDTH,
+            height: HEIGHT,
+            _color: PhantomData,
+        }
+    }
+
+    /// Combine the pixels of `src_rect` in `src` with the pixels of this display at `dst_point`
+    ///
+    /// `src_rect` is given in the buffer coordinates of `src`, `dst_point` is the top left corner
+    /// in drawing coordinates (i.e. with the current orientation) of this display. Anything
+    /// outside of `src` or of this display is cut off.
+    ///
+    /// `op` works on the raw bits of the buffers: both planes of [`TriColor`] and the whole
+    /// nibble of [`crate::color::OctColor`] are combined. Without rotation or mirroring whole
+    /// bytes are combined at once, whatever the bit offsets of the source and the destination.
+    pub fn blit(
+        &mut self,
+        src: &Bitmap<'_, COLOR>,
+        src_rect: Rect,
+        dst_point: Point,
+        op: RasterOp,
+    ) {
+        blit::<COLOR>(
+            &mut self.buffer,
+            WIDTH,
+            HEIGHT,
+            self.transform,
+            src,
+            src_rect,
+            dst_point,
+            op,
+        );
+    }
 }
 
 /// Some Tricolor specifics
-impl<const WIDTH: u32, const HEIGHT: u32, const BWRBIT: bool, const BYTECOUNT: usize>
-    Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, TriColor>
+impl<
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    > Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, TriColor, BACKGROUND, INVERTED, CHROMATIC>
 {
     /// get black/white internal buffer to use it (to draw in epd)
     pub fn bw_buffer(&self) -> &[u8] {
@@ -177,6 +448,24 @@ impl<const WIDTH: u32, const HEIGHT: u32, const BWRBIT: bool, const BYTECOUNT: u
     pub fn chromatic_buffer(&self) -> &[u8] {
         &self.buffer[self.buffer.len() / 2..]
     }
+
+    /// Typical colors of the panel, with the CHROMATIC ink
+    pub const PALETTE: Palette<'static, TriColor> =
+        Palette::new(&TriColor::palette_entries(CHROMATIC));
+
+    /// Color of the chromatic pixels on the panel
+    pub const CHROMATIC_RGB: Rgb888 = {
+        let (r, g, b) = TriColor::ink_rgb(CHROMATIC);
+        Rgb888::new(r, g, b)
+    };
+
+    /// Color of the panel which looks the most alike `rgb`
+    ///
+    /// Same as `TriColor::from` with the CHROMATIC ink of the display instead of red. Use
+    /// [`Self::PALETTE`] with `image::draw_with_palette` to dither images for this display.
+    pub fn color_from_rgb(rgb: Rgb888) -> TriColor {
+        TriColor::from_rgb_in(rgb, CHROMATIC, &Self::PALETTE)
+    }
 }
 
 /// Same as `Display`, except that its characteristics are defined at runtime.
@@ -187,7 +476,7 @@ pub struct VarDisplay<'a, COLOR: ColorType + PixelColor> {
     height: u32,
     bwrbit: bool,
     buffer: &'a mut [u8],
-    rotation: DisplayRotation,
+    transform: DisplayTransform,
     _color: PhantomData<COLOR>,
 }
 
@@ -210,19 +499,12 @@ impl<COLOR: ColorType + PixelColor> DrawTarget for VarDisplay<'_, COLOR> {
 /// For use with embedded_grahics
 impl<COLOR: ColorType + PixelColor> OriginDimensions for VarDisplay<'_, COLOR> {
     fn size(&self) -> Size {
-        match self.rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                Size::new(self.width, self.height)
-            }
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                Size::new(self.height, self.width)
-            }
-        }
+        self.transform.size(self.width, self.height)
     }
 }
 
 /// Error found during usage of VarDisplay
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum VarDisplayError {
     /// The provided buffer was too small
     BufferTooSmall,
@@ -244,7 +526,7 @@ impl<'a, COLOR: ColorType + PixelColor> VarDisplay<'a, COLOR> {
             height,
             bwrbit,
             buffer,
-            rotation: DisplayRotation::default(),
+            transform: DisplayTransform::default(),
             _color: PhantomData,
         };
         // enfore some constraints dynamicly
@@ -271,14 +553,28 @@ impl<'a, COLOR: ColorType + PixelColor> VarDisplay<'a, COLOR> {
     /// Set the display rotation.
     ///
     /// This only concerns future drawing made to it. Anything aready drawn
-    /// stays as it is in the buffer.
+    /// stays as it is in the buffer, use `rotate_contents` to move it along.
+    /// A mirroring set with `set_transform` is kept.
     pub fn set_rotation(&mut self, rotation: DisplayRotation) {
-        self.rotation = rotation;
+        self.transform.rotation = rotation;
     }
 
     /// Get current rotation
     pub fn rotation(&self) -> DisplayRotation {
-        self.rotation
+        self.transform.rotation
+    }
+
+    /// Set the display orientation, rotation and mirroring.
+    ///
+    /// This only concerns future drawing made to it. Anything aready drawn
+    /// stays as it is in the buffer.
+    pub fn set_transform(&mut self, transform: DisplayTransform) {
+        self.transform = transform;
+    }
+
+    /// Get current orientation
+    pub fn transform(&self) -> DisplayTransform {
+        self.transform
     }
 
     /// Set a specific pixel color on this display
@@ -288,11 +584,104 @@ impl<'a, COLOR: ColorType + PixelColor> VarDisplay<'a, COLOR> {
             &mut self.buffer[..size],
             self.width,
             self.height,
-            self.rotation,
+            self.transform,
             self.bwrbit,
+            false,
             pixel,
         );
     }
+
+    /// Change the rotation and move everything already drawn along with it.
+    ///
+    /// See [`Display::rotate_contents`].
+    pub fn rotate_contents(&mut self, rotation: DisplayRotation) -> Result<(), TransformError> {
+        self.transform_contents(DisplayTransform::new(rotation, self.transform.mirror))
+    }
+
+    /// Change the orientation and move everything already drawn along with it.
+    ///
+    /// See [`Display::transform_contents`].
+    pub fn transform_contents(
+        &mut self,
+        transform: DisplayTransform,
+    ) -> Result<(), TransformError> {
+        let size = self.buffer_size();
+        transform_contents::<COLOR>(
+            &mut self.buffer[..size],
+            self.width,
+            self.height,
+            self.transform,
+            transform,
+        )?;
+        self.transform = transform;
+        Ok(())
+    }
+
+    /// Copy everything drawn on this display to `target`, with the orientation of `target`.
+    ///
+    /// See [`Display::transform_contents_into`].
+    pub fn transform_contents_into(&self, target: &mut VarDisplay<'_, COLOR>) {
+        let size = target.buffer_size();
+        transform_contents_into::<COLOR>(
+            self.buffer(),
+            self.width,
+            self.height,
+            self.transform,
+            &mut target.buffer[..size],
+            target.width,
+            target.height,
+            target.transform,
+        );
+    }
+
+    /// Get a view on a part of this display, see [`SubDisplay`]
+    ///
+    /// See [`Display::sub_display`].
+    pub fn sub_display(&mut self, area: Rect) -> SubDisplay<'_, COLOR> {
+        let size = self.buffer_size();
+        SubDisplay::new(
+            &mut self.buffer[..size],
+            self.width,
+            self.height,
+            self.bwrbit,
+            false,
+            self.transform,
+            area,
+        )
+    }
+
+    /// Get a read-only view on the buffer of this display, to be used as a source of [`VarDisplay::blit`]
+    pub fn bitmap(&self) -> Bitmap<'_, COLOR> {
+        Bitmap {
+            buffer: self.buffer(),
+            width: self.width,
+            height: self.height,
+            _color: PhantomData,
+        }
+    }
+
+    /// Combine the pixels of `src_rect` in `src` with the pixels of this display at `dst_point`
+    ///
+    /// See [`Display::blit`].
+    pub fn blit(
+        &mut self,
+        src: &Bitmap<'_, COLOR>,
+        src_rect: Rect,
+        dst_point: Point,
+        op: RasterOp,
+    ) {
+        let size = self.buffer_size();
+        blit::<COLOR>(
+            &mut self.buffer[..size],
+            self.width,
+            self.height,
+            self.transform,
+            src,
+            src_rect,
+            dst_point,
+            op,
+        );
+    }
 }
 
 /// Some Tricolor specifics
@@ -308,101 +697,966 @@ impl VarDisplay<'_, TriColor> {
     }
 }
 
-// This is a function to share code between `Display` and `VarDisplay`
-// It sets a specific pixel in a buffer to a given color.
-// The big number of parameters is due to the fact that it is an internal function to both
-// strctures.
-fn set_pixel<COLOR: ColorType + PixelColor>(
-    buffer: &mut [u8],
+/// Read-only pixel buffer with the same layout as the buffer of a [`Display`] of the same color,
+/// used as the source of [`Display::blit`] and [`VarDisplay::blit`]
+///
+/// This can be a pre-rendered tile, icon or glyph cache stored in flash, or the buffer of
+/// another display (see [`Display::bitmap`]).
+#[derive(Clone, Copy)]
+pub struct Bitmap<'a, COLOR: ColorType> {
+    buffer: &'a [u8],
     width: u32,
     height: u32,
-    rotation: DisplayRotation,
-    bwrbit: bool,
-    pixel: Pixel<COLOR>,
-) {
-    let Pixel(point, color) = pixel;
+    _color: PhantomData<COLOR>,
+}
 
-    // final coordinates
-    let (x, y) = match rotation {
-        // as i32 = never use more than 2 billion pixel per line or per column
-        DisplayRotation::Rotate0 => (point.x, point.y),
-        DisplayRotation::Rotate90 => (width as i32 - 1 - point.y, point.x),
-        DisplayRotation::Rotate180 => (width as i32 - 1 - point.x, height as i32 - 1 - point.y),
-        DisplayRotation::Rotate270 => (point.y, height as i32 - 1 - point.x),
-    };
+impl<'a, COLOR: ColorType> Bitmap<'a, COLOR> {
+    /// Use `buffer` as a bitmap of `width` x `height` pixels, it must be large enough to contain
+    /// all pixels. Lines are padded to full bytes as in a [`Display`].
+    pub fn new(buffer: &'a [u8], width: u32, height: u32) -> Result<Self, VarDisplayError> {
+        let size = height as usize
+            * line_bytes(width, COLOR::BITS_PER_PIXEL_PER_BUFFER)
+            * COLOR::BUFFER_COUNT;
+        if size > buffer.len() {
+            return Err(VarDisplayError::BufferTooSmall);
+        }
+        Ok(Self {
+            buffer: &buffer[..size],
+            width,
+            height,
+            _color: PhantomData,
+        })
+    }
 
-    // Out of range check
-    if (x < 0) || (x >= width as i32) || (y < 0) || (y >= height as i32) {
-        // don't do anything in case of out of range
-        return;
+    /// Width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
     }
 
-    let index = x as usize * COLOR::BITS_PER_PIXEL_PER_BUFFER / 8
-        + y as usize * line_bytes(width, COLOR::BITS_PER_PIXEL_PER_BUFFER);
-    let (mask, bits) = color.bitmask(bwrbit, x as u32);
+    /// Height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
 
-    if COLOR::BUFFER_COUNT == 2 {
-        // split buffer is for tricolor displays that use 2 buffer for 2 bits per pixel
-        buffer[index] = buffer[index] & mask | (bits & 0xFF) as u8;
-        let index = index + buffer.len() / 2;
-        buffer[index] = buffer[index] & mask | (bits >> 8) as u8;
-    } else {
-        buffer[index] = buffer[index] & mask | bits as u8;
+    /// Underlying buffer
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::color::*;
-    use embedded_graphics::{
-        prelude::*,
-        primitives::{Line, PrimitiveStyle},
-    };
-
-    // test buffer length
-    #[test]
-    fn graphics_size() {
-        // example definition taken from epd1in54
-        let display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
-        assert_eq!(display.buffer().len(), 5000);
-    }
+/// How the pixels of a source are combined with the pixels of a destination by a blit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RasterOp {
+    /// Replace the destination with the source
+    #[default]
+    Copy,
+    /// Keep the bits set in both the source and the destination
+    And,
+    /// Keep the bits set in the source or the destination
+    Or,
+    /// Toggle the destination bits set in the source
+    Xor,
+    /// Replace the destination with the inverted source
+    Not,
+}
 
-    // test default background color on all bytes
-    #[test]
-    fn graphics_default() {
-        let display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
-        for &byte in display.buffer() {
-            assert_eq!(byte, 0);
+impl RasterOp {
+    fn apply(self, dst: u8, src: u8) -> u8 {
+        match self {
+            RasterOp::Copy => src,
+            RasterOp::And => dst & src,
+            RasterOp::Or => dst | src,
+            RasterOp::Xor => dst ^ src,
+            RasterOp::Not => !src,
         }
     }
+}
 
-    #[test]
-    fn graphics_rotation_0() {
-        let mut display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
-        let _ = Line::new(Point::new(0, 0), Point::new(7, 0))
-            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
-            .draw(&mut display);
-
-        let buffer = display.buffer();
-
-        assert_eq!(buffer[0], Color::Black.get_byte_value());
+/// A [`Display`] which remembers the frame currently shown on the screen
+///
+/// Displays with a [`QuickRefresh`] need the old and the new frame to refresh.
+/// This keeps a copy of the last flushed frame, and [`flush`](DoubleBufferedDisplay::flush)
+/// only sends the part that changed since then.
+///
+/// Both frames start with the same content, draw the content which is already on the screen
+/// and call [`mark_displayed`](DoubleBufferedDisplay::mark_displayed) after a full refresh to get
+/// them in sync with the screen.
+///
+///```rust, no_run
+///# use embedded_hal_mock::eh1::*;
+///# fn main() -> Result<(), embedded_hal::spi::ErrorKind> {
+///# use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+///use epd_waveshare::{epd4in2::*, graphics::DoubleBufferedDisplay, prelude::*};
+///#
+///# let expectations = [];
+///# let mut spi = spi::Mock::new(&expectations);
+///# let expectations = [];
+///# let busy_in = digital::Mock::new(&expectations);
+///# let dc = digital::Mock::new(&expectations);
+///# let rst = digital::Mock::new(&expectations);
+///# let mut delay = delay::NoopDelay::new();
+///let mut epd = Epd4in2::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+///let mut display: DoubleBufferedDisplay<WIDTH, HEIGHT, false, { WIDTH as usize / 8 * HEIGHT as usize }, Color> =
+///    DoubleBufferedDisplay::default();
+///
+///display.clear(Color::White).ok();
+///epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)?;
+///display.mark_displayed();
+///
+///epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))?;
+///let _ = Line::new(Point::new(0, 120), Point::new(0, 295))
+///    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+///    .draw(&mut display);
+///display.flush(&mut epd, &mut spi, &mut delay)?;
+///# Ok(())
+///# }
+///```
+pub struct DoubleBufferedDisplay<
+    const WIDTH: u32,
+    const HEIGHT: u32,
+    const BWRBIT: bool,
+    const BYTECOUNT: usize,
+    COLOR: ColorType + PixelColor,
+    const BACKGROUND: u16 = 0,
+    const INVERTED: bool = false,
+    const CHROMATIC: u32 = { TriColor::RED_INK },
+> {
+    display: Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC>,
+    /// frame on the screen, also used as scratch buffer during `flush`
+    previous: [u8; BYTECOUNT],
+}
 
-        for &byte in buffer.iter().skip(1) {
-            assert_eq!(byte, 0);
+impl<
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        COLOR: ColorType + PixelColor,
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    > Default
+    for DoubleBufferedDisplay<
+        WIDTH,
+        HEIGHT,
+        BWRBIT,
+        BYTECOUNT,
+        COLOR,
+        BACKGROUND,
+        INVERTED,
+        CHROMATIC,
+    >
+{
+    // inline is necessary here to allow heap allocation via Box on stack limited programs
+    #[inline(always)]
+    fn default() -> Self {
+        let display = Display::default();
+        Self {
+            previous: display.buffer,
+            display,
         }
     }
+}
+
+/// For use with embedded_grahics
+impl<
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        COLOR: ColorType + PixelColor,
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    > DrawTarget
+    for DoubleBufferedDisplay<
+        WIDTH,
+        HEIGHT,
+        BWRBIT,
+        BYTECOUNT,
+        COLOR,
+        BACKGROUND,
+        INVERTED,
+        CHROMATIC,
+    >
+{
+    type Color = COLOR;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.draw_iter(pixels)
+    }
+}
+
+/// For use with embedded_grahics
+impl<
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        COLOR: ColorType + PixelColor,
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    > OriginDimensions
+    for DoubleBufferedDisplay<
+        WIDTH,
+        HEIGHT,
+        BWRBIT,
+        BYTECOUNT,
+        COLOR,
+        BACKGROUND,
+        INVERTED,
+        CHROMATIC,
+    >
+{
+    fn size(&self) -> Size {
+        self.display.size()
+    }
+}
+
+impl<
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        COLOR: ColorType + PixelColor,
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    >
+    DoubleBufferedDisplay<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC>
+{
+    /// get the internal buffer of the new frame
+    pub fn buffer(&self) -> &[u8] {
+        self.display.buffer()
+    }
+
+    /// get the internal buffer of the frame currently on the screen
+    pub fn previous_buffer(&self) -> &[u8] {
+        &self.previous
+    }
+
+    /// The display used for drawing the new frame
+    pub fn display(
+        &self,
+    ) -> &Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC> {
+        &self.display
+    }
+
+    /// The display used for drawing the new frame, e.g. to change its rotation
+    pub fn display_mut(
+        &mut self,
+    ) -> &mut Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC>
+    {
+        &mut self.display
+    }
+
+    /// Remember the new frame as the one on the screen
+    ///
+    /// Use this after sending it with a full refresh.
+    pub fn mark_displayed(&mut self) {
+        self.previous.copy_from_slice(self.display.buffer());
+    }
+
+    /// Part of the screen which changed since the last flush, in controller coordinates
+    ///
+    /// The window is extended to multiples of 8 pixels in x direction.
+    pub fn changed_window(&self) -> Option<Rect> {
+        changed_bytes::<COLOR>(&self.previous, self.display.buffer(), WIDTH, HEIGHT)
+            .map(|bytes| bytes.window::<COLOR>())
+    }
+
+    /// Send the part of the screen which changed since the last flush and refresh it
+    ///
+    /// The old and new frame data of the changed window are sent with
+    /// [`update_partial_old_frame`](QuickRefresh::update_partial_old_frame) and
+    /// [`update_partial_new_frame`](QuickRefresh::update_partial_new_frame), followed by
+    /// [`display_new_frame`](QuickRefresh::display_new_frame). Afterwards the new frame is
+    /// remembered as the one on the screen.
+    ///
+    /// Returns the refreshed window, nothing is sent if nothing changed.
+    /// After an error the remembered frame is lost, do a full refresh and
+    /// [`mark_displayed`](DoubleBufferedDisplay::mark_displayed) in this case.
+    pub fn flush<SPI, BUSY, DC, RST, DELAY, EPD>(
+        &mut self,
+        epd: &mut EPD,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<Option<Rect>, SPI::Error>
+    where
+        SPI: SpiDevice,
+        BUSY: InputPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: QuickRefresh<SPI, BUSY, DC, RST, DELAY>,
+    {
+        let bytes =
+            match changed_bytes::<COLOR>(&self.previous, self.display.buffer(), WIDTH, HEIGHT) {
+                Some(bytes) => bytes,
+                None => return Ok(None),
+            };
+        let window = bytes.window::<COLOR>();
+
+        // the previous frame isn't needed any more once its window is packed,
+        // so it is used as scratch buffer for both windows
+        let len = bytes.pack::<COLOR>(&mut self.previous, WIDTH);
+        epd.update_partial_old_frame(
+            spi,
+            delay,
+            &self.previous[..len],
+            window.x,
+            window.y,
+            window.w,
+            window.h,
+        )?;
+
+        self.mark_displayed();
+        bytes.pack::<COLOR>(&mut self.previous, WIDTH);
+        let result = epd.update_partial_new_frame(
+            spi,
+            delay,
+            &self.previous[..len],
+            window.x,
+            window.y,
+            window.w,
+            window.h,
+        );
+        self.mark_displayed();
+        result?;
+
+        epd.display_new_frame(spi, delay)?;
+        Ok(Some(window))
+    }
+}
+
+// Bounding box of the bytes which differ between two buffers, in controller coordinates
+struct ChangedBytes {
+    /// first byte column
+    column: usize,
+    /// number of byte columns
+    columns: usize,
+    /// first line
+    line: usize,
+    /// number of lines
+    lines: usize,
+}
+
+impl ChangedBytes {
+    fn window<COLOR: ColorType>(&self) -> Rect {
+        let pixels_per_byte = 8 / COLOR::BITS_PER_PIXEL_PER_BUFFER;
+        Rect::new(
+            (self.column * pixels_per_byte) as u32,
+            self.line as u32,
+            (self.columns * pixels_per_byte) as u32,
+            self.lines as u32,
+        )
+    }
+
+    // Move the lines of the box to the start of the buffer, plane after plane.
+    // Every line moves towards the start, so this works in place.
+    fn pack<COLOR: ColorType>(&self, buffer: &mut [u8], width: u32) -> usize {
+        let line_len = line_bytes(width, COLOR::BITS_PER_PIXEL_PER_BUFFER);
+        let plane_len = buffer.len() / COLOR::BUFFER_COUNT;
+        let mut pos = 0;
+        for plane in 0..COLOR::BUFFER_COUNT {
+            for line in self.line..self.line + self.lines {
+                let start = plane * plane_len + line * line_len + self.column;
+                buffer.copy_within(start..start + self.columns, pos);
+                pos += self.columns;
+            }
+        }
+        pos
+    }
+}
+
+// Compare two buffers, the box is extended to full multiples of 8 pixels
+fn changed_bytes<COLOR: ColorType>(
+    old: &[u8],
+    new: &[u8],
+    width: u32,
+    height: u32,
+) -> Option<ChangedBytes> {
+    let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+    let line_len = line_bytes(width, bits);
+    let plane_len = line_len * height as usize;
+    let (mut first_column, mut last_column) = (usize::MAX, 0);
+    let (mut first_line, mut last_line) = (usize::MAX, 0);
+    for (index, _) in old
+        .iter()
+        .zip(new.iter())
+        .take(plane_len * COLOR::BUFFER_COUNT)
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+    {
+        let (line, column) = (index % plane_len / line_len, index % line_len);
+        first_column = first_column.min(column);
+        last_column = last_column.max(column);
+        first_line = first_line.min(line);
+        last_line = last_line.max(line);
+    }
+    if first_line == usize::MAX {
+        return None;
+    }
+    // 8 pixels are `bits` bytes
+    let column = first_column / bits * bits;
+    let end_column = ((last_column / bits + 1) * bits).min(line_len);
+    Some(ChangedBytes {
+        column,
+        columns: end_column - column,
+        line: first_line,
+        lines: last_line + 1 - first_line,
+    })
+}
+
+/// A window on a part of a [`Display`] or [`VarDisplay`] with its own coordinates
+///
+/// Drawing at (0, 0) draws at the top left corner of the window, anything outside of the
+/// window is ignored. This way partial updates can be drawn directly in the full frame buffer.
+///
+/// The window can be exported in the format expected by
+/// [`update_partial_frame`](crate::prelude::WaveshareDisplay::update_partial_frame):
+/// [`window`](SubDisplay::window) gives the position and size in controller coordinates,
+/// extended to full bytes (multiples of 8 pixels in x direction) and respecting the orientation,
+/// and [`copy_packed`](SubDisplay::copy_packed) or [`rows`](SubDisplay::rows) give the data.
+///
+///```rust
+///# use epd_waveshare::{color::*, graphics::*, rect::Rect};
+///# use embedded_graphics::{prelude::*, primitives::{PrimitiveStyle, Rectangle}};
+///let mut display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
+///display.set_rotation(DisplayRotation::Rotate90);
+///
+///let mut sub = display.sub_display(Rect::new(20, 40, 30, 16));
+///let _ = Rectangle::new(Point::new(0, 0), Size::new(30, 16))
+///    .into_styled(PrimitiveStyle::with_fill(Color::White))
+///    .draw(&mut sub);
+///
+///let window = sub.window();
+///let mut buffer = [0u8; 200 * 200 / 8];
+///let len = sub.copy_packed(&mut buffer).unwrap();
+///assert_eq!(len, (window.w / 8 * window.h) as usize);
+///// epd.update_partial_frame(&mut spi, &mut delay, &buffer[..len], window.x, window.y, window.w, window.h)?;
+///```
+pub struct SubDisplay<'a, COLOR: ColorType + PixelColor> {
+    buffer: &'a mut [u8],
+    width: u32,
+    height: u32,
+    bwrbit: bool,
+    inverted: bool,
+    transform: DisplayTransform,
+    /// part of the drawing area which is used
+    area: Rect,
+    _color: PhantomData<COLOR>,
+}
+
+/// For use with embedded_grahics
+impl<COLOR: ColorType + PixelColor> DrawTarget for SubDisplay<'_, COLOR> {
+    type Color = COLOR;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            self.set_pixel(pixel);
+        }
+        Ok(())
+    }
+}
+
+/// For use with embedded_grahics
+impl<COLOR: ColorType + PixelColor> OriginDimensions for SubDisplay<'_, COLOR> {
+    fn size(&self) -> Size {
+        Size::new(self.area.w, self.area.h)
+    }
+}
+
+impl<'a, COLOR: ColorType + PixelColor> SubDisplay<'a, COLOR> {
+    fn new(
+        buffer: &'a mut [u8],
+        width: u32,
+        height: u32,
+        bwrbit: bool,
+        inverted: bool,
+        transform: DisplayTransform,
+        area: Rect,
+    ) -> Self {
+        let size = transform.size(width, height);
+        let area = area.intersect(Rect::new(0, 0, size.width, size.height));
+        Self {
+            buffer,
+            width,
+            height,
+            bwrbit,
+            inverted,
+            transform,
+            area,
+            _color: PhantomData,
+        }
+    }
+
+    /// Part of the drawing area covered by this window, in drawing coordinates
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    /// Set a specific pixel color, relative to the top left corner of the window
+    pub fn set_pixel(&mut self, pixel: Pixel<COLOR>) {
+        let Pixel(point, color) = pixel;
+        if point.x < 0
+            || point.y < 0
+            || point.x >= self.area.w as i32
+            || point.y >= self.area.h as i32
+        {
+            return;
+        }
+        let point = point + Point::new(self.area.x as i32, self.area.y as i32);
+        set_pixel(
+            self.buffer,
+            self.width,
+            self.height,
+            self.transform,
+            self.bwrbit,
+            self.inverted,
+            Pixel(point, color),
+        );
+    }
+
+    /// Position and size of the window in controller coordinates
+    ///
+    /// This is what `update_partial_frame` expects as `x`, `y`, `width` and `height`.
+    /// It is extended to full bytes, so it might be a bit larger than the area.
+    pub fn window(&self) -> Rect {
+        if self.area.is_empty() {
+            return Rect::default();
+        }
+        let first = self.transform.apply(
+            Point::new(self.area.x as i32, self.area.y as i32),
+            self.width,
+            self.height,
+        );
+        let last = self.transform.apply(
+            Point::new(
+                (self.area.x + self.area.w - 1) as i32,
+                (self.area.y + self.area.h - 1) as i32,
+            ),
+            self.width,
+            self.height,
+        );
+        let x = first.x.min(last.x) as u32 & !7;
+        let y = first.y.min(last.y) as u32;
+        let end_x = (first.x.max(last.x) as u32 + 8) & !7;
+        let end_y = first.y.max(last.y) as u32 + 1;
+        Rect::new(x, y, end_x - x, end_y - y)
+    }
+
+    /// Number of bytes needed by [`copy_packed`](SubDisplay::copy_packed)
+    pub fn packed_len(&self) -> usize {
+        let window = self.window();
+        window.h as usize
+            * line_bytes(window.w, COLOR::BITS_PER_PIXEL_PER_BUFFER)
+            * COLOR::BUFFER_COUNT
+    }
+
+    /// Iterate over the lines of the window, top to bottom in controller coordinates
+    ///
+    /// `plane` selects the buffer for colors using more than one buffer (0 is black/white and 1
+    /// is chromatic for [`TriColor`]). Nothing is copied, this can be used to stream the window.
+    pub fn rows(&self, plane: usize) -> impl Iterator<Item = &[u8]> + '_ {
+        let window = self.window();
+        let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+        let line = line_bytes(self.width, bits);
+        let start = window.x as usize * bits / 8;
+        let len = line_bytes(window.w, bits);
+        let offset = plane * self.buffer.len() / COLOR::BUFFER_COUNT;
+        (window.y..window.y + window.h).map(move |y| {
+            let begin = offset + y as usize * line + start;
+            &self.buffer[begin..begin + len]
+        })
+    }
+
+    /// Copy the window into a tightly packed buffer and return the number of bytes used
+    ///
+    /// The layout is the same as the one of a full display of the size of the window: all lines
+    /// of the first buffer followed by all lines of the second buffer for [`TriColor`].
+    pub fn copy_packed(&self, target: &mut [u8]) -> Result<usize, VarDisplayError> {
+        let len = self.packed_len();
+        if target.len() < len {
+            return Err(VarDisplayError::BufferTooSmall);
+        }
+        let mut pos = 0;
+        for plane in 0..COLOR::BUFFER_COUNT {
+            for row in self.rows(plane) {
+                target[pos..pos + row.len()].copy_from_slice(row);
+                pos += row.len();
+            }
+        }
+        Ok(len)
+    }
+}
+
+// This is a function to share code between `Display` and `VarDisplay`
+// It sets a specific pixel in a buffer to a given color.
+// The big number of parameters is due to the fact that it is an internal function to both
+// strctures.
+fn set_pixel<COLOR: ColorType + PixelColor>(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    transform: DisplayTransform,
+    bwrbit: bool,
+    inverted: bool,
+    pixel: Pixel<COLOR>,
+) {
+    let Pixel(point, color) = pixel;
+
+    // final coordinates
+    let Point { x, y } = transform.apply(point, width, height);
+
+    // Out of range check
+    if (x < 0) || (x >= width as i32) || (y < 0) || (y >= height as i32) {
+        // don't do anything in case of out of range
+        return;
+    }
+
+    let index = x as usize * COLOR::BITS_PER_PIXEL_PER_BUFFER / 8
+        + y as usize * line_bytes(width, COLOR::BITS_PER_PIXEL_PER_BUFFER);
+    let (mask, mut bits) = color.bitmask(bwrbit, x as u32);
+    if inverted {
+        // only the black/white bits change their polarity
+        bits ^= (!mask) as u16;
+    }
+
+    if COLOR::BUFFER_COUNT == 2 {
+        // split buffer is for tricolor displays that use 2 buffer for 2 bits per pixel
+        buffer[index] = buffer[index] & mask | (bits & 0xFF) as u8;
+        let index = index + buffer.len() / 2;
+        buffer[index] = buffer[index] & mask | (bits >> 8) as u8;
+    } else {
+        buffer[index] = buffer[index] & mask | bits as u8;
+    }
+}
+
+// Position of a pixel in a single buffer plane:
+// the byte index and the shift of the pixel bits inside of this byte
+fn pixel_position<COLOR: ColorType>(width: u32, x: u32, y: u32) -> (usize, usize) {
+    let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+    let index = x as usize * bits / 8 + y as usize * line_bytes(width, bits);
+    let shift = 8 - bits - x as usize * bits % 8;
+    (index, shift)
+}
+
+// Get the raw bits of a pixel, the bits of the second plane (if any) are in the high byte
+pub(crate) fn get_raw<COLOR: ColorType>(buffer: &[u8], width: u32, x: u32, y: u32) -> u16 {
+    let (index, shift) = pixel_position::<COLOR>(width, x, y);
+    let mask = (1u16 << COLOR::BITS_PER_PIXEL_PER_BUFFER) - 1;
+    let mut raw = (buffer[index] >> shift) as u16 & mask;
+    if COLOR::BUFFER_COUNT == 2 {
+        let index = index + buffer.len() / 2;
+        raw |= ((buffer[index] >> shift) as u16 & mask) << 8;
+    }
+    raw
+}
+
+// Set the raw bits of a pixel as returned by `get_raw`
+fn set_raw<COLOR: ColorType>(buffer: &mut [u8], width: u32, x: u32, y: u32, raw: u16) {
+    let (index, shift) = pixel_position::<COLOR>(width, x, y);
+    let mask = (((1u16 << COLOR::BITS_PER_PIXEL_PER_BUFFER) - 1) as u8) << shift;
+    buffer[index] = buffer[index] & !mask | ((raw as u8) << shift) & mask;
+    if COLOR::BUFFER_COUNT == 2 {
+        let index = index + buffer.len() / 2;
+        buffer[index] = buffer[index] & !mask | (((raw >> 8) as u8) << shift) & mask;
+    }
+}
+
+// Combine a rectangle of `src` with the buffer at a point in drawing coordinates.
+// Without transformation every line is handled byte by byte, otherwise pixel by pixel.
+#[allow(clippy::too_many_arguments)]
+fn blit<COLOR: ColorType>(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    transform: DisplayTransform,
+    src: &Bitmap<'_, COLOR>,
+    src_rect: Rect,
+    dst_point: Point,
+    op: RasterOp,
+) {
+    let mut rect = src_rect.intersect(Rect::new(0, 0, src.width, src.height));
+    let mut dst_point = dst_point;
+    // cut off what is left of or above the drawing area
+    if dst_point.x < 0 {
+        let cut = dst_point.x.unsigned_abs().min(rect.w);
+        rect = Rect::new(rect.x + cut, rect.y, rect.w - cut, rect.h);
+        dst_point.x = 0;
+    }
+    if dst_point.y < 0 {
+        let cut = dst_point.y.unsigned_abs().min(rect.h);
+        rect = Rect::new(rect.x, rect.y + cut, rect.w, rect.h - cut);
+        dst_point.y = 0;
+    }
+    // and what is right of or below it
+    let size = transform.size(width, height);
+    let (dst_x, dst_y) = (dst_point.x as u32, dst_point.y as u32);
+    rect.w = rect.w.min(size.width.saturating_sub(dst_x));
+    rect.h = rect.h.min(size.height.saturating_sub(dst_y));
+    if rect.is_empty() {
+        return;
+    }
+
+    if transform == DisplayTransform::default() {
+        let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+        let src_line = line_bytes(src.width, bits);
+        let dst_line = line_bytes(width, bits);
+        for plane in 0..COLOR::BUFFER_COUNT {
+            let src_plane = plane * src.buffer.len() / COLOR::BUFFER_COUNT;
+            let dst_plane = plane * buffer.len() / COLOR::BUFFER_COUNT;
+            for line in 0..rect.h as usize {
+                let src_start = src_plane + (rect.y as usize + line) * src_line;
+                let dst_start = dst_plane + (dst_y as usize + line) * dst_line;
+                blit_line(
+                    &mut buffer[dst_start..dst_start + dst_line],
+                    dst_x as usize * bits,
+                    &src.buffer[src_start..src_start + src_line],
+                    rect.x as usize * bits,
+                    rect.w as usize * bits,
+                    op,
+                );
+            }
+        }
+    } else {
+        for y in 0..rect.h {
+            for x in 0..rect.w {
+                let point = Point::new((dst_x + x) as i32, (dst_y + y) as i32);
+                let Point { x: px, y: py } = transform.apply(point, width, height);
+                let (px, py) = (px as u32, py as u32);
+                let raw = get_raw::<COLOR>(src.buffer, src.width, rect.x + x, rect.y + y);
+                let old = get_raw::<COLOR>(buffer, width, px, py);
+                let low = op.apply(old as u8, raw as u8) as u16;
+                let high = op.apply((old >> 8) as u8, (raw >> 8) as u8) as u16;
+                set_raw::<COLOR>(buffer, width, px, py, high << 8 | low);
+            }
+        }
+    }
+}
+
+// Combine `bits` bits of a source line starting at bit `src_bit` with a destination line
+// starting at bit `dst_bit`, one destination byte at a time.
+fn blit_line(
+    dst: &mut [u8],
+    dst_bit: usize,
+    src: &[u8],
+    src_bit: usize,
+    bits: usize,
+    op: RasterOp,
+) {
+    // 8 bits of the source starting at `bit`, anything outside of the line reads as 0
+    let read = |bit: isize| {
+        let byte = bit.div_euclid(8);
+        let get = |index: isize| {
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| src.get(index))
+                .map_or(0, |&byte| byte as u16)
+        };
+        let word = get(byte) << 8 | get(byte + 1);
+        (word << bit.rem_euclid(8) >> 8) as u8
+    };
+    let end = dst_bit + bits;
+    let bytes = &mut dst[dst_bit / 8..(end + 7) / 8];
+    for (offset, byte) in bytes.iter_mut().enumerate() {
+        let start = (dst_bit / 8 + offset) * 8;
+        let first = start.max(dst_bit) - start;
+        let last = (start + 8).min(end) - start;
+        let mask = ((0xFFu16 >> first) & !(0xFFu16 >> last)) as u8;
+        let value = read(src_bit as isize + start as isize - dst_bit as isize);
+        *byte = *byte & !mask | op.apply(*byte, value) & mask;
+    }
+}
+
+// Move every pixel of a buffer drawn with `from` to where it would have been drawn with `to`.
+// Every dihedral transformation only has cycles of up to 4 pixels, they are rotated in place
+// starting from the smallest index of each cycle.
+fn transform_contents<COLOR: ColorType>(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    from: DisplayTransform,
+    to: DisplayTransform,
+) -> Result<(), TransformError> {
+    if from.size(width, height) != to.size(width, height) {
+        return Err(TransformError::SizeMismatch);
+    }
+    let next = |index: u32| {
+        let point = Point::new((index % width) as i32, (index / width) as i32);
+        let point = to.apply(from.apply_inverse(point, width, height), width, height);
+        point.y as u32 * width + point.x as u32
+    };
+    for start in 0..width * height {
+        let mut index = next(start);
+        while index > start {
+            index = next(index);
+        }
+        if index < start {
+            // this cycle has already been handled
+            continue;
+        }
+        let mut carry = get_raw::<COLOR>(buffer, width, start % width, start / width);
+        let mut index = next(start);
+        while index != start {
+            let (x, y) = (index % width, index / width);
+            let raw = get_raw::<COLOR>(buffer, width, x, y);
+            set_raw::<COLOR>(buffer, width, x, y, carry);
+            carry = raw;
+            index = next(index);
+        }
+        set_raw::<COLOR>(buffer, width, start % width, start / width, carry);
+    }
+    Ok(())
+}
+
+// Copy every pixel of a buffer drawn with `from` to a buffer drawn with `to`
+#[allow(clippy::too_many_arguments)]
+fn transform_contents_into<COLOR: ColorType>(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    from: DisplayTransform,
+    target: &mut [u8],
+    target_width: u32,
+    target_height: u32,
+    to: DisplayTransform,
+) {
+    for y in 0..height {
+        for x in 0..width {
+            let point = from.apply_inverse(Point::new(x as i32, y as i32), width, height);
+            let Point {
+                x: target_x,
+                y: target_y,
+            } = to.apply(point, target_width, target_height);
+            if target_x < 0
+                || target_x >= target_width as i32
+                || target_y < 0
+                || target_y >= target_height as i32
+            {
+                continue;
+            }
+            let raw = get_raw::<COLOR>(buffer, width, x, y);
+            set_raw::<COLOR>(target, target_width, target_x as u32, target_y as u32, raw);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::*;
+    extern crate std;
+    use embedded_graphics::{
+        prelude::*,
+        primitives::{Line, PrimitiveStyle},
+    };
+
+    // test buffer length
+    #[test]
+    fn graphics_size() {
+        // example definition taken from epd1in54
+        let display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
+        assert_eq!(display.buffer().len(), 5000);
+    }
+
+    // test default background color on all bytes
+    #[test]
+    fn graphics_default() {
+        let display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
+        for &byte in display.buffer() {
+            assert_eq!(byte, 0);
+        }
+    }
+
+    // 2 bits per pixel, lines are padded to full bytes
+    #[test]
+    fn graphics_quadcolor() {
+        let mut display = Display::<6, 2, false, 4, QuadColor, 0x55>::default();
+        assert_eq!(display.buffer(), [0x55; 4]);
+
+        display.set_pixel(Pixel(Point::new(0, 0), QuadColor::Red));
+        display.set_pixel(Pixel(Point::new(5, 1), QuadColor::Yellow));
+        assert_eq!(display.buffer(), [0b11_01_01_01, 0x55, 0x55, 0b01_10_01_01]);
+
+        display.set_rotation(DisplayRotation::Rotate90);
+        display.set_pixel(Pixel(Point::new(0, 0), QuadColor::Black));
+        assert_eq!(display.buffer()[1], 0b01_00_01_01);
+        assert_eq!(
+            get_raw::<QuadColor>(display.buffer(), 6, 5, 0),
+            QuadColor::Black.get_bits() as u16
+        );
+    }
+
+    // test the blank frame of a display with a background and a polarity
+    #[test]
+    fn graphics_default_background() {
+        let display = Display::<16, 2, false, { 16 * 2 / 8 }, Color, 0xFF>::default();
+        assert_eq!(display.buffer(), [0xFF; 4]);
+
+        let display = Display::<16, 2, true, { 16 * 2 * 2 / 8 }, TriColor, 0xFF>::default();
+        assert_eq!(display.bw_buffer(), [0xFF; 4]);
+        assert_eq!(display.chromatic_buffer(), [0x00; 4]);
+
+        let display = Display::<4, 1, false, { 4 * 4 / 8 }, OctColor, 0x11>::default();
+        assert_eq!(display.buffer(), [0x11; 2]);
+
+        let double = DoubleBufferedDisplay::<16, 2, false, { 16 * 2 / 8 }, Color, 0xFF>::default();
+        assert_eq!(double.previous_buffer(), [0xFF; 4]);
+    }
+
+    // white is 0 on inverted displays, clear() gives the same frame as default()
+    #[test]
+    fn graphics_default_inverted() {
+        let mut display = Display::<16, 2, false, { 16 * 2 / 8 }, Color, 0xFF, true>::default();
+        assert_eq!(display.buffer(), [0x00; 4]);
+
+        display.set_pixel(Pixel(Point::new(1, 0), Color::Black));
+        display
+            .sub_display(Rect::new(8, 1, 8, 1))
+            .set_pixel(Pixel(Point::new(0, 0), Color::Black));
+        assert_eq!(display.buffer(), [0x40, 0x00, 0x00, 0x80]);
+
+        display.clear(Color::White).unwrap();
+        assert_eq!(display.buffer(), [0x00; 4]);
+    }
+
+    #[test]
+    fn graphics_rotation_0() {
+        let mut display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
+        let _ = Line::new(Point::new(0, 0), Point::new(7, 0))
+            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+            .draw(&mut display);
+
+        let buffer = display.buffer();
+
+        assert_eq!(buffer[0], Color::Black.get_byte_value());
+
+        for &byte in buffer.iter().skip(1) {
+            assert_eq!(byte, 0);
+        }
+    }
+
+    #[test]
+    fn graphics_rotation_90() {
+        let mut display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
+        display.set_rotation(DisplayRotation::Rotate90);
+        let _ = Line::new(Point::new(0, 192), Point::new(0, 199))
+            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+            .draw(&mut display);
+
+        let buffer = display.buffer();
 
-    #[test]
-    fn graphics_rotation_90() {
-        let mut display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
-        display.set_rotation(DisplayRotation::Rotate90);
-        let _ = Line::new(Point::new(0, 192), Point::new(0, 199))
-            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
-            .draw(&mut display);
-
-        let buffer = display.buffer();
-
         assert_eq!(buffer[0], Color::Black.get_byte_value());
 
         for &byte in buffer.iter().skip(1) {
@@ -450,6 +1704,489 @@ mod tests {
         }
     }
 
+    #[test]
+    fn graphics_mirror_horizontal() {
+        let mut display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
+        display.set_transform(DisplayTransform::default().mirror_horizontal());
+        let _ = Line::new(Point::new(192, 0), Point::new(199, 0))
+            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+            .draw(&mut display);
+
+        let buffer = display.buffer();
+
+        assert_eq!(buffer[0], Color::Black.get_byte_value());
+
+        for &byte in buffer.iter().skip(1) {
+            assert_eq!(byte, 0);
+        }
+    }
+
+    #[test]
+    fn graphics_mirror_vertical_rotation_90() {
+        let mut display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
+        display.set_transform(DisplayTransform::from(DisplayRotation::Rotate90).mirror_vertical());
+        let _ = Line::new(Point::new(0, 0), Point::new(0, 7))
+            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+            .draw(&mut display);
+
+        let buffer = display.buffer();
+
+        assert_eq!(buffer[0], Color::Black.get_byte_value());
+
+        for &byte in buffer.iter().skip(1) {
+            assert_eq!(byte, 0);
+        }
+    }
+
+    // all 8 orientations must map the drawing area one to one onto the buffer, and differently
+    #[test]
+    fn graphics_transform_dihedral() {
+        let rotations = [
+            DisplayRotation::Rotate0,
+            DisplayRotation::Rotate90,
+            DisplayRotation::Rotate180,
+            DisplayRotation::Rotate270,
+        ];
+        let mut corners = [Point::zero(); 8];
+        for (i, transform) in rotations
+            .iter()
+            .flat_map(|&r| [false, true].map(|m| DisplayTransform::new(r, m)))
+            .enumerate()
+        {
+            let size = transform.size(3, 2);
+            let mut seen = [false; 6];
+            for y in 0..size.height as i32 {
+                for x in 0..size.width as i32 {
+                    let p = transform.apply(Point::new(x, y), 3, 2);
+                    assert!(p.x >= 0 && p.x < 3 && p.y >= 0 && p.y < 2);
+                    assert!(!seen[(p.y * 3 + p.x) as usize]);
+                    seen[(p.y * 3 + p.x) as usize] = true;
+                }
+            }
+            // where the drawing origin and its right neighbour land identify the orientation
+            corners[i] =
+                transform.apply(Point::zero(), 3, 2) * 4 + transform.apply(Point::new(1, 0), 3, 2);
+        }
+        for i in 0..corners.len() {
+            for j in i + 1..corners.len() {
+                assert_ne!(corners[i], corners[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn graphics_rotate_contents_square() {
+        let mut display = Display::<16, 16, false, { 16 * 16 / 8 }, Color>::default();
+        let _ = Line::new(Point::new(0, 0), Point::new(7, 0))
+            .into_styled(PrimitiveStyle::with_stroke(Color::White, 1))
+            .draw(&mut display);
+
+        display.rotate_contents(DisplayRotation::Rotate90).unwrap();
+        assert_eq!(display.rotation(), DisplayRotation::Rotate90);
+
+        // the same line drawn with the new rotation
+        let mut expected = Display::<16, 16, false, { 16 * 16 / 8 }, Color>::default();
+        expected.set_rotation(DisplayRotation::Rotate90);
+        let _ = Line::new(Point::new(0, 0), Point::new(7, 0))
+            .into_styled(PrimitiveStyle::with_stroke(Color::White, 1))
+            .draw(&mut expected);
+        assert_eq!(display.buffer(), expected.buffer());
+
+        // and all the way back
+        display
+            .transform_contents(DisplayTransform::default())
+            .unwrap();
+        assert_eq!(display.buffer()[0], 0xFF);
+        for &byte in display.buffer().iter().skip(1) {
+            assert_eq!(byte, 0);
+        }
+    }
+
+    #[test]
+    fn graphics_rotate_contents_octcolor() {
+        let mut display = Display::<4, 4, false, { 4 * 4 * 4 / 8 }, OctColor>::default();
+        display.set_pixel(Pixel(Point::new(0, 0), OctColor::Red));
+        display.set_pixel(Pixel(Point::new(1, 0), OctColor::Green));
+        display.set_pixel(Pixel(Point::new(0, 3), OctColor::Blue));
+
+        display
+            .transform_contents(DisplayTransform::new(DisplayRotation::Rotate270, true))
+            .unwrap();
+
+        let mut expected = Display::<4, 4, false, { 4 * 4 * 4 / 8 }, OctColor>::default();
+        expected.set_transform(DisplayTransform::new(DisplayRotation::Rotate270, true));
+        expected.set_pixel(Pixel(Point::new(0, 0), OctColor::Red));
+        expected.set_pixel(Pixel(Point::new(1, 0), OctColor::Green));
+        expected.set_pixel(Pixel(Point::new(0, 3), OctColor::Blue));
+        assert_eq!(display.buffer(), expected.buffer());
+    }
+
+    #[test]
+    fn graphics_rotate_contents_tricolor() {
+        let mut display = Display::<8, 4, true, { 8 * 4 * 2 / 8 }, TriColor>::default();
+        display.set_pixel(Pixel(Point::new(0, 0), TriColor::White));
+        display.set_pixel(Pixel(Point::new(1, 0), TriColor::Chromatic));
+        display.set_pixel(Pixel(Point::new(7, 3), TriColor::Chromatic));
+
+        display.rotate_contents(DisplayRotation::Rotate180).unwrap();
+
+        assert_eq!(display.bw_buffer(), [0, 0, 0, 0x01]);
+        assert_eq!(display.chromatic_buffer(), [0x80, 0, 0, 0x02]);
+    }
+
+    #[test]
+    fn graphics_rotate_contents_non_square() {
+        let mut display = Display::<16, 8, false, { 16 * 8 / 8 }, Color>::default();
+        display.set_pixel(Pixel(Point::new(15, 0), Color::White));
+
+        assert_eq!(
+            display.rotate_contents(DisplayRotation::Rotate90),
+            Err(TransformError::SizeMismatch)
+        );
+        assert_eq!(display.rotation(), DisplayRotation::Rotate0);
+
+        // drawing area of the target is 16x8 again
+        let mut buffer = [0u8; 16 * 8 / 8];
+        let mut target = VarDisplay::<Color>::new(8, 16, &mut buffer, false).unwrap();
+        target.set_rotation(DisplayRotation::Rotate90);
+        display.transform_contents_into(&mut target);
+
+        let mut expected = [0u8; 16 * 8 / 8];
+        let mut expected = VarDisplay::<Color>::new(8, 16, &mut expected, false).unwrap();
+        expected.set_rotation(DisplayRotation::Rotate90);
+        expected.set_pixel(Pixel(Point::new(15, 0), Color::White));
+        assert_eq!(target.buffer(), expected.buffer());
+    }
+
+    #[test]
+    fn graphics_sub_display() {
+        let mut display = Display::<32, 16, false, { 32 * 16 / 8 }, Color>::default();
+        let mut sub = display.sub_display(Rect::new(10, 2, 4, 3));
+        assert_eq!(sub.size(), Size::new(4, 3));
+        let _ = Line::new(Point::new(0, 0), Point::new(7, 0))
+            .into_styled(PrimitiveStyle::with_stroke(Color::White, 1))
+            .draw(&mut sub);
+
+        assert_eq!(sub.window(), Rect::new(8, 2, 8, 3));
+        let mut packed = [0u8; 8];
+        assert_eq!(sub.copy_packed(&mut packed), Ok(3));
+        assert_eq!(packed[..3], [0b0011_1100, 0, 0]);
+
+        // only the part inside of the window has been drawn
+        assert_eq!(display.buffer()[2 * 4 + 1], 0b0011_1100);
+        assert_eq!(display.buffer().iter().filter(|&&b| b != 0).count(), 1);
+    }
+
+    #[test]
+    fn graphics_sub_display_rotated() {
+        let mut display = Display::<32, 16, false, { 32 * 16 / 8 }, Color>::default();
+        display.set_rotation(DisplayRotation::Rotate90);
+        // drawing area is 16x32, the area is on the left side of it, so at the top of the buffer
+        let mut sub = display.sub_display(Rect::new(0, 20, 4, 12));
+        let _ = Line::new(Point::new(0, 0), Point::new(0, 11))
+            .into_styled(PrimitiveStyle::with_stroke(Color::White, 1))
+            .draw(&mut sub);
+
+        assert_eq!(sub.window(), Rect::new(0, 0, 16, 4));
+        let rows: std::vec::Vec<&[u8]> = sub.rows(0).collect();
+        assert_eq!(rows, [[0xFF, 0xF0], [0, 0], [0, 0], [0, 0]]);
+    }
+
+    #[test]
+    fn graphics_sub_display_tricolor() {
+        let mut display = Display::<16, 4, true, { 16 * 4 * 2 / 8 }, TriColor>::default();
+        let mut sub = display.sub_display(Rect::new(8, 1, 100, 100));
+        assert_eq!(sub.area(), Rect::new(8, 1, 8, 3));
+        sub.set_pixel(Pixel(Point::new(0, 0), TriColor::Chromatic));
+        sub.set_pixel(Pixel(Point::new(1, 2), TriColor::White));
+
+        let mut packed = [0u8; 6];
+        assert_eq!(sub.copy_packed(&mut packed), Ok(6));
+        assert_eq!(packed, [0, 0, 0x40, 0x80, 0, 0]);
+    }
+
+    #[test]
+    fn graphics_sub_display_large_area() {
+        let mut display = Display::<16, 4, false, { 16 * 4 / 8 }, Color>::default();
+        let sub = display.sub_display(Rect::new(8, 1, u32::MAX, u32::MAX));
+        assert_eq!(sub.area(), Rect::new(8, 1, 8, 3));
+        let sub = display.sub_display(Rect::new(u32::MAX, u32::MAX, 2, 2));
+        assert!(sub.area().is_empty());
+        assert_eq!(sub.window(), Rect::default());
+
+        display.clear(Color::White).unwrap();
+        let src = [0u8; 2];
+        let src = Bitmap::<Color>::new(&src, 8, 2).unwrap();
+        display.blit(
+            &src,
+            Rect::new(0, 0, u32::MAX, u32::MAX),
+            Point::new(8, 2),
+            RasterOp::Copy,
+        );
+        assert_eq!(display.buffer(), [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0xFF, 0]);
+    }
+
+    // Reference blit working pixel by pixel on the raw bits of unrotated buffers
+    fn blit_reference<COLOR: ColorType>(
+        dst: &mut [u8],
+        width: u32,
+        src: &Bitmap<'_, COLOR>,
+        rect: Rect,
+        (x, y): (u32, u32),
+        op: RasterOp,
+    ) {
+        for dy in 0..rect.h {
+            for dx in 0..rect.w {
+                let raw = get_raw::<COLOR>(src.buffer(), src.width(), rect.x + dx, rect.y + dy);
+                let old = get_raw::<COLOR>(dst, width, x + dx, y + dy);
+                let low = op.apply(old as u8, raw as u8) as u16;
+                let high = op.apply((old >> 8) as u8, (raw >> 8) as u8) as u16;
+                set_raw::<COLOR>(dst, width, x + dx, y + dy, high << 8 | low);
+            }
+        }
+    }
+
+    #[test]
+    fn graphics_blit_bit_offsets() {
+        let tile: [u8; 9] = [0xA5, 0x3C, 0xF0, 0x0F, 0x81, 0x7E, 0x55, 0xC3, 0x99];
+        let src = Bitmap::<Color>::new(&tile, 24, 3).unwrap();
+        for op in [
+            RasterOp::Copy,
+            RasterOp::And,
+            RasterOp::Or,
+            RasterOp::Xor,
+            RasterOp::Not,
+        ] {
+            for (rect, x) in [
+                (Rect::new(0, 0, 24, 3), 0),
+                (Rect::new(3, 1, 13, 2), 5),
+                (Rect::new(7, 0, 2, 3), 14),
+                (Rect::new(1, 0, 22, 3), 9),
+            ] {
+                let mut display = Display::<32, 4, false, { 32 * 4 / 8 }, Color>::default();
+                display.buffer.iter_mut().enumerate().for_each(|(i, byte)| {
+                    *byte = (i as u8).wrapping_mul(0x3B);
+                });
+                let mut expected = display.buffer;
+                blit_reference::<Color>(&mut expected, 32, &src, rect, (x, 1), op);
+
+                display.blit(&src, rect, Point::new(x as i32, 1), op);
+                assert_eq!(display.buffer(), &expected, "{:?} {:?} {}", op, rect, x);
+            }
+        }
+    }
+
+    #[test]
+    fn graphics_bitmap_tricolor_planes() {
+        // 3 pixels wide: one byte per line and plane, two planes of 2 lines
+        let tile = [0x20, 0x40, 0x80, 0x00];
+        assert_eq!(
+            Bitmap::<TriColor>::new(&tile[..3], 3, 2).err(),
+            Some(VarDisplayError::BufferTooSmall)
+        );
+        let src = Bitmap::<TriColor>::new(&tile, 3, 2).unwrap();
+        assert_eq!(src.buffer().len(), 4);
+
+        let mut display = Display::<8, 2, true, { 8 * 2 * 2 / 8 }, TriColor>::default();
+        display.blit(&src, Rect::new(0, 0, 3, 2), Point::zero(), RasterOp::Copy);
+        assert_eq!(display.bw_buffer(), [0x20, 0x40]);
+        assert_eq!(display.chromatic_buffer(), [0x80, 0x00]);
+    }
+
+    #[test]
+    fn graphics_blit_tricolor_octcolor() {
+        let mut source = Display::<8, 2, true, { 8 * 2 * 2 / 8 }, TriColor>::default();
+        source.set_pixel(Pixel(Point::new(1, 0), TriColor::Chromatic));
+        source.set_pixel(Pixel(Point::new(2, 1), TriColor::White));
+        let mut display = Display::<16, 2, true, { 16 * 2 * 2 / 8 }, TriColor>::default();
+        display.blit(
+            &source.bitmap(),
+            Rect::new(0, 0, 8, 2),
+            Point::new(3, 0),
+            RasterOp::Copy,
+        );
+        assert_eq!(display.bw_buffer(), [0, 0, 0x04, 0]);
+        assert_eq!(display.chromatic_buffer(), [0x08, 0, 0, 0]);
+
+        let mut source = Display::<4, 1, false, { 4 * 4 / 8 }, OctColor>::default();
+        source.buffer.copy_from_slice(&[0x12, 0x34]);
+        let mut display = Display::<6, 1, false, { 6 * 4 / 8 }, OctColor>::default();
+        display.buffer.copy_from_slice(&[0x77, 0x77, 0x77]);
+        display.blit(
+            &source.bitmap(),
+            Rect::new(1, 0, 3, 1),
+            Point::new(1, 0),
+            RasterOp::Copy,
+        );
+        assert_eq!(display.buffer(), [0x72, 0x34, 0x77]);
+        display.blit(
+            &source.bitmap(),
+            Rect::new(0, 0, 1, 1),
+            Point::new(5, 0),
+            RasterOp::Xor,
+        );
+        assert_eq!(display.buffer(), [0x72, 0x34, 0x76]);
+    }
+
+    #[test]
+    fn graphics_blit_rotated_and_clipped() {
+        let mut source = Display::<8, 3, false, { 8 * 3 / 8 }, Color>::default();
+        Line::new(Point::new(0, 0), Point::new(7, 2))
+            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+            .draw(&mut source)
+            .unwrap();
+        let src = source.bitmap();
+
+        for transform in [
+            DisplayTransform::new(DisplayRotation::Rotate90, false),
+            DisplayTransform::new(DisplayRotation::Rotate180, true),
+        ] {
+            let mut display = Display::<16, 16, false, { 16 * 16 / 8 }, Color>::default();
+            display.set_transform(transform);
+            display.blit(
+                &src,
+                Rect::new(0, 0, 8, 3),
+                Point::new(-2, 14),
+                RasterOp::Or,
+            );
+
+            let mut expected = Display::<16, 16, false, { 16 * 16 / 8 }, Color>::default();
+            expected.set_transform(transform);
+            for y in 0..3 {
+                for x in 0..8 {
+                    let raw = get_raw::<Color>(src.buffer(), 8, x, y);
+                    let color = Color::try_from(raw as u8).unwrap();
+                    expected.set_pixel(Pixel(Point::new(x as i32 - 2, y as i32 + 14), color));
+                }
+            }
+            assert_eq!(display.buffer(), expected.buffer(), "{:?}", transform);
+        }
+    }
+
+    // Records what `DoubleBufferedDisplay::flush` sends
+    #[derive(Default)]
+    struct FakeQuickRefresh {
+        old: std::vec::Vec<u8>,
+        new: std::vec::Vec<u8>,
+        window: Option<Rect>,
+        displayed: usize,
+    }
+
+    impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY> for FakeQuickRefresh
+    where
+        SPI: SpiDevice,
+        BUSY: InputPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        fn update_old_frame(
+            &mut self,
+            _: &mut SPI,
+            _: &[u8],
+            _: &mut DELAY,
+        ) -> Result<(), SPI::Error> {
+            unimplemented!()
+        }
+
+        fn update_new_frame(
+            &mut self,
+            _: &mut SPI,
+            _: &[u8],
+            _: &mut DELAY,
+        ) -> Result<(), SPI::Error> {
+            unimplemented!()
+        }
+
+        fn display_new_frame(&mut self, _: &mut SPI, _: &mut DELAY) -> Result<(), SPI::Error> {
+            self.displayed += 1;
+            Ok(())
+        }
+
+        fn update_and_display_new_frame(
+            &mut self,
+            _: &mut SPI,
+            _: &[u8],
+            _: &mut DELAY,
+        ) -> Result<(), SPI::Error> {
+            unimplemented!()
+        }
+
+        fn update_partial_old_frame(
+            &mut self,
+            _: &mut SPI,
+            _: &mut DELAY,
+            buffer: &[u8],
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+        ) -> Result<(), SPI::Error> {
+            self.old = buffer.to_vec();
+            self.window = Some(Rect::new(x, y, width, height));
+            Ok(())
+        }
+
+        fn update_partial_new_frame(
+            &mut self,
+            _: &mut SPI,
+            _: &mut DELAY,
+            buffer: &[u8],
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+        ) -> Result<(), SPI::Error> {
+            self.new = buffer.to_vec();
+            assert_eq!(self.window, Some(Rect::new(x, y, width, height)));
+            Ok(())
+        }
+
+        fn clear_partial_frame(
+            &mut self,
+            _: &mut SPI,
+            _: &mut DELAY,
+            _: u32,
+            _: u32,
+            _: u32,
+            _: u32,
+        ) -> Result<(), SPI::Error> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn graphics_double_buffered_flush() {
+        use embedded_hal_mock::eh1::{delay::NoopDelay, digital, spi};
+
+        let mut display = DoubleBufferedDisplay::<32, 8, false, { 32 * 8 / 8 }, Color>::default();
+        display.clear(Color::White).unwrap();
+        display.mark_displayed();
+        assert_eq!(display.changed_window(), None);
+
+        let _ = Line::new(Point::new(9, 2), Point::new(17, 3))
+            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+            .draw(&mut display);
+        assert_eq!(display.changed_window(), Some(Rect::new(8, 2, 16, 2)));
+
+        let mut epd = FakeQuickRefresh::default();
+        let mut spi = spi::Mock::new(&[]);
+        let mut delay = NoopDelay::new();
+        let window = display
+            .flush::<_, digital::Mock, digital::Mock, digital::Mock, _, _>(
+                &mut epd, &mut spi, &mut delay,
+            )
+            .unwrap();
+        spi.done();
+
+        assert_eq!(window, Some(Rect::new(8, 2, 16, 2)));
+        assert_eq!(epd.old, [0xFF; 4]);
+        assert_eq!(epd.new, [0x83, 0xFF, 0xFC, 0x3F]);
+        assert_eq!(epd.displayed, 1);
+        assert_eq!(display.previous_buffer(), display.buffer());
+        assert_eq!(display.changed_window(), None);
+    }
+
     #[test]
     fn graphics_set_pixel_tricolor_false() {
         let mut display = Display::<4, 4, false, { 4 * 4 * 2 / 8 }, TriColor>::default();
@@ -485,4 +2222,37 @@ mod tests {
         assert_eq!(bw_buffer, [128, 0]);
         assert_eq!(chromatic_buffer, [64, 0]);
     }
+
+    #[test]
+    fn graphics_tricolor_ink() {
+        type Red = Display<4, 4, true, { 4 * 4 * 2 / 8 }, TriColor>;
+        type Yellow =
+            Display<4, 4, true, { 4 * 4 * 2 / 8 }, TriColor, 0, false, { TriColor::YELLOW_INK }>;
+
+        assert_eq!(Red::CHROMATIC_RGB, Rgb888::RED);
+        assert_eq!(Yellow::CHROMATIC_RGB, Rgb888::YELLOW);
+
+        let orange = Rgb888::new(0xFF, 0xA0, 0x00);
+        assert_eq!(Yellow::color_from_rgb(orange), TriColor::Chromatic);
+        let gold = Rgb888::new(0xF0, 0xD0, 0x30);
+        assert_eq!(Red::color_from_rgb(gold), TriColor::White);
+        assert_eq!(Yellow::color_from_rgb(gold), TriColor::Chromatic);
+        assert_eq!(Yellow::color_from_rgb(Rgb888::YELLOW), TriColor::Chromatic);
+        assert_eq!(
+            Yellow::color_from_rgb(Rgb888::new(0x90, 0, 0)),
+            TriColor::Black
+        );
+        assert_eq!(
+            Yellow::color_from_rgb(gold),
+            TriColor::from_rgb_with_ink(gold, TriColor::YELLOW_INK)
+        );
+        assert_eq!(TriColor::from(gold), Red::color_from_rgb(gold));
+
+        // the ink doesn't change the buffer
+        let mut red = Red::default();
+        let mut yellow = Yellow::default();
+        red.set_pixel(Pixel(Point::new(1, 0), TriColor::Chromatic));
+        yellow.set_pixel(Pixel(Point::new(1, 0), TriColor::Chromatic));
+        assert_eq!(red.buffer(), yellow.buffer());
+    }
 }
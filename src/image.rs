@@ -0,0 +1,819 @@
+//! Decoders for simple image formats, drawing straight into a display
+//!
+//! Supported formats:
+//!
+//! - PBM, plain (P1) and raw (P4)
+//! - PGM, plain (P2) and raw (P5), with a maximum value of up to 65535
+//! - PPM, plain (P3) and raw (P6), with a maximum value of up to 65535
+//! - BMP, uncompressed with 1, 4, 8 or 24 bits per pixel, bottom-up or top-down
+//! - XBM
+//!
+//! The image is read from a [`Read`] source and drawn pixel by pixel while it is decoded,
+//! it is never loaded as a whole. Apart from a small read buffer, only the palette of BMP
+//! images (at most 256 colors) is kept in memory.
+//!
+//! Colors are converted to the color of the target through [`Rgb888`], i.e. to the nearest color
+//! of its default [palette](crate::palette), or with [`draw_with_palette`] to a palette of your own.
+//! An optional ordered dithering renders gray levels and mixed colors on displays with a few colors
+//! only.
+//!
+//!```rust
+//!# fn main() -> Result<(), epd_waveshare::image::ImageError<core::convert::Infallible>> {
+//!use epd_waveshare::{epd2in9_v2::*, image::{self, Dither}, prelude::*};
+//!use embedded_graphics::prelude::*;
+//!
+//!let mut display = Display2in9::default();
+//!let logo: &[u8] = b"P1\n4 2\n1 0 0 1\n0 1 1 0\n";
+//!let size = image::draw(logo, &mut display, Point::new(10, 20), Dither::None)?;
+//!assert_eq!(size, Size::new(4, 2));
+//!# Ok(())
+//!# }
+//!```
+
+use crate::palette::Palette;
+use core::convert::Infallible;
+use embedded_graphics_core::{pixelcolor::Rgb888, prelude::*};
+
+/// Source of the bytes of an image, e.g. a file on a SD card or a slice in flash
+pub trait Read {
+    /// Error of the underlying storage
+    type Error;
+
+    /// Read up to `buf.len()` bytes into `buf` and return how many have been read,
+    /// 0 means that the end of the image has been reached
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+impl Read for &[u8] {
+    type Error = Infallible;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let len = buf.len().min(self.len());
+        let (head, tail) = self.split_at(len);
+        buf[..len].copy_from_slice(head);
+        *self = tail;
+        Ok(len)
+    }
+}
+
+/// Error found while decoding an image
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImageError<E> {
+    /// The source failed to read
+    Read(E),
+    /// The data ends before the end of the image
+    UnexpectedEof,
+    /// The data doesn't start like any of the supported formats
+    UnknownFormat,
+    /// A variant of the format which is not supported (e.g. a compressed BMP)
+    Unsupported,
+    /// The header or the data is malformed
+    Invalid,
+}
+
+/// Image formats known by [`draw`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Portable bitmap (P1 and P4)
+    Pbm,
+    /// Portable graymap (P2 and P5)
+    Pgm,
+    /// Portable pixmap (P3 and P6)
+    Ppm,
+    /// Windows bitmap
+    Bmp,
+    /// X bitmap
+    Xbm,
+}
+
+/// How colors which aren't available on the display are rendered
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// Use the nearest color
+    #[default]
+    None,
+    /// Mix the available colors with a 4x4 Bayer matrix, this needs no additional memory
+    Ordered,
+}
+
+/// Decode an image and draw it with its top left corner at `position`
+///
+/// The format is found from the first bytes of the data, see [`draw_format`] to only accept a
+/// specific one. Pixels outside of `target` are ignored. Returns the size of the image.
+pub fn draw<R, D>(
+    reader: R,
+    target: &mut D,
+    position: Point,
+    dither: Dither,
+) -> Result<Size, ImageError<R::Error>>
+where
+    R: Read,
+    D: DrawTarget<Error = Infallible>,
+    D::Color: From<Rgb888>,
+{
+    let mut bytes = Bytes::new(reader);
+    let format = detect(&mut bytes)?;
+    decode(&mut bytes, format, target, position, dither, D::Color::from)
+}
+
+/// Decode an image of a known format and draw it with its top left corner at `position`
+///
+/// Same as [`draw`], but fails with [`ImageError::UnknownFormat`] if the data is not in `format`.
+pub fn draw_format<R, D>(
+    reader: R,
+    format: Format,
+    target: &mut D,
+    position: Point,
+    dither: Dither,
+) -> Result<Size, ImageError<R::Error>>
+where
+    R: Read,
+    D: DrawTarget<Error = Infallible>,
+    D::Color: From<Rgb888>,
+{
+    let mut bytes = Bytes::new(reader);
+    if detect(&mut bytes)? != format {
+        return Err(ImageError::UnknownFormat);
+    }
+    decode(&mut bytes, format, target, position, dither, D::Color::from)
+}
+
+/// Decode an image and draw it with the colors of `palette`
+///
+/// Same as [`draw`], but every color (after the dithering) becomes the color of the palette
+/// which looks the most alike, e.g. with the values measured on a panel.
+pub fn draw_with_palette<R, D>(
+    reader: R,
+    target: &mut D,
+    position: Point,
+    dither: Dither,
+    palette: &Palette<'_, D::Color>,
+) -> Result<Size, ImageError<R::Error>>
+where
+    R: Read,
+    D: DrawTarget<Error = Infallible>,
+    D::Color: Copy,
+{
+    let mut bytes = Bytes::new(reader);
+    let format = detect(&mut bytes)?;
+    decode(
+        &mut bytes,
+        format,
+        target,
+        position,
+        dither,
+        |color: Rgb888| palette.nearest((color.r(), color.g(), color.b())),
+    )
+}
+
+// Find the format from the magic bytes, the first one is consumed
+fn detect<R: Read>(bytes: &mut Bytes<R>) -> Result<Format, ImageError<R::Error>> {
+    bytes.skip_space(false)?;
+    match (bytes.byte()?, bytes.peek()?) {
+        (b'P', Some(b'1' | b'4')) => Ok(Format::Pbm),
+        (b'P', Some(b'2' | b'5')) => Ok(Format::Pgm),
+        (b'P', Some(b'3' | b'6')) => Ok(Format::Ppm),
+        (b'B', Some(b'M')) => Ok(Format::Bmp),
+        (b'#', _) => Ok(Format::Xbm),
+        _ => Err(ImageError::UnknownFormat),
+    }
+}
+
+fn decode<R, D, F>(
+    bytes: &mut Bytes<R>,
+    format: Format,
+    target: &mut D,
+    position: Point,
+    dither: Dither,
+    to_color: F,
+) -> Result<Size, ImageError<R::Error>>
+where
+    R: Read,
+    D: DrawTarget<Error = Infallible>,
+    F: Fn(Rgb888) -> D::Color,
+{
+    let mut painter = Painter {
+        target,
+        position,
+        dither,
+        to_color,
+    };
+    match format {
+        Format::Pbm | Format::Pgm | Format::Ppm => decode_pnm(bytes, &mut painter),
+        Format::Bmp => decode_bmp(bytes, &mut painter),
+        Format::Xbm => decode_xbm(bytes, &mut painter),
+    }
+}
+
+// Draws the decoded pixels, relative to the top left corner of the image
+struct Painter<'a, D, F> {
+    target: &'a mut D,
+    position: Point,
+    dither: Dither,
+    // maps the (dithered) colors of the image to the display
+    to_color: F,
+}
+
+// 4x4 Bayer matrix for the ordered dithering
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+impl<D, F> Painter<'_, D, F>
+where
+    D: DrawTarget<Error = Infallible>,
+    F: Fn(Rgb888) -> D::Color,
+{
+    fn pixel(&mut self, x: u32, y: u32, color: Rgb888) {
+        let point = self.position + Point::new(x as i32, y as i32);
+        let color = match self.dither {
+            Dither::None => color,
+            Dither::Ordered => {
+                // the threshold depends on the position on the display, not in the image
+                let threshold = BAYER[point.y as usize & 3][point.x as usize & 3];
+                let offset = i16::from(threshold) * 16 + 8 - 128;
+                let channel = |value: u8| (i16::from(value) + offset).clamp(0, 255) as u8;
+                Rgb888::new(channel(color.r()), channel(color.g()), channel(color.b()))
+            }
+        };
+        let _ = self
+            .target
+            .draw_iter(core::iter::once(Pixel(point, (self.to_color)(color))));
+    }
+
+    fn bit(&mut self, x: u32, y: u32, black: bool) {
+        let color = if black { Rgb888::BLACK } else { Rgb888::WHITE };
+        self.pixel(x, y, color);
+    }
+
+    fn gray(&mut self, x: u32, y: u32, value: u8) {
+        self.pixel(x, y, Rgb888::new(value, value, value));
+    }
+}
+
+// Buffered reader with the few parsing helpers needed by the decoders
+struct Bytes<R> {
+    reader: R,
+    buf: [u8; 64],
+    pos: usize,
+    len: usize,
+    // number of bytes consumed so far
+    offset: u32,
+}
+
+impl<R: Read> Bytes<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: [0; 64],
+            pos: 0,
+            len: 0,
+            offset: 0,
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, ImageError<R::Error>> {
+        if self.pos == self.len {
+            self.len = self.reader.read(&mut self.buf).map_err(ImageError::Read)?;
+            self.pos = 0;
+            if self.len == 0 {
+                return Ok(None);
+            }
+        }
+        Ok(Some(self.buf[self.pos]))
+    }
+
+    fn byte(&mut self) -> Result<u8, ImageError<R::Error>> {
+        let byte = self.peek()?.ok_or(ImageError::UnexpectedEof)?;
+        self.pos += 1;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn skip(&mut self, count: u32) -> Result<(), ImageError<R::Error>> {
+        for _ in 0..count {
+            self.byte()?;
+        }
+        Ok(())
+    }
+
+    fn u16_le(&mut self) -> Result<u16, ImageError<R::Error>> {
+        Ok(u16::from_le_bytes([self.byte()?, self.byte()?]))
+    }
+
+    fn u32_le(&mut self) -> Result<u32, ImageError<R::Error>> {
+        Ok(u32::from_le_bytes([
+            self.byte()?,
+            self.byte()?,
+            self.byte()?,
+            self.byte()?,
+        ]))
+    }
+
+    // Skip whitespace, and with `comments` also everything from a '#' to the end of the line
+    fn skip_space(&mut self, comments: bool) -> Result<(), ImageError<R::Error>> {
+        while let Some(byte) = self.peek()? {
+            if byte.is_ascii_whitespace() {
+                self.byte()?;
+            } else if comments && byte == b'#' {
+                while !matches!(self.peek()?, None | Some(b'\n' | b'\r')) {
+                    self.byte()?;
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Parse a decimal or a `0x` prefixed hexadecimal number, stops before the first other byte
+    fn number(&mut self) -> Result<u32, ImageError<R::Error>> {
+        let mut radix = 10;
+        if self.peek()? == Some(b'0') {
+            self.byte()?;
+            if matches!(self.peek()?, Some(b'x' | b'X')) {
+                self.byte()?;
+                radix = 16;
+            } else if !matches!(self.peek()?, Some(b'0'..=b'9')) {
+                return Ok(0);
+            }
+        }
+        let mut value: u32 = 0;
+        let mut digits = 0;
+        while let Some(digit) = self.peek()?.and_then(|byte| (byte as char).to_digit(radix)) {
+            self.byte()?;
+            value = value.saturating_mul(radix).saturating_add(digit);
+            digits += 1;
+        }
+        if digits == 0 {
+            return Err(ImageError::Invalid);
+        }
+        Ok(value)
+    }
+}
+
+// PBM, PGM and PPM, the leading 'P' has already been consumed
+fn decode_pnm<R, D, F>(
+    bytes: &mut Bytes<R>,
+    painter: &mut Painter<'_, D, F>,
+) -> Result<Size, ImageError<R::Error>>
+where
+    R: Read,
+    D: DrawTarget<Error = Infallible>,
+    F: Fn(Rgb888) -> D::Color,
+{
+    let kind = bytes.byte()?;
+    bytes.skip_space(true)?;
+    let width = bytes.number()?;
+    bytes.skip_space(true)?;
+    let height = bytes.number()?;
+    let max = if matches!(kind, b'2' | b'3' | b'5' | b'6') {
+        bytes.skip_space(true)?;
+        match bytes.number()? {
+            0 | 65536.. => return Err(ImageError::Invalid),
+            max => max,
+        }
+    } else {
+        1
+    };
+    // a single whitespace separates the header from the data
+    if !bytes.byte()?.is_ascii_whitespace() {
+        return Err(ImageError::Invalid);
+    }
+
+    for y in 0..height {
+        match kind {
+            b'1' => {
+                for x in 0..width {
+                    bytes.skip_space(true)?;
+                    match bytes.byte()? {
+                        b'0' => painter.bit(x, y, false),
+                        b'1' => painter.bit(x, y, true),
+                        _ => return Err(ImageError::Invalid),
+                    }
+                }
+            }
+            b'4' => {
+                let mut byte = 0;
+                for x in 0..width {
+                    if x % 8 == 0 {
+                        byte = bytes.byte()?;
+                    }
+                    painter.bit(x, y, byte & (0x80 >> (x % 8)) != 0);
+                }
+            }
+            _ => {
+                // one sample per pixel for gray levels, three for colors
+                let mut sample = || -> Result<u8, ImageError<R::Error>> {
+                    let value = match kind {
+                        b'2' | b'3' => {
+                            bytes.skip_space(true)?;
+                            bytes.number()?
+                        }
+                        _ if max > 255 => u32::from(bytes.byte()?) << 8 | u32::from(bytes.byte()?),
+                        _ => u32::from(bytes.byte()?),
+                    };
+                    Ok((value.min(max) * 255 / max) as u8)
+                };
+                for x in 0..width {
+                    if matches!(kind, b'3' | b'6') {
+                        let color = Rgb888::new(sample()?, sample()?, sample()?);
+                        painter.pixel(x, y, color);
+                    } else {
+                        let value = sample()?;
+                        painter.gray(x, y, value);
+                    }
+                }
+            }
+        }
+    }
+    Ok(Size::new(width, height))
+}
+
+// BMP, the leading 'B' has already been consumed
+fn decode_bmp<R, D, F>(
+    bytes: &mut Bytes<R>,
+    painter: &mut Painter<'_, D, F>,
+) -> Result<Size, ImageError<R::Error>>
+where
+    R: Read,
+    D: DrawTarget<Error = Infallible>,
+    F: Fn(Rgb888) -> D::Color,
+{
+    if bytes.byte()? != b'M' {
+        return Err(ImageError::UnknownFormat);
+    }
+    // file size and reserved fields
+    bytes.skip(8)?;
+    let data_offset = bytes.u32_le()?;
+    let header_size = bytes.u32_le()?;
+    if header_size < 40 {
+        // OS/2 headers
+        return Err(ImageError::Unsupported);
+    }
+    let width = bytes.u32_le()? as i32;
+    let height = bytes.u32_le()? as i32;
+    let _planes = bytes.u16_le()?;
+    let bits = bytes.u16_le()?;
+    let compression = bytes.u32_le()?;
+    // image size and resolution
+    bytes.skip(12)?;
+    let colors_used = bytes.u32_le()?;
+    let _colors_important = bytes.u32_le()?;
+    bytes.skip(header_size - 40)?;
+
+    if compression != 0 || !matches!(bits, 1 | 4 | 8 | 24) {
+        return Err(ImageError::Unsupported);
+    }
+    if width <= 0 || height == 0 {
+        return Err(ImageError::Invalid);
+    }
+    let width = width as u32;
+    let bottom_up = height > 0;
+    let height = height.unsigned_abs();
+
+    let mut palette = [Rgb888::BLACK; 256];
+    if bits <= 8 {
+        let count = match colors_used {
+            0 => 1 << bits,
+            count => count,
+        };
+        if count > 1 << bits {
+            return Err(ImageError::Invalid);
+        }
+        for color in palette.iter_mut().take(count as usize) {
+            let (b, g, r) = (bytes.byte()?, bytes.byte()?, bytes.byte()?);
+            bytes.byte()?;
+            *color = Rgb888::new(r, g, b);
+        }
+    }
+    if bytes.offset > data_offset {
+        return Err(ImageError::Invalid);
+    }
+    bytes.skip(data_offset - bytes.offset)?;
+
+    // rows are padded to 4 bytes
+    let stride = width
+        .checked_mul(u32::from(bits))
+        .and_then(|row_bits| row_bits.checked_add(31))
+        .ok_or(ImageError::Invalid)?
+        / 32
+        * 4;
+    for row in 0..height {
+        let y = if bottom_up { height - 1 - row } else { row };
+        let mut read = 0;
+        if bits == 24 {
+            for x in 0..width {
+                let (b, g, r) = (bytes.byte()?, bytes.byte()?, bytes.byte()?);
+                painter.pixel(x, y, Rgb888::new(r, g, b));
+            }
+            read = width * 3;
+        } else {
+            let per_byte = 8 / u32::from(bits);
+            let mask = ((1u16 << bits) - 1) as u8;
+            let mut byte = 0;
+            for x in 0..width {
+                if x % per_byte == 0 {
+                    byte = bytes.byte()?;
+                    read += 1;
+                }
+                let shift = 8 - bits as u32 * (x % per_byte + 1);
+                let index = (byte >> shift) & mask;
+                painter.pixel(x, y, palette[index as usize]);
+            }
+        }
+        bytes.skip(stride - read)?;
+    }
+    Ok(Size::new(width, height))
+}
+
+// XBM, the leading '#' has already been consumed
+fn decode_xbm<R, D, F>(
+    bytes: &mut Bytes<R>,
+    painter: &mut Painter<'_, D, F>,
+) -> Result<Size, ImageError<R::Error>>
+where
+    R: Read,
+    D: DrawTarget<Error = Infallible>,
+    F: Fn(Rgb888) -> D::Color,
+{
+    let mut width = None;
+    let mut height = None;
+    // header: `#define <name>_width <value>` lines, up to the opening brace of the data.
+    // The first '#' has already been consumed.
+    let mut hash = true;
+    loop {
+        if !hash {
+            match bytes.byte()? {
+                b'{' => break,
+                b'#' => {}
+                _ => continue,
+            }
+        }
+        hash = false;
+        let mut directive = [0u8; 6];
+        for byte in directive.iter_mut() {
+            *byte = bytes.byte()?;
+        }
+        if &directive != b"define" {
+            continue;
+        }
+        bytes.skip_space(false)?;
+        // only the end of the name matters
+        let mut suffix = [0u8; 7];
+        while let Some(byte) = bytes.peek()? {
+            if byte.is_ascii_whitespace() {
+                break;
+            }
+            suffix.rotate_left(1);
+            suffix[6] = bytes.byte()?;
+        }
+        bytes.skip_space(false)?;
+        let value = bytes.number()?;
+        if suffix.ends_with(b"_width") {
+            width = Some(value);
+        } else if suffix.ends_with(b"_height") {
+            height = Some(value);
+        }
+    }
+    let (width, height) = match (width, height) {
+        (Some(width), Some(height)) => (width, height),
+        _ => return Err(ImageError::Invalid),
+    };
+
+    for y in 0..height {
+        let mut byte = 0;
+        for x in 0..width {
+            if x % 8 == 0 {
+                while let Some(b' ' | b'\t' | b'\r' | b'\n' | b',') = bytes.peek()? {
+                    bytes.byte()?;
+                }
+                byte = bytes.number()?;
+            }
+            // least significant bit first, set bits are the foreground
+            painter.bit(x, y, byte & (1 << (x % 8)) != 0);
+        }
+    }
+    Ok(Size::new(width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Color, OctColor, TriColor};
+    use crate::graphics::Display;
+    extern crate std;
+    use std::vec::Vec;
+
+    type Display16x4 = Display<16, 4, false, { 16 * 4 / 8 }, Color, 0xFF>;
+
+    // BMP file with a BITMAPINFOHEADER, `rows` are given top-down and padded here
+    fn bmp(width: i32, height: i32, bits: u16, palette: &[[u8; 4]], rows: &[&[u8]]) -> Vec<u8> {
+        let offset = 14 + 40 + palette.len() as u32 * 4;
+        let mut data = Vec::new();
+        data.extend_from_slice(b"BM");
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&offset.to_le_bytes());
+        data.extend_from_slice(&40u32.to_le_bytes());
+        data.extend_from_slice(&width.to_le_bytes());
+        data.extend_from_slice(&height.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&bits.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&(palette.len() as u32).to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for color in palette {
+            data.extend_from_slice(color);
+        }
+        let ordered: Vec<&&[u8]> = if height > 0 {
+            rows.iter().rev().collect()
+        } else {
+            rows.iter().collect()
+        };
+        for row in ordered {
+            data.extend_from_slice(row);
+            data.resize(data.len() + (4 - row.len() % 4) % 4, 0);
+        }
+        data
+    }
+
+    #[test]
+    fn pbm_plain_and_raw() {
+        let mut display = Display16x4::default();
+        let pbm: &[u8] = b"P1\n# logo\n4 2\n1 0 0 1\n0110\n";
+        assert_eq!(
+            draw(pbm, &mut display, Point::new(2, 1), Dither::None),
+            Ok(Size::new(4, 2))
+        );
+        assert_eq!(
+            display.buffer(),
+            [0xFF, 0xFF, 0xDB, 0xFF, 0xE7, 0xFF, 0xFF, 0xFF]
+        );
+
+        let mut display = Display16x4::default();
+        let pbm: &[u8] = b"P4 10 2\n\x80\x40\xFF\xC0";
+        assert_eq!(
+            draw(pbm, &mut display, Point::new(0, 0), Dither::None),
+            Ok(Size::new(10, 2))
+        );
+        assert_eq!(
+            display.buffer(),
+            [0x7F, 0xBF, 0x00, 0x3F, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn pgm_plain_and_raw() {
+        let mut display = Display16x4::default();
+        let pgm: &[u8] = b"P2 4 1 15\n0 7 8 15\n";
+        assert_eq!(
+            draw(pgm, &mut display, Point::zero(), Dither::None),
+            Ok(Size::new(4, 1))
+        );
+        assert_eq!(display.buffer()[0], 0x3F);
+
+        let mut display = Display16x4::default();
+        let pgm: &[u8] = b"P5 2 1 65535\n\x00\x10\xFF\x00";
+        assert_eq!(
+            draw(pgm, &mut display, Point::zero(), Dither::None),
+            Ok(Size::new(2, 1))
+        );
+        assert_eq!(display.buffer()[0], 0x7F);
+    }
+
+    #[test]
+    fn ppm_plain_and_raw() {
+        let mut display = Display::<4, 1, false, 2, OctColor>::default();
+        let ppm: &[u8] = b"P3 2 1 15\n15 0 0  0 0 15\n";
+        draw(ppm, &mut display, Point::zero(), Dither::None).unwrap();
+        let ppm: &[u8] = b"P6 2 1 255\n\x00\xFF\x00\xFF\xFF\xFF";
+        draw(ppm, &mut display, Point::new(2, 0), Dither::None).unwrap();
+        assert_eq!(
+            display.buffer(),
+            [
+                OctColor::colors_byte(OctColor::Red, OctColor::Blue),
+                OctColor::colors_byte(OctColor::Green, OctColor::White)
+            ]
+        );
+    }
+
+    #[test]
+    fn bmp_palette_and_rgb() {
+        let black_white = [[0, 0, 0, 0], [0xFF, 0xFF, 0xFF, 0]];
+        for (height, rows) in [(2, [0x5F, 0x00]), (-2, [0x5F, 0x00])] {
+            let mut display = Display16x4::default();
+            let data = bmp(12, height, 1, &black_white, &[&[0x5F, 0xF0], &[0x00, 0x00]]);
+            assert_eq!(
+                draw(data.as_slice(), &mut display, Point::zero(), Dither::None),
+                Ok(Size::new(12, 2))
+            );
+            assert_eq!(display.buffer()[..4], [rows[0], 0xFF, rows[1], 0x0F]);
+        }
+
+        let data = bmp(3, 1, 4, &black_white, &[&[0x10, 0x10]]);
+        let mut display = Display16x4::default();
+        draw(data.as_slice(), &mut display, Point::zero(), Dither::None).unwrap();
+        assert_eq!(display.buffer()[0], 0xBF);
+
+        let data = bmp(2, 1, 8, &black_white, &[&[0x01, 0x00]]);
+        let mut display = Display16x4::default();
+        draw(data.as_slice(), &mut display, Point::zero(), Dither::None).unwrap();
+        assert_eq!(display.buffer()[0], 0xBF);
+
+        let data = bmp(
+            3,
+            1,
+            24,
+            &[],
+            &[&[0, 0, 0xFF, 0, 0xFF, 0, 0xFF, 0xFF, 0xFF]],
+        );
+        let mut display = Display::<4, 1, false, 2, OctColor>::default();
+        draw(data.as_slice(), &mut display, Point::zero(), Dither::None).unwrap();
+        assert_eq!(
+            display.buffer(),
+            [
+                OctColor::colors_byte(OctColor::Red, OctColor::Green),
+                OctColor::colors_byte(OctColor::White, OctColor::Black)
+            ]
+        );
+
+        let mut display = Display::<8, 1, true, 2, TriColor, 0xFF>::default();
+        draw(data.as_slice(), &mut display, Point::zero(), Dither::None).unwrap();
+        // green looks more like white than like the red of the panel
+        assert_eq!(display.bw_buffer(), [0x7F]);
+        assert_eq!(display.chromatic_buffer(), [0x80]);
+    }
+
+    #[test]
+    fn xbm() {
+        let xbm: &[u8] = b"#define icon_width 10
+#define icon_height 2
+#define icon_x_hot 1
+static unsigned char icon_bits[] = {
+   0x01, 0x02, 0xff,
+   0x03 };
+";
+        let mut display = Display16x4::default();
+        assert_eq!(
+            draw(xbm, &mut display, Point::new(1, 1), Dither::None),
+            Ok(Size::new(10, 2))
+        );
+        assert_eq!(
+            display.buffer(),
+            [0xFF, 0xFF, 0xBF, 0xDF, 0x80, 0x1F, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn ordered_dither() {
+        let mut display = Display16x4::default();
+        let pgm: &[u8] =
+            b"P5 4 4 255\n\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80";
+        draw(pgm, &mut display, Point::zero(), Dither::Ordered).unwrap();
+        let black = display
+            .buffer()
+            .iter()
+            .step_by(2)
+            .map(|byte| (!byte >> 4).count_ones())
+            .sum::<u32>();
+        assert_eq!(black, 8);
+    }
+
+    #[test]
+    fn custom_palette() {
+        use crate::palette::PaletteEntry;
+        let entries = [
+            PaletteEntry::new(TriColor::Black, (0x20, 0x20, 0x20)),
+            PaletteEntry::new(TriColor::White, (0xE0, 0xE0, 0xE0)),
+            PaletteEntry::new(TriColor::Chromatic, (0xE0, 0xC0, 0x20)),
+        ];
+        let yellow = Palette::new(&entries);
+        let mut display = Display::<8, 1, true, 2, TriColor, 0xFF>::default();
+        let ppm: &[u8] = b"P6 2 1 255\n\xFF\xFF\x00\x40\x40\x40";
+        draw_with_palette(ppm, &mut display, Point::zero(), Dither::None, &yellow).unwrap();
+        assert_eq!(display.bw_buffer(), [0x3F]);
+        assert_eq!(display.chromatic_buffer(), [0x80]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut display = Display16x4::default();
+        let mut draw_bytes =
+            |data: &[u8]| draw(data, &mut display, Point::zero(), Dither::None).map(|_| ());
+        assert_eq!(draw_bytes(b"GIF89a"), Err(ImageError::UnknownFormat));
+        assert_eq!(draw_bytes(b"P4 16 2\n\x00"), Err(ImageError::UnexpectedEof));
+        assert_eq!(draw_bytes(b"P1 2 1\n0 2"), Err(ImageError::Invalid));
+        let mut compressed = bmp(1, 1, 8, &[], &[&[0]]);
+        compressed[30] = 1;
+        assert_eq!(draw_bytes(&compressed), Err(ImageError::Unsupported));
+        assert_eq!(
+            draw_format(
+                &b"P1 1 1 0"[..],
+                Format::Xbm,
+                &mut display,
+                Point::zero(),
+                Dither::None
+            ),
+            Err(ImageError::UnknownFormat)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::rect::Rect;
 use crate::traits::Command;
 use core::marker::PhantomData;
 use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
@@ -19,6 +20,32 @@ pub(crate) struct DisplayInterface<SPI, BUSY, DC, RST, DELAY, const SINGLE_BYTE_
     rst: RST,
     /// number of ms the idle loop should sleep on
     delay_us: u32,
+    /// Temperature of the panel given by
+    /// [`Temperature::set_temperature`](crate::traits::Temperature::set_temperature), the
+    /// controller measures it with its internal sensor if unset
+    temperature: Option<i8>,
+}
+
+/// Temperature commands at the same address in all controllers of a family
+#[derive(Clone, Copy)]
+enum TemperatureCommand {
+    /// SSD168x: write to the temperature register, 12 bit in 1/16 degrees
+    Ssd168xTemperatureWrite = 0x1A,
+    /// SSD168x: display update control 2, the steps of the update sequence
+    Ssd168xUpdateControl2 = 0x22,
+    /// SSD168x: run the update sequence
+    Ssd168xMasterActivation = 0x20,
+    /// UC81xx: cascade setting, TSFIX (bit 1) replaces the internal sensor by the forced
+    /// temperature
+    Uc81xxCascadeSetting = 0xE0,
+    /// UC81xx: force temperature
+    Uc81xxForceTemperature = 0xE5,
+}
+
+impl Command for TemperatureCommand {
+    fn address(self) -> u8 {
+        self as u8
+    }
 }
 
 impl<SPI, BUSY, DC, RST, DELAY, const SINGLE_BYTE_WRITE: bool>
@@ -43,9 +70,89 @@ where
             dc,
             rst,
             delay_us,
+            temperature: None,
         }
     }
 
+    /// Temperature of the panel, `None` if the controller measures it
+    pub(crate) fn temperature(&self) -> Option<i8> {
+        self.temperature
+    }
+
+    /// Stores the temperature of the panel, the drivers send it on init and with the LUTs
+    pub(crate) fn set_temperature(&mut self, celsius: Option<i8>) {
+        self.temperature = celsius;
+    }
+
+    /// Sends the temperature to UC81xx controllers, which take the waveform of the OTP for it
+    ///
+    /// `None` switches back to the internal sensor.
+    pub(crate) fn cmd_forced_temperature(
+        &mut self,
+        spi: &mut SPI,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        if let Some(celsius) = celsius {
+            self.cmd_with_data(spi, TemperatureCommand::Uc81xxCascadeSetting, &[0x02])?;
+            self.cmd_with_data(
+                spi,
+                TemperatureCommand::Uc81xxForceTemperature,
+                &[celsius as u8],
+            )
+        } else {
+            self.cmd_with_data(spi, TemperatureCommand::Uc81xxCascadeSetting, &[0x00])
+        }
+    }
+
+    /// Writes the temperature register of SSD168x controllers
+    ///
+    /// The load LUT step of an update sequence picks the waveform of the OTP for it, as long as
+    /// the sequence doesn't load the temperature of the sensor as well (see
+    /// [`ssd168x_update_control`](Self::ssd168x_update_control)). `None` leaves the register
+    /// to the sensor.
+    pub(crate) fn cmd_temperature_register(
+        &mut self,
+        spi: &mut SPI,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error> {
+        match celsius {
+            Some(celsius) => self.cmd_with_data(
+                spi,
+                TemperatureCommand::Ssd168xTemperatureWrite,
+                &[celsius as u8, 0x00],
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// SSD168x update sequence `control` without its load temperature step (bit 5) while the
+    /// temperature of the panel is set, so the written one is kept
+    pub(crate) fn ssd168x_update_control(&self, control: u8) -> u8 {
+        if self.temperature.is_some() {
+            control & !0x20
+        } else {
+            control
+        }
+    }
+
+    /// Loads the waveform of the OTP of SSD168x controllers for a temperature, the one for a
+    /// forced high temperature is about twice as fast as the full LUT (Waveshare's fast init)
+    pub(crate) fn load_otp_waveform(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        is_busy_low: bool,
+        celsius: i8,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(delay, is_busy_low);
+        self.cmd_temperature_register(spi, Some(celsius))?;
+        // enable clock, load the LUT of the written temperature, disable clock
+        self.cmd_with_data(spi, TemperatureCommand::Ssd168xUpdateControl2, &[0x91])?;
+        self.cmd(spi, TemperatureCommand::Ssd168xMasterActivation)?;
+        self.wait_until_idle(delay, is_busy_low);
+        Ok(())
+    }
+
     /// Basic function for sending [Commands](Command).
     ///
     /// Enables direct interaction with the device with the help of [data()](DisplayInterface::data())
@@ -89,6 +196,41 @@ where
         self.data(spi, data)
     }
 
+    /// Sends the partial window command (0x90) of the UC81xx controllers for `window` in pixels
+    ///
+    /// The x coordinates are positioned in bytes, the window is widened to whole bytes. The
+    /// controllers of the panels wider than 256 pixels take them as two bytes (`wide_x`).
+    pub(crate) fn cmd_partial_window<T: Command>(
+        &mut self,
+        spi: &mut SPI,
+        command: T,
+        window: Rect,
+        wide_x: bool,
+    ) -> Result<(), SPI::Error> {
+        let x_start = window.x & !0x07;
+        let x_end = (window.x + window.w - 1) | 0x07;
+        let y_end = window.y + window.h - 1;
+        let data = [
+            (x_start >> 8) as u8,
+            x_start as u8,
+            (x_end >> 8) as u8,
+            x_end as u8,
+            (window.y >> 8) as u8,
+            window.y as u8,
+            (y_end >> 8) as u8,
+            y_end as u8,
+            // gates scan both inside and outside of the partial window
+            0x01,
+        ];
+        if wide_x {
+            self.cmd_with_data(spi, command, &data)
+        } else {
+            let mut narrow = [data[1], data[3], 0, 0, 0, 0, 0];
+            narrow[2..].copy_from_slice(&data[4..]);
+            self.cmd_with_data(spi, command, &narrow)
+        }
+    }
+
     /// Basic function for sending the same byte of data (one u8) multiple times over spi
     ///
     /// Enables direct interaction with the device with the help of [command()](ConnectionInterface::command())
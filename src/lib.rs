@@ -63,15 +63,33 @@
 #![no_std]
 #![deny(missing_docs)]
 
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
 #[cfg(feature = "graphics")]
 pub mod graphics;
 
+#[cfg(feature = "graphics")]
+pub mod image;
+
+#[cfg(all(feature = "graphics", any(feature = "std", test)))]
+pub mod snapshot;
+
+#[cfg(all(feature = "graphics", any(feature = "std", test)))]
+pub mod simulator;
+
 mod traits;
 
 pub mod color;
 
+pub mod palette;
+
+pub mod lut;
+
 pub mod rect;
 
+pub mod scheduler;
+
 /// Interface for the physical connection between display and the controlling device
 mod interface;
 
@@ -92,11 +110,14 @@ pub mod epd2in9_v2;
 pub mod epd2in9b_v4;
 pub mod epd2in9bc;
 pub mod epd2in9d;
+pub mod epd3in0g;
 pub mod epd3in7;
 pub mod epd4in2;
+pub mod epd4in37g;
 pub mod epd5in65f;
 pub mod epd5in83_v2;
 pub mod epd5in83b_v2;
+pub mod epd7in3e;
 pub mod epd7in3f;
 pub mod epd7in5;
 pub mod epd7in5_hd;
@@ -106,18 +127,21 @@ pub use epd7in5b_v2 as epd7in5b_v3;
 pub mod epd12in48b_v2;
 
 pub(crate) mod type_a;
+pub(crate) mod type_g;
 
 /// Includes everything important besides the chosen Display
 pub mod prelude {
-    pub use crate::color::{Color, OctColor, TriColor};
+    pub use crate::color::{Color, OctColor, QuadColor, SixColor, TriColor};
     pub use crate::traits::{
-        QuickRefresh, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+        CleanStep, CustomLut, DefaultCleanSequence, HardwareMirror, QuickRefresh, RefreshLut,
+        Temperature, WaveshareDisplay, WaveshareThreeColorDisplay,
+        WaveshareThreeColorPartialDisplay,
     };
 
     pub use crate::SPI_MODE;
 
     #[cfg(feature = "graphics")]
-    pub use crate::graphics::{Display, DisplayRotation};
+    pub use crate::graphics::{Bitmap, Display, DisplayRotation, DisplayTransform, RasterOp};
 }
 
 /// Computes the needed buffer length. Takes care of rounding up in case width
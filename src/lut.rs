@@ -0,0 +1,726 @@
+//! Typed waveforms (LUTs) for the controller families that accept them from the host
+//!
+//! Each controller family has its own layout, so there is one type per family. They only
+//! check what the controller would otherwise silently misinterpret, i.e. the length of the
+//! tables and the voltage registers. Load them with [`CustomLut::set_custom_lut`].
+//!
+//! The SSD1680 waveforms can be split into their groups and phases with
+//! [`Ssd1680Lut::decode`], and written as [`Ssd1680Waveform`] and packed with
+//! [`Ssd1680Waveform::encode`].
+//!
+//! ```
+//! use epd_waveshare::lut::{LutError, Ssd1680Lut};
+//!
+//! let mut bytes = [0u8; 159];
+//! // end option, VGH, VSH1, VSH2, VSL, VCOM
+//! bytes[153..].copy_from_slice(&[0x22, 0x17, 0x41, 0x00, 0x33, 0x36]);
+//! assert_eq!(
+//!     Ssd1680Lut::from_bytes(&bytes),
+//!     Err(LutError::Voltage { index: 157, value: 0x33 })
+//! );
+//!
+//! bytes[157] = 0x32;
+//! let lut = Ssd1680Lut::from_bytes(&bytes).unwrap();
+//! assert_eq!(lut.voltages().unwrap().vcom, 0x36);
+//! ```
+//!
+//! [`CustomLut::set_custom_lut`]: crate::traits::CustomLut::set_custom_lut
+
+use core::ops::RangeInclusive;
+
+/// Temperatures the waveforms of this crate are made for
+///
+/// Waveshare only publishes waveforms tuned at room temperature, so this is the only band the
+/// drivers have waveforms for. For a panel outside of it (see
+/// [`Temperature::set_temperature`]) they refresh with the waveform the controller has in its
+/// OTP for that temperature instead.
+///
+/// [`Temperature::set_temperature`]: crate::traits::Temperature::set_temperature
+pub const HOST_LUT_CELSIUS: RangeInclusive<i8> = 20..=30;
+
+/// Waveform of a driver for the panels within a temperature band
+pub(crate) struct LutBand<L> {
+    pub(crate) celsius: RangeInclusive<i8>,
+    pub(crate) lut: L,
+}
+
+/// Waveform picked by [`select`] for a temperature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Waveform<L> {
+    /// Waveform of the driver, sent by the host
+    Host(L),
+    /// Waveform the controller has in its OTP for the temperature
+    Otp(i8),
+}
+
+/// Waveform of the band containing `celsius`, the one of the OTP if no band does
+///
+/// While the controller measures the temperature itself the first band is used, so `bands`
+/// must not be empty.
+pub(crate) fn select<L: Copy>(bands: &[LutBand<L>], celsius: Option<i8>) -> Waveform<L> {
+    match celsius {
+        None => Waveform::Host(bands[0].lut),
+        Some(celsius) => bands
+            .iter()
+            .find(|band| band.celsius.contains(&celsius))
+            .map_or(Waveform::Otp(celsius), |band| Waveform::Host(band.lut)),
+    }
+}
+
+/// Forced temperature selecting the fast waveform of the OTP of SSD168x controllers
+pub(crate) const FAST_CELSIUS: i8 = 100;
+
+/// Reasons for rejecting a waveform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LutError {
+    /// The table doesn't have the size the controller expects
+    Length {
+        /// Expected number of bytes
+        expected: usize,
+        /// Number of bytes given
+        found: usize,
+    },
+    /// A voltage register is set to a value outside of its documented range
+    Voltage {
+        /// Position of the byte in the complete waveform
+        index: usize,
+        /// The rejected value
+        value: u8,
+    },
+    /// The frame rate of a group doesn't fit into its 4 bits
+    FrameRate {
+        /// Index of the group
+        group: usize,
+        /// The rejected value
+        value: u8,
+    },
+}
+
+impl core::fmt::Display for LutError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LutError::Length { expected, found } => {
+                write!(f, "LUT has {} bytes instead of {}", found, expected)
+            }
+            LutError::Voltage { index, value } => {
+                write!(f, "Invalid voltage {:#04x} at byte {}", value, index)
+            }
+            LutError::FrameRate { group, value } => {
+                write!(
+                    f,
+                    "Frame rate {:#04x} of group {} exceeds 4 bits",
+                    value, group
+                )
+            }
+        }
+    }
+}
+
+fn check_length(bytes: &[u8], expected: usize) -> Result<(), LutError> {
+    if bytes.len() == expected {
+        Ok(())
+    } else {
+        Err(LutError::Length {
+            expected,
+            found: bytes.len(),
+        })
+    }
+}
+
+/// Voltage registers that follow the waveform of the SSD1680 family (SSD1680, SSD1681)
+///
+/// The values are the raw register settings of commands 0x3F, 0x03, 0x04 and 0x2C.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ssd1680Voltages {
+    /// End option (EOPT) of the waveform, e.g. 0x22 for normal, 0x07 to keep the source level
+    pub end_option: u8,
+    /// Gate driving voltage VGH, 0x00 or 0x03 (10V) to 0x17 (20V)
+    pub gate: u8,
+    /// Source driving voltages VSH1, VSH2 and VSL
+    ///
+    /// VSH1 and VSH2 are either 0x23 (9V) to 0x50 (18V) or 0x8E (2.4V) to 0xCE (8.8V), VSH2
+    /// may also be 0x00 as in Waveshare's waveforms. VSL is an even value from 0x1A (-9V) to
+    /// 0x3E (-18V).
+    pub source: [u8; 3],
+    /// VCOM from 0x08 (-0.2V) to 0x78 (-3V)
+    pub vcom: u8,
+}
+
+impl Ssd1680Voltages {
+    /// The registers in the order of Waveshare's 159-byte arrays
+    pub fn to_bytes(&self) -> [u8; 6] {
+        let [vsh1, vsh2, vsl] = self.source;
+        [self.end_option, self.gate, vsh1, vsh2, vsl, self.vcom]
+    }
+
+    /// Check all registers against the ranges of the datasheet
+    pub fn validate(&self) -> Result<(), LutError> {
+        let vsh = |value: u8| matches!(value, 0x23..=0x50 | 0x8E..=0xCE);
+        let checks = [
+            (self.gate, matches!(self.gate, 0x00 | 0x03..=0x17)),
+            (self.source[0], vsh(self.source[0])),
+            (self.source[1], self.source[1] == 0 || vsh(self.source[1])),
+            (
+                self.source[2],
+                matches!(self.source[2], 0x1A..=0x3E) && self.source[2] % 2 == 0,
+            ),
+            (self.vcom, matches!(self.vcom, 0x08..=0x78)),
+        ];
+        // the end option at 153 isn't checked, the registers start at 154
+        for (offset, (value, valid)) in checks.iter().enumerate() {
+            if !valid {
+                return Err(LutError::Voltage {
+                    index: Ssd1680Lut::WAVEFORM_LEN + 1 + offset,
+                    value: *value,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Waveform of the SSD1680 family (epd1in54_v2, epd2in9_v2 and epd2in13 v3)
+///
+/// The 153 bytes of command 0x32, optionally followed by the six bytes of
+/// [`Ssd1680Voltages`] as in Waveshare's 159-byte arrays. Without them the voltages of the
+/// controller are left as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ssd1680Lut {
+    waveform: [u8; Ssd1680Lut::WAVEFORM_LEN],
+    voltages: Option<Ssd1680Voltages>,
+}
+
+impl Ssd1680Lut {
+    /// Size of the waveform written with command 0x32
+    pub const WAVEFORM_LEN: usize = 153;
+    /// Size of the waveform including the voltage registers
+    pub const LEN: usize = 159;
+
+    /// Waveform without voltage settings
+    pub const fn new(waveform: [u8; Ssd1680Lut::WAVEFORM_LEN]) -> Self {
+        Ssd1680Lut {
+            waveform,
+            voltages: None,
+        }
+    }
+
+    /// Add the voltage registers, rejects them if they are out of range
+    pub fn with_voltages(mut self, voltages: Ssd1680Voltages) -> Result<Self, LutError> {
+        voltages.validate()?;
+        self.voltages = Some(voltages);
+        Ok(self)
+    }
+
+    /// Parse the 153 byte waveform or the 159 byte waveform with voltage registers
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LutError> {
+        if bytes.len() != Self::WAVEFORM_LEN {
+            check_length(bytes, Self::LEN)?;
+        }
+        let mut waveform = [0; Self::WAVEFORM_LEN];
+        waveform.copy_from_slice(&bytes[..Self::WAVEFORM_LEN]);
+        let lut = Self::new(waveform);
+        match bytes.get(Self::WAVEFORM_LEN..) {
+            Some([end_option, gate, vsh1, vsh2, vsl, vcom]) => lut.with_voltages(Ssd1680Voltages {
+                end_option: *end_option,
+                gate: *gate,
+                source: [*vsh1, *vsh2, *vsl],
+                vcom: *vcom,
+            }),
+            _ => Ok(lut),
+        }
+    }
+
+    /// The 153 bytes of command 0x32
+    pub fn waveform(&self) -> &[u8; Ssd1680Lut::WAVEFORM_LEN] {
+        &self.waveform
+    }
+
+    /// The voltage registers, if the waveform sets them
+    pub fn voltages(&self) -> Option<&Ssd1680Voltages> {
+        self.voltages.as_ref()
+    }
+
+    /// Split the waveform into its groups and phases
+    pub fn decode(&self) -> Ssd1680Waveform {
+        let bytes = &self.waveform;
+        let mut groups = [Ssd1680Group::EMPTY; SSD1680_GROUPS];
+        for (n, group) in groups.iter_mut().enumerate() {
+            for (lut, levels) in group.levels.iter_mut().enumerate() {
+                let byte = bytes[lut * SSD1680_GROUPS + n];
+                for (phase, level) in levels.iter_mut().enumerate() {
+                    *level = Ssd1680Level::from_bits(byte >> (6 - 2 * phase));
+                }
+            }
+            let timing = &bytes[SSD1680_TIMING + 7 * n..SSD1680_TIMING + 7 * (n + 1)];
+            group.frames = [timing[0], timing[1], timing[3], timing[4]];
+            group.phase_repeats = [timing[2], timing[5]];
+            group.repeat = timing[6];
+            group.frame_rate = bytes[SSD1680_FRAME_RATE + n / 2] >> (4 - 4 * (n % 2)) & 0x0F;
+            for (pair, gate_on) in group.gate_on.iter_mut().enumerate() {
+                let bit = 2 * n + pair;
+                *gate_on = bytes[SSD1680_GATE_ON + bit / 8] & (0x80 >> (bit % 8)) != 0;
+            }
+        }
+        Ssd1680Waveform {
+            groups,
+            voltages: self.voltages,
+        }
+    }
+}
+
+const SSD1680_GROUPS: usize = 12;
+const SSD1680_TIMING: usize = 5 * SSD1680_GROUPS;
+const SSD1680_FRAME_RATE: usize = SSD1680_TIMING + 7 * SSD1680_GROUPS;
+const SSD1680_GATE_ON: usize = SSD1680_FRAME_RATE + SSD1680_GROUPS / 2;
+
+/// Voltage of a phase, the VCOM LUT adds it to DCVCOM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ssd1680Level {
+    /// VSS, or DCVCOM for the VCOM LUT
+    #[default]
+    Vss,
+    /// VSH1
+    Vsh1,
+    /// VSL
+    Vsl,
+    /// VSH2
+    Vsh2,
+}
+
+impl Ssd1680Level {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => Ssd1680Level::Vss,
+            0b01 => Ssd1680Level::Vsh1,
+            0b10 => Ssd1680Level::Vsl,
+            _ => Ssd1680Level::Vsh2,
+        }
+    }
+
+    fn bits(self) -> u8 {
+        match self {
+            Ssd1680Level::Vss => 0b00,
+            Ssd1680Level::Vsh1 => 0b01,
+            Ssd1680Level::Vsl => 0b10,
+            Ssd1680Level::Vsh2 => 0b11,
+        }
+    }
+}
+
+/// One of the 12 groups of an SSD1680 waveform, made of the phases A, B, C and D
+///
+/// The values are the raw settings of the datasheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ssd1680Group {
+    /// Voltages of the phases per LUT
+    ///
+    /// A pixel is driven by LUT0 to LUT3 depending on its bits in the old (0x26) and the new
+    /// (0x24) RAM, 00 to 11 (BB, BW, WB and WW in Waveshare's comments). LUT4 drives VCOM.
+    pub levels: [[Ssd1680Level; 4]; 5],
+    /// Frames of each phase (TP)
+    pub frames: [u8; 4],
+    /// Repeats of the phases A and B, and of the phases C and D (SR)
+    pub phase_repeats: [u8; 2],
+    /// Repeats of the group (RP)
+    pub repeat: u8,
+    /// Frame rate of the group (FR), 4 bits
+    pub frame_rate: u8,
+    /// Gate scanning during the phases A and B, and during C and D (XON)
+    pub gate_on: [bool; 2],
+}
+
+impl Ssd1680Group {
+    /// Group which doesn't drive anything
+    pub const EMPTY: Ssd1680Group = Ssd1680Group {
+        levels: [[Ssd1680Level::Vss; 4]; 5],
+        frames: [0; 4],
+        phase_repeats: [0; 2],
+        repeat: 0,
+        frame_rate: 0,
+        gate_on: [false; 2],
+    };
+}
+
+/// Decoded waveform of the SSD1680 family, see [`Ssd1680Lut::decode`]
+///
+/// ```
+/// use epd_waveshare::lut::{Ssd1680Group, Ssd1680Level::*, Ssd1680Waveform};
+///
+/// let mut waveform = Ssd1680Waveform::default();
+/// // drive LUT1 with VSH1 and LUT2 with VSL for 20 frames
+/// waveform.groups[0] = Ssd1680Group {
+///     levels: [[Vss; 4], [Vsh1, Vss, Vss, Vss], [Vsl, Vss, Vss, Vss], [Vss; 4], [Vss; 4]],
+///     frames: [20, 0, 0, 0],
+///     ..Ssd1680Group::EMPTY
+/// };
+/// let lut = waveform.encode().unwrap();
+/// assert_eq!(lut.waveform()[12], 0x40);
+/// assert_eq!(lut.waveform()[60], 20);
+/// assert_eq!(lut.decode(), waveform);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ssd1680Waveform {
+    /// The groups in the order they are run
+    pub groups: [Ssd1680Group; 12],
+    /// The voltage registers following the waveform
+    pub voltages: Option<Ssd1680Voltages>,
+}
+
+impl Ssd1680Waveform {
+    /// Pack the waveform into the bytes of the controller, rejects frame rates or voltages
+    /// outside of their range
+    pub fn encode(&self) -> Result<Ssd1680Lut, LutError> {
+        let mut bytes = [0; Ssd1680Lut::WAVEFORM_LEN];
+        for (n, group) in self.groups.iter().enumerate() {
+            if group.frame_rate > 0x0F {
+                return Err(LutError::FrameRate {
+                    group: n,
+                    value: group.frame_rate,
+                });
+            }
+            for (lut, levels) in group.levels.iter().enumerate() {
+                bytes[lut * SSD1680_GROUPS + n] =
+                    levels.iter().enumerate().fold(0, |byte, (phase, level)| {
+                        byte | level.bits() << (6 - 2 * phase)
+                    });
+            }
+            let [a, b, c, d] = group.frames;
+            let [ab, cd] = group.phase_repeats;
+            bytes[SSD1680_TIMING + 7 * n..SSD1680_TIMING + 7 * (n + 1)].copy_from_slice(&[
+                a,
+                b,
+                ab,
+                c,
+                d,
+                cd,
+                group.repeat,
+            ]);
+            bytes[SSD1680_FRAME_RATE + n / 2] |= group.frame_rate << (4 - 4 * (n % 2));
+            for (pair, gate_on) in group.gate_on.iter().enumerate() {
+                let bit = 2 * n + pair;
+                if *gate_on {
+                    bytes[SSD1680_GATE_ON + bit / 8] |= 0x80 >> (bit % 8);
+                }
+            }
+        }
+        let lut = Ssd1680Lut::new(bytes);
+        match self.voltages {
+            Some(voltages) => lut.with_voltages(voltages),
+            None => Ok(lut),
+        }
+    }
+}
+
+/// Decodes and encodes a waveform and checks that it comes out unchanged
+#[cfg(test)]
+pub(crate) fn assert_round_trip(bytes: &[u8]) {
+    let lut = Ssd1680Lut::from_bytes(bytes).unwrap();
+    let encoded = lut.decode().encode().unwrap();
+    assert_eq!(&encoded.waveform()[..], &bytes[..Ssd1680Lut::WAVEFORM_LEN]);
+    assert_eq!(
+        encoded.voltages().map(|voltages| voltages.to_bytes()),
+        bytes[Ssd1680Lut::WAVEFORM_LEN..].try_into().ok()
+    );
+}
+
+/// Waveform of the UC8176 family (epd4in2)
+///
+/// The VCOM table and the four tables for the transitions between black and white, written
+/// with the commands 0x20 to 0x24. The panel must be set to take its LUT from the registers,
+/// which the drivers of this crate already do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Uc8176Lut {
+    /// VCOM table
+    pub vcom: [u8; Uc8176Lut::VCOM_LEN],
+    /// White to white
+    pub ww: [u8; Uc8176Lut::COLOR_LEN],
+    /// Black to white
+    pub bw: [u8; Uc8176Lut::COLOR_LEN],
+    /// White to black
+    pub wb: [u8; Uc8176Lut::COLOR_LEN],
+    /// Black to black
+    pub bb: [u8; Uc8176Lut::COLOR_LEN],
+}
+
+impl Uc8176Lut {
+    /// Size of the VCOM table
+    pub const VCOM_LEN: usize = 44;
+    /// Size of each color table
+    pub const COLOR_LEN: usize = 42;
+
+    /// Waveform from the five tables
+    pub const fn new(
+        vcom: [u8; Uc8176Lut::VCOM_LEN],
+        ww: [u8; Uc8176Lut::COLOR_LEN],
+        bw: [u8; Uc8176Lut::COLOR_LEN],
+        wb: [u8; Uc8176Lut::COLOR_LEN],
+        bb: [u8; Uc8176Lut::COLOR_LEN],
+    ) -> Self {
+        Uc8176Lut {
+            vcom,
+            ww,
+            bw,
+            wb,
+            bb,
+        }
+    }
+
+    /// Waveform from five slices, rejects them if a table has the wrong length
+    pub fn from_bytes(
+        vcom: &[u8],
+        ww: &[u8],
+        bw: &[u8],
+        wb: &[u8],
+        bb: &[u8],
+    ) -> Result<Self, LutError> {
+        check_length(vcom, Self::VCOM_LEN)?;
+        for table in [ww, bw, wb, bb] {
+            check_length(table, Self::COLOR_LEN)?;
+        }
+        let mut lut = Self::new(
+            [0; Self::VCOM_LEN],
+            [0; Self::COLOR_LEN],
+            [0; Self::COLOR_LEN],
+            [0; Self::COLOR_LEN],
+            [0; Self::COLOR_LEN],
+        );
+        lut.vcom.copy_from_slice(vcom);
+        lut.ww.copy_from_slice(ww);
+        lut.bw.copy_from_slice(bw);
+        lut.wb.copy_from_slice(wb);
+        lut.bb.copy_from_slice(bb);
+        Ok(lut)
+    }
+}
+
+/// Waveform of the SSD1677 family (epd3in7)
+///
+/// The 105 bytes of command 0x32.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ssd1677Lut([u8; Ssd1677Lut::LEN]);
+
+impl Ssd1677Lut {
+    /// Size of the waveform
+    pub const LEN: usize = 105;
+
+    /// Waveform from its bytes
+    pub const fn new(waveform: [u8; Ssd1677Lut::LEN]) -> Self {
+        Ssd1677Lut(waveform)
+    }
+
+    /// Waveform from a slice, rejects it if it has the wrong length
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LutError> {
+        check_length(bytes, Self::LEN)?;
+        let mut waveform = [0; Self::LEN];
+        waveform.copy_from_slice(bytes);
+        Ok(Self::new(waveform))
+    }
+
+    /// The bytes of command 0x32
+    pub fn waveform(&self) -> &[u8; Ssd1677Lut::LEN] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAIL: [u8; 6] = [0x22, 0x17, 0x41, 0x00, 0x32, 0x36];
+
+    fn ssd1680_bytes(tail: [u8; 6]) -> [u8; 159] {
+        let mut bytes = [0u8; 159];
+        for (i, b) in bytes.iter_mut().enumerate().take(153) {
+            *b = i as u8;
+        }
+        bytes[153..].copy_from_slice(&tail);
+        bytes
+    }
+
+    #[test]
+    fn ssd1680_lengths() {
+        let bytes = ssd1680_bytes(TAIL);
+        let lut = Ssd1680Lut::from_bytes(&bytes[..153]).unwrap();
+        assert_eq!(&lut.waveform()[..], &bytes[..153]);
+        assert_eq!(lut.voltages(), None);
+
+        let lut = Ssd1680Lut::from_bytes(&bytes).unwrap();
+        assert_eq!(&lut.waveform()[..], &bytes[..153]);
+        assert_eq!(
+            lut.voltages(),
+            Some(&Ssd1680Voltages {
+                end_option: 0x22,
+                gate: 0x17,
+                source: [0x41, 0x00, 0x32],
+                vcom: 0x36,
+            })
+        );
+
+        assert_eq!(
+            Ssd1680Lut::from_bytes(&bytes[..158]),
+            Err(LutError::Length {
+                expected: 159,
+                found: 158
+            })
+        );
+    }
+
+    #[test]
+    fn ssd1680_voltages() {
+        let mut tail = TAIL;
+        tail[3] = 0xB0;
+        assert!(Ssd1680Lut::from_bytes(&ssd1680_bytes(tail)).is_ok());
+
+        let invalid = [
+            (1, 0x18),
+            (2, 0x00),
+            (2, 0x51),
+            (3, 0x8D),
+            (4, 0x33),
+            (4, 0x40),
+            (5, 0x07),
+            (5, 0x79),
+        ];
+        for (offset, value) in invalid {
+            let mut bad = tail;
+            bad[offset] = value;
+            assert_eq!(
+                Ssd1680Lut::from_bytes(&ssd1680_bytes(bad)),
+                Err(LutError::Voltage {
+                    index: 153 + offset,
+                    value
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn ssd1680_decode() {
+        use Ssd1680Level::*;
+
+        let mut bytes = [0u8; 159];
+        // LUT1 of group 0 and LUT4 of group 11
+        bytes[12] = 0b01_10_11_00;
+        bytes[59] = 0b00_00_00_11;
+        // group 1: TP A, TP B, SR AB, TP C, TP D, SR CD, RP
+        bytes[67..74].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7]);
+        // frame rates of the groups 0 and 1, and 11
+        bytes[144] = 0x21;
+        bytes[149] = 0x0F;
+        // XON of group 0 CD and group 11 AB
+        bytes[150] = 0x40;
+        bytes[152] = 0x02;
+        bytes[153..].copy_from_slice(&TAIL);
+
+        let waveform = Ssd1680Lut::from_bytes(&bytes).unwrap().decode();
+        let groups = &waveform.groups;
+        assert_eq!(groups[0].levels[1], [Vsh1, Vsl, Vsh2, Vss]);
+        assert_eq!(groups[11].levels[4], [Vss, Vss, Vss, Vsh2]);
+        assert_eq!(groups[1].frames, [1, 2, 4, 5]);
+        assert_eq!(groups[1].phase_repeats, [3, 6]);
+        assert_eq!(groups[1].repeat, 7);
+        assert_eq!(
+            (
+                groups[0].frame_rate,
+                groups[1].frame_rate,
+                groups[11].frame_rate
+            ),
+            (2, 1, 15)
+        );
+        assert_eq!(groups[0].gate_on, [false, true]);
+        assert_eq!(groups[11].gate_on, [true, false]);
+        assert_eq!(groups[5], Ssd1680Group::EMPTY);
+        assert_eq!(waveform.voltages.unwrap().to_bytes(), TAIL);
+
+        assert_round_trip(&bytes);
+        assert_round_trip(&bytes[..153]);
+    }
+
+    #[test]
+    fn ssd1680_encode_checks() {
+        let mut waveform = Ssd1680Waveform::default();
+        waveform.groups[3].frame_rate = 0x10;
+        assert_eq!(
+            waveform.encode(),
+            Err(LutError::FrameRate {
+                group: 3,
+                value: 0x10
+            })
+        );
+
+        let mut tail = TAIL;
+        tail[5] = 0x80;
+        let waveform = Ssd1680Waveform {
+            voltages: Some(Ssd1680Voltages {
+                end_option: tail[0],
+                gate: tail[1],
+                source: [tail[2], tail[3], tail[4]],
+                vcom: tail[5],
+            }),
+            ..Ssd1680Waveform::default()
+        };
+        assert_eq!(
+            waveform.encode(),
+            Err(LutError::Voltage {
+                index: 158,
+                value: 0x80
+            })
+        );
+    }
+
+    #[test]
+    fn uc8176_lengths() {
+        let lut = Uc8176Lut::from_bytes(&[1; 44], &[2; 42], &[3; 42], &[4; 42], &[5; 42]).unwrap();
+        assert_eq!(
+            lut,
+            Uc8176Lut::new([1; 44], [2; 42], [3; 42], [4; 42], [5; 42])
+        );
+
+        assert_eq!(
+            Uc8176Lut::from_bytes(&[0; 42], &[0; 42], &[0; 42], &[0; 42], &[0; 42]),
+            Err(LutError::Length {
+                expected: 44,
+                found: 42
+            })
+        );
+        assert_eq!(
+            Uc8176Lut::from_bytes(&[0; 44], &[0; 42], &[0; 42], &[0; 36], &[0; 42]),
+            Err(LutError::Length {
+                expected: 42,
+                found: 36
+            })
+        );
+    }
+
+    #[test]
+    fn bands() {
+        let bands = [
+            LutBand {
+                celsius: 0..=19,
+                lut: 'c',
+            },
+            LutBand {
+                celsius: HOST_LUT_CELSIUS,
+                lut: 'r',
+            },
+        ];
+        assert_eq!(select(&bands, None), Waveform::Host('c'));
+        assert_eq!(select(&bands, Some(5)), Waveform::Host('c'));
+        assert_eq!(select(&bands, Some(30)), Waveform::Host('r'));
+        assert_eq!(select(&bands, Some(31)), Waveform::Otp(31));
+        assert_eq!(select(&bands, Some(-5)), Waveform::Otp(-5));
+    }
+
+    #[test]
+    fn ssd1677_lengths() {
+        let lut = Ssd1677Lut::from_bytes(&[7; 105]).unwrap();
+        assert_eq!(lut.waveform(), &[7; 105]);
+        assert_eq!(
+            Ssd1677Lut::from_bytes(&[7; 104]),
+            Err(LutError::Length {
+                expected: 105,
+                found: 104
+            })
+        );
+    }
+}
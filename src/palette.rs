@@ -0,0 +1,391 @@
+//! Perceptual color matching against the colors the panels really show
+//!
+//! The colors of an e-paper panel are a lot less saturated than their names suggest, e.g. the green
+//! of the 7 color (ACeP) panels is a dark teal. A [`Palette`] holds the sRGB value of each color
+//! of a panel and finds the nearest one in the [OKLab](https://bottosson.github.io/posts/oklab/)
+//! color space, where distances follow the perceived difference. All computations are done in
+//! fixed point, so a palette can be built in a `const`.
+//!
+//! Every multi-color type has a default palette ([`PaletteColor::PALETTE`]), which is used by its
+//! `From<Rgb888>` conversion and thus by the image decoding and its dithering. For calibrated
+//! results, build a palette with the values measured on your panel and use it with
+//! [`Palette::nearest`] or `image::draw_with_palette`:
+//!
+//!```rust
+//!use epd_waveshare::{color::OctColor, palette::{Palette, PaletteEntry}};
+//!
+//!const MY_PANEL: Palette<'static, OctColor> = Palette::new(&[
+//!    PaletteEntry::new(OctColor::Black, (0x30, 0x2A, 0x33)),
+//!    PaletteEntry::new(OctColor::White, (0xE8, 0xE8, 0xE0)),
+//!    PaletteEntry::new(OctColor::Green, (0x35, 0x5E, 0x45)),
+//!    PaletteEntry::new(OctColor::Blue, (0x3A, 0x3B, 0x60)),
+//!    PaletteEntry::new(OctColor::Red, (0xA0, 0x45, 0x48)),
+//!    PaletteEntry::new(OctColor::Yellow, (0xD5, 0xC0, 0x45)),
+//!    PaletteEntry::new(OctColor::Orange, (0xB8, 0x6A, 0x48)),
+//!]);
+//!
+//!assert_eq!(MY_PANEL.nearest((0x00, 0x80, 0x40)), OctColor::Green);
+//!```
+
+use crate::color::{Color, OctColor, QuadColor, SixColor, TriColor};
+
+/// Color in the OKLab color space, in fixed point where 65536 is 1.0
+///
+/// `l` is the lightness from 0 (black) to 65536 (white), `a` goes from green to red and `b` from
+/// blue to yellow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Oklab {
+    /// Lightness
+    pub l: i32,
+    /// Green (negative) to red (positive)
+    pub a: i32,
+    /// Blue (negative) to yellow (positive)
+    pub b: i32,
+}
+
+impl Oklab {
+    /// Convert an sRGB color
+    pub const fn from_srgb((r, g, b): (u8, u8, u8)) -> Oklab {
+        let rgb = [
+            SRGB_TO_LINEAR[r as usize] as i64,
+            SRGB_TO_LINEAR[g as usize] as i64,
+            SRGB_TO_LINEAR[b as usize] as i64,
+        ];
+        let lms = [
+            cbrt(mul(&RGB_TO_LMS[0], &rgb)),
+            cbrt(mul(&RGB_TO_LMS[1], &rgb)),
+            cbrt(mul(&RGB_TO_LMS[2], &rgb)),
+        ];
+        Oklab {
+            l: mul(&LMS_TO_LAB[0], &lms) as i32,
+            a: mul(&LMS_TO_LAB[1], &lms) as i32,
+            b: mul(&LMS_TO_LAB[2], &lms) as i32,
+        }
+    }
+
+    /// Squared euclidean distance, i.e. how different both colors look
+    pub const fn distance(self, other: Oklab) -> i64 {
+        let l = (self.l - other.l) as i64;
+        let a = (self.a - other.a) as i64;
+        let b = (self.b - other.b) as i64;
+        l * l + a * a + b * b
+    }
+}
+
+/// A color of a [`Palette`] with the sRGB value it has on the panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaletteEntry<C> {
+    color: C,
+    rgb: (u8, u8, u8),
+    oklab: Oklab,
+}
+
+impl<C: Copy> PaletteEntry<C> {
+    /// `color` looks like `rgb` on the panel
+    pub const fn new(color: C, rgb: (u8, u8, u8)) -> Self {
+        Self {
+            color,
+            rgb,
+            oklab: Oklab::from_srgb(rgb),
+        }
+    }
+
+    /// Color of the display
+    pub const fn color(&self) -> C {
+        self.color
+    }
+
+    /// sRGB value of the color on the panel
+    pub const fn rgb(&self) -> (u8, u8, u8) {
+        self.rgb
+    }
+
+    /// OKLab value of the color on the panel
+    pub const fn oklab(&self) -> Oklab {
+        self.oklab
+    }
+}
+
+/// Colors of a panel, see the [module documentation](self)
+#[derive(Clone, Copy, Debug)]
+pub struct Palette<'a, C> {
+    entries: &'a [PaletteEntry<C>],
+}
+
+impl<'a, C: Copy> Palette<'a, C> {
+    /// Palette of the `entries`, panics if there are none
+    pub const fn new(entries: &'a [PaletteEntry<C>]) -> Self {
+        assert!(!entries.is_empty(), "a palette needs at least one color");
+        Self { entries }
+    }
+
+    /// Colors of the palette
+    pub const fn entries(&self) -> &'a [PaletteEntry<C>] {
+        self.entries
+    }
+
+    /// Color which looks the most like the sRGB value `rgb`
+    pub fn nearest(&self, rgb: (u8, u8, u8)) -> C {
+        self.nearest_oklab(Oklab::from_srgb(rgb))
+    }
+
+    /// Color which looks the most like `oklab`
+    pub fn nearest_oklab(&self, oklab: Oklab) -> C {
+        let mut nearest = &self.entries[0];
+        for entry in &self.entries[1..] {
+            if entry.oklab.distance(oklab) < nearest.oklab.distance(oklab) {
+                nearest = entry;
+            }
+        }
+        nearest.color
+    }
+
+    /// sRGB value of `color` on the panel, `None` if it isn't part of the palette
+    pub fn rgb(&self, color: C) -> Option<(u8, u8, u8)>
+    where
+        C: PartialEq,
+    {
+        self.entries
+            .iter()
+            .find(|entry| entry.color == color)
+            .map(|entry| entry.rgb)
+    }
+}
+
+/// Color type with a default palette
+pub trait PaletteColor: Copy + 'static {
+    /// Typical colors of the panels using this type. They vary with the lighting and from panel to
+    /// panel, measure yours for calibrated results.
+    const PALETTE: Palette<'static, Self>;
+}
+
+impl PaletteColor for Color {
+    const PALETTE: Palette<'static, Self> = Palette::new(&[
+        PaletteEntry::new(Color::Black, (0x23, 0x23, 0x23)),
+        PaletteEntry::new(Color::White, (0xE6, 0xE6, 0xE1)),
+    ]);
+}
+
+impl PaletteColor for TriColor {
+    /// The chromatic color is the red of the black/white/red panels, see
+    /// [`TriColor::palette_entries`] for the other inks
+    const PALETTE: Palette<'static, Self> =
+        Palette::new(&TriColor::palette_entries(TriColor::RED_INK));
+}
+
+impl TriColor {
+    /// Typical colors of the panels with the chromatic `ink`, given as `0xRRGGBB` like the
+    /// `CHROMATIC` parameter of [`Display`](crate::graphics::Display)
+    ///
+    /// The red and yellow inks use the colors measured on the panels, any other ink is taken as
+    /// it is.
+    pub const fn palette_entries(ink: u32) -> [PaletteEntry<TriColor>; 3] {
+        let chromatic = match ink {
+            TriColor::RED_INK => (0xB4, 0x28, 0x28),
+            TriColor::YELLOW_INK => (0xE6, 0xC8, 0x28),
+            _ => TriColor::ink_rgb(ink),
+        };
+        [
+            PaletteEntry::new(TriColor::Black, (0x23, 0x23, 0x23)),
+            PaletteEntry::new(TriColor::White, (0xE6, 0xE6, 0xE1)),
+            PaletteEntry::new(TriColor::Chromatic, chromatic),
+        ]
+    }
+}
+
+impl PaletteColor for QuadColor {
+    const PALETTE: Palette<'static, Self> = Palette::new(&[
+        PaletteEntry::new(QuadColor::Black, (0x23, 0x23, 0x23)),
+        PaletteEntry::new(QuadColor::White, (0xE6, 0xE6, 0xE1)),
+        PaletteEntry::new(QuadColor::Yellow, (0xE6, 0xC8, 0x28)),
+        PaletteEntry::new(QuadColor::Red, (0xB4, 0x28, 0x28)),
+    ]);
+}
+
+impl PaletteColor for OctColor {
+    /// The 7 colors of the ACeP panels, `HiZ` is left out as it isn't meant for drawing
+    const PALETTE: Palette<'static, Self> = Palette::new(&[
+        PaletteEntry::new(OctColor::Black, (0x39, 0x30, 0x39)),
+        PaletteEntry::new(OctColor::White, (0xFF, 0xFF, 0xFF)),
+        PaletteEntry::new(OctColor::Green, (0x3A, 0x5B, 0x46)),
+        PaletteEntry::new(OctColor::Blue, (0x3D, 0x3B, 0x5E)),
+        PaletteEntry::new(OctColor::Red, (0x9C, 0x48, 0x4B)),
+        PaletteEntry::new(OctColor::Yellow, (0xD0, 0xBE, 0x47)),
+        PaletteEntry::new(OctColor::Orange, (0xB1, 0x6A, 0x49)),
+    ]);
+}
+
+impl PaletteColor for SixColor {
+    /// The 6 colors of the Spectra 6 panels
+    const PALETTE: Palette<'static, Self> = Palette::new(&[
+        PaletteEntry::new(SixColor::Black, (0x19, 0x1E, 0x21)),
+        PaletteEntry::new(SixColor::White, (0xE8, 0xE8, 0xE8)),
+        PaletteEntry::new(SixColor::Yellow, (0xEF, 0xDE, 0x44)),
+        PaletteEntry::new(SixColor::Red, (0xB2, 0x13, 0x18)),
+        PaletteEntry::new(SixColor::Blue, (0x21, 0x57, 0xBA)),
+        PaletteEntry::new(SixColor::Green, (0x12, 0x5F, 0x20)),
+    ]);
+}
+
+// Row of a fixed point matrix (65536 is 1.0) times a vector
+const fn mul(row: &[i64; 3], vector: &[i64; 3]) -> i64 {
+    (row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2]) >> 16
+}
+
+// Cube root in fixed point (65536 is 1.0)
+const fn cbrt(value: i64) -> i64 {
+    let negative = value < 0;
+    let value = (if negative { -value } else { value } as u64) << 32;
+    // largest root with root^3 <= value
+    let (mut low, mut high) = (0u64, 1 << 21);
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if mid * mid * mid <= value {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    if negative {
+        -(low as i64)
+    } else {
+        low as i64
+    }
+}
+
+// Linear RGB to cone responses
+const RGB_TO_LMS: [[i64; 3]; 3] = [
+    [27015, 35149, 3372],
+    [13887, 44610, 7038],
+    [5787, 18463, 41286],
+];
+
+// Cube root of the cone responses to OKLab
+const LMS_TO_LAB: [[i64; 3]; 3] = [
+    [13792, 52011, -267],
+    [129630, -159160, 29530],
+    [1698, 51300, -52997],
+];
+
+// sRGB gamma expansion, 65535 is 1.0
+#[rustfmt::skip]
+const SRGB_TO_LINEAR: [u16; 256] = [
+    0, 20, 40, 60, 80, 99, 119, 139, 159, 179, 199, 219,
+    241, 264, 288, 313, 340, 367, 396, 427, 458, 491, 526, 562,
+    599, 637, 677, 718, 761, 805, 851, 898, 947, 997, 1048, 1101,
+    1156, 1212, 1270, 1330, 1391, 1453, 1517, 1583, 1651, 1720, 1790, 1863,
+    1937, 2013, 2090, 2170, 2250, 2333, 2418, 2504, 2592, 2681, 2773, 2866,
+    2961, 3058, 3157, 3258, 3360, 3464, 3570, 3678, 3788, 3900, 4014, 4129,
+    4247, 4366, 4488, 4611, 4736, 4864, 4993, 5124, 5257, 5392, 5530, 5669,
+    5810, 5953, 6099, 6246, 6395, 6547, 6700, 6856, 7014, 7174, 7335, 7500,
+    7666, 7834, 8004, 8177, 8352, 8528, 8708, 8889, 9072, 9258, 9445, 9635,
+    9828, 10022, 10219, 10417, 10619, 10822, 11028, 11235, 11446, 11658, 11873, 12090,
+    12309, 12530, 12754, 12980, 13209, 13440, 13673, 13909, 14146, 14387, 14629, 14874,
+    15122, 15371, 15623, 15878, 16135, 16394, 16656, 16920, 17187, 17456, 17727, 18001,
+    18277, 18556, 18837, 19121, 19407, 19696, 19987, 20281, 20577, 20876, 21177, 21481,
+    21787, 22096, 22407, 22721, 23038, 23357, 23678, 24002, 24329, 24658, 24990, 25325,
+    25662, 26001, 26344, 26688, 27036, 27386, 27739, 28094, 28452, 28813, 29176, 29542,
+    29911, 30282, 30656, 31033, 31412, 31794, 32179, 32567, 32957, 33350, 33745, 34143,
+    34544, 34948, 35355, 35764, 36176, 36591, 37008, 37429, 37852, 38278, 38706, 39138,
+    39572, 40009, 40449, 40891, 41337, 41785, 42236, 42690, 43147, 43606, 44069, 44534,
+    45002, 45473, 45947, 46423, 46903, 47385, 47871, 48359, 48850, 49344, 49841, 50341,
+    50844, 51349, 51858, 52369, 52884, 53401, 53921, 54445, 54971, 55500, 56032, 56567,
+    57105, 57646, 58190, 58737, 59287, 59840, 60396, 60955, 61517, 62082, 62650, 63221,
+    63795, 64372, 64952, 65535,];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oklab_reference_values() {
+        // reference values computed in floating point
+        let close = |rgb, (l, a, b): (f32, f32, f32)| {
+            let lab = Oklab::from_srgb(rgb);
+            let scale = |v: f32| (v * 65536.0) as i32;
+            assert!((lab.l - scale(l)).abs() < 40, "{:?}: {:?}", rgb, lab);
+            assert!((lab.a - scale(a)).abs() < 40, "{:?}: {:?}", rgb, lab);
+            assert!((lab.b - scale(b)).abs() < 40, "{:?}: {:?}", rgb, lab);
+        };
+        close((0, 0, 0), (0.0, 0.0, 0.0));
+        close((255, 255, 255), (1.0, 0.0, 0.0));
+        close((255, 0, 0), (0.62796, 0.22486, 0.12585));
+        close((0, 255, 0), (0.86644, -0.23389, 0.17950));
+        close((0, 0, 255), (0.45201, -0.03246, -0.31153));
+        close((128, 128, 128), (0.59987, 0.0, 0.0));
+    }
+
+    #[test]
+    fn cube_root() {
+        assert_eq!(cbrt(0), 0);
+        assert_eq!(cbrt(65536), 65536);
+        assert_eq!(cbrt(8192), 32768);
+        assert_eq!(cbrt(-8192), -32768);
+    }
+
+    #[test]
+    fn nearest_is_perceptual() {
+        assert_eq!(
+            OctColor::PALETTE.nearest((0x20, 0x80, 0x40)),
+            OctColor::Green
+        );
+        assert_eq!(
+            OctColor::PALETTE.nearest((0x3A, 0x5B, 0x46)),
+            OctColor::Green
+        );
+        // a brown looks like the orange of the panel, not like its red
+        assert_eq!(
+            OctColor::PALETTE.nearest((0xA0, 0x60, 0x40)),
+            OctColor::Orange
+        );
+        assert_eq!(
+            OctColor::PALETTE.nearest((0xF0, 0xF0, 0xF0)),
+            OctColor::White
+        );
+        assert_eq!(
+            SixColor::PALETTE.nearest((0x30, 0x60, 0xFF)),
+            SixColor::Blue
+        );
+        assert_eq!(
+            TriColor::PALETTE.nearest((0xE0, 0x30, 0x30)),
+            TriColor::Chromatic
+        );
+        assert_eq!(
+            QuadColor::PALETTE.nearest((0xFF, 0xD0, 0x00)),
+            QuadColor::Yellow
+        );
+        assert_eq!(Color::PALETTE.nearest((0x40, 0x40, 0x40)), Color::Black);
+    }
+
+    #[test]
+    fn custom_palette() {
+        const PALETTE: Palette<'static, TriColor> = Palette::new(&[
+            PaletteEntry::new(TriColor::Black, (0, 0, 0)),
+            PaletteEntry::new(TriColor::White, (0xFF, 0xFF, 0xFF)),
+            PaletteEntry::new(TriColor::Chromatic, (0xE0, 0xC0, 0x20)),
+        ]);
+        assert_eq!(PALETTE.nearest((0xFF, 0xFF, 0x00)), TriColor::Chromatic);
+        assert_eq!(PALETTE.nearest((0xFF, 0x00, 0x00)), TriColor::Chromatic);
+        assert_eq!(PALETTE.rgb(TriColor::Chromatic), Some((0xE0, 0xC0, 0x20)));
+        assert_eq!(PALETTE.entries().len(), 3);
+    }
+
+    #[test]
+    fn tricolor_inks() {
+        let yellow = TriColor::palette_entries(TriColor::YELLOW_INK);
+        let yellow = Palette::new(&yellow);
+        assert_eq!(yellow.nearest((0xFF, 0xD0, 0x00)), TriColor::Chromatic);
+        // red is too dark to be printed with yellow ink
+        assert_eq!(yellow.nearest((0x80, 0x00, 0x00)), TriColor::Black);
+        assert_eq!(
+            TriColor::PALETTE.nearest((0xFF, 0xD0, 0x00)),
+            TriColor::White
+        );
+
+        let blue = TriColor::palette_entries(0x0000FF);
+        assert_eq!(
+            Palette::new(&blue).rgb(TriColor::Chromatic),
+            Some((0x00, 0x00, 0xFF))
+        );
+    }
+}
@@ -19,12 +19,24 @@ impl Rect {
     pub const fn new(x: u32, y: u32, w: u32, h: u32) -> Rect {
         Rect { x, y, w, h }
     }
+    /// First column right of the rectangle, saturating at `u32::MAX`
+    pub const fn right(&self) -> u32 {
+        self.x.saturating_add(self.w)
+    }
+    /// First row below the rectangle, saturating at `u32::MAX`
+    pub const fn bottom(&self) -> u32 {
+        self.y.saturating_add(self.h)
+    }
+    /// Number of pixels, saturating at `u32::MAX`
+    pub const fn area(&self) -> u32 {
+        self.w.saturating_mul(self.h)
+    }
     /// Compute intersection with another rectangle
     pub fn intersect(&self, other: Rect) -> Rect {
         let x = cmp::max(self.x, other.x);
         let y = cmp::max(self.y, other.y);
-        let w = cmp::min(self.x + self.w, other.x + other.w).saturating_sub(x);
-        let h = cmp::min(self.y + self.h, other.y + other.h).saturating_sub(y);
+        let w = cmp::min(self.right(), other.right()).saturating_sub(x);
+        let h = cmp::min(self.bottom(), other.bottom()).saturating_sub(y);
         Rect { x, y, w, h }
     }
     /// Move rectangle by (-dx,-dy)
@@ -71,6 +83,18 @@ fn test_intersect() {
     ));
 }
 
+#[test]
+fn intersect_large() {
+    let r1 = Rect::new(0, 0, 10, 10);
+    let r2 = Rect::new(5, 5, u32::MAX, u32::MAX);
+    assert_eq!(r1.intersect(r2), Rect::new(5, 5, 5, 5));
+    assert_eq!(r2.right(), u32::MAX);
+    assert_eq!(r2.area(), u32::MAX);
+
+    let r3 = Rect::new(u32::MAX, 0, 10, 10);
+    assert!(r1.intersect(r3).is_empty());
+}
+
 #[test]
 fn sub_offset() {
     let r1 = Rect::new(10, 10, 10, 10);
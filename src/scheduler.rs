@@ -0,0 +1,679 @@
+//! Scheduling of full refreshes between the quick ones
+//!
+//! Quick and partial refreshes leave a bit of the previous image behind, which adds up until a
+//! full refresh clears it. A [`RefreshScheduler`] wraps a driver and counts the quick refreshes,
+//! the pixels they changed and the time reported with [`RefreshScheduler::elapsed`]. Once one of
+//! the limits of its [`RefreshPolicy`] is reached, the next update is done with the full LUT.
+//!
+//! The drivers with quick refreshes have default limits ([`DefaultRefreshPolicy`]).
+//!
+//! Ghosting which builds up over many cycles isn't removed by a single full refresh. With
+//! [`RefreshPolicy::max_full_refreshes`] set, [`RefreshScheduler::deep_clean_due`] tells when to
+//! run the longer cycle of [`RefreshScheduler::deep_clean`].
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), embedded_hal::spi::ErrorKind> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{epd2in9::*, prelude::*, scheduler::RefreshScheduler};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!let mut epd = RefreshScheduler::new(epd);
+//!let mut display = Display2in9::default();
+//!
+//!for y in 0..100 {
+//!    let _ = Line::new(Point::new(0, y), Point::new(127, y))
+//!        .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!        .draw(&mut display);
+//!    // full refresh for the first frame and every few frames, quick ones in between
+//!    let _refresh = epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)?;
+//!    epd.elapsed(60_000);
+//!}
+//!# Ok(())
+//!# }
+//!```
+
+use crate::traits::{CleanStep, RefreshLut, WaveshareDisplay};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+#[cfg(feature = "graphics")]
+use crate::{color::ColorType, graphics::DoubleBufferedDisplay, rect::Rect, traits::QuickRefresh};
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::pixelcolor::PixelColor;
+
+/// One day, the longest time a panel should keep an image without a full refresh
+const DAY_MS: u32 = 24 * 60 * 60 * 1000;
+
+/// Limits after which the next refresh is a full one, `None` disables a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    /// Number of quick refreshes in a row
+    pub max_quick_refreshes: Option<u32>,
+    /// Number of pixels changed by the quick refreshes, i.e. the sum of the areas of their
+    /// windows
+    pub max_changed_pixels: Option<u32>,
+    /// Time since the last full refresh in ms, as reported with [`RefreshScheduler::elapsed`]
+    pub max_elapsed_ms: Option<u32>,
+    /// Number of full refreshes after which a deep clean is due, see
+    /// [`RefreshScheduler::deep_clean_due`]
+    pub max_full_refreshes: Option<u32>,
+}
+
+impl RefreshPolicy {
+    /// Never forces a full refresh
+    pub const UNLIMITED: RefreshPolicy = RefreshPolicy {
+        max_quick_refreshes: None,
+        max_changed_pixels: None,
+        max_elapsed_ms: None,
+        max_full_refreshes: None,
+    };
+
+    /// Limits for a `width` x `height` panel: `max_quick_refreshes` quick refreshes, quick
+    /// refreshes changing as many pixels as half of them sent as whole frames, and a day without
+    /// full refresh. Deep cleans are left to the application.
+    pub const fn for_panel(width: u32, height: u32, max_quick_refreshes: u32) -> RefreshPolicy {
+        RefreshPolicy {
+            max_quick_refreshes: Some(max_quick_refreshes),
+            max_changed_pixels: Some(width * height * max_quick_refreshes / 2),
+            max_elapsed_ms: Some(DAY_MS),
+            max_full_refreshes: None,
+        }
+    }
+}
+
+/// Drivers with quick refreshes, and the limits recommended for their panel
+pub trait DefaultRefreshPolicy {
+    /// Limits used by [`RefreshScheduler::new`]
+    const REFRESH_POLICY: RefreshPolicy;
+}
+
+/// Driver wrapper which switches between quick and full refreshes
+///
+/// The first update after creating the scheduler is a full refresh, as the content of the screen
+/// is unknown. Refreshes done on the driver itself (see [`RefreshScheduler::epd_mut`]) can be
+/// accounted for with [`RefreshScheduler::record`].
+#[derive(Debug)]
+pub struct RefreshScheduler<EPD> {
+    epd: EPD,
+    policy: RefreshPolicy,
+    quick_refreshes: u32,
+    changed_pixels: u32,
+    elapsed_ms: u32,
+    full_requested: bool,
+    full_refreshes: u32,
+}
+
+impl<EPD: DefaultRefreshPolicy> RefreshScheduler<EPD> {
+    /// Wrap a driver, with the default limits of its panel
+    pub fn new(epd: EPD) -> Self {
+        Self::with_policy(epd, EPD::REFRESH_POLICY)
+    }
+}
+
+impl<EPD> RefreshScheduler<EPD> {
+    /// Wrap a driver, with the given limits
+    pub fn with_policy(epd: EPD, policy: RefreshPolicy) -> Self {
+        RefreshScheduler {
+            epd,
+            policy,
+            quick_refreshes: 0,
+            changed_pixels: 0,
+            elapsed_ms: 0,
+            full_requested: true,
+            full_refreshes: 0,
+        }
+    }
+
+    /// The wrapped driver
+    pub fn epd(&self) -> &EPD {
+        &self.epd
+    }
+
+    /// The wrapped driver, e.g. to put it to sleep
+    pub fn epd_mut(&mut self) -> &mut EPD {
+        &mut self.epd
+    }
+
+    /// Unwrap the driver
+    pub fn into_inner(self) -> EPD {
+        self.epd
+    }
+
+    /// Current limits
+    pub fn policy(&self) -> RefreshPolicy {
+        self.policy
+    }
+
+    /// Change the limits, the refreshes counted so far are kept
+    pub fn set_policy(&mut self, policy: RefreshPolicy) {
+        self.policy = policy;
+    }
+
+    /// Quick refreshes since the last full refresh
+    pub fn quick_refreshes(&self) -> u32 {
+        self.quick_refreshes
+    }
+
+    /// Pixels changed by the quick refreshes since the last full refresh
+    pub fn changed_pixels(&self) -> u32 {
+        self.changed_pixels
+    }
+
+    /// Time reported since the last full refresh, in ms
+    pub fn elapsed_ms(&self) -> u32 {
+        self.elapsed_ms
+    }
+
+    /// Full refreshes since the last deep clean
+    pub fn full_refreshes(&self) -> u32 {
+        self.full_refreshes
+    }
+
+    /// Whether the full refreshes since the last deep clean reached the limit of the policy
+    pub fn deep_clean_due(&self) -> bool {
+        self.policy
+            .max_full_refreshes
+            .map_or(false, |limit| self.full_refreshes >= limit)
+    }
+
+    /// Report the time passed since the last call, in ms
+    pub fn elapsed(&mut self, ms: u32) {
+        self.elapsed_ms = self.elapsed_ms.saturating_add(ms);
+    }
+
+    /// Make the next update a full refresh, e.g. after waking the device up
+    pub fn request_full_refresh(&mut self) {
+        self.full_requested = true;
+    }
+
+    /// Kind of the next refresh
+    pub fn next_refresh(&self) -> RefreshLut {
+        let reached = |limit: Option<u32>, value: u32| limit.map_or(false, |limit| value >= limit);
+        if self.full_requested
+            || reached(self.policy.max_quick_refreshes, self.quick_refreshes)
+            || reached(self.policy.max_changed_pixels, self.changed_pixels)
+            || reached(self.policy.max_elapsed_ms, self.elapsed_ms)
+        {
+            RefreshLut::Full
+        } else {
+            RefreshLut::Quick
+        }
+    }
+
+    /// Account for a refresh changing `changed_pixels` pixels
+    ///
+    /// A full refresh, also a fast one, starts the counting over.
+    pub fn record(&mut self, refresh: RefreshLut, changed_pixels: u32) {
+        match refresh {
+            RefreshLut::Full | RefreshLut::Fast => {
+                self.quick_refreshes = 0;
+                self.changed_pixels = 0;
+                self.elapsed_ms = 0;
+                self.full_requested = false;
+                self.full_refreshes = self.full_refreshes.saturating_add(1);
+            }
+            RefreshLut::Quick => {
+                self.quick_refreshes = self.quick_refreshes.saturating_add(1);
+                self.changed_pixels = self.changed_pixels.saturating_add(changed_pixels);
+            }
+        }
+    }
+
+    /// Transmit a full frame and display it with the next refresh
+    ///
+    /// Returns the kind of refresh which was done.
+    pub fn update_and_display_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<RefreshLut, SPI::Error>
+    where
+        SPI: SpiDevice,
+        BUSY: InputPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    {
+        let refresh = self.next_refresh();
+        self.epd.set_lut(spi, delay, Some(refresh))?;
+        self.epd.update_and_display_frame(spi, buffer, delay)?;
+        let pixels = self.epd.width().saturating_mul(self.epd.height());
+        self.record(refresh, pixels);
+        Ok(refresh)
+    }
+
+    /// Transmit a part of the frame and display it with the next refresh
+    ///
+    /// A full refresh redraws the whole screen from the RAM of the controller.
+    /// Returns the kind of refresh which was done.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_and_display_partial_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<RefreshLut, SPI::Error>
+    where
+        SPI: SpiDevice,
+        BUSY: InputPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    {
+        let refresh = self.next_refresh();
+        self.epd.set_lut(spi, delay, Some(refresh))?;
+        self.epd
+            .update_partial_frame(spi, delay, buffer, x, y, width, height)?;
+        self.epd.display_frame(spi, delay)?;
+        self.record(refresh, width.saturating_mul(height));
+        Ok(refresh)
+    }
+
+    /// Run the ghost clearing cycle of [`WaveshareDisplay::deep_clean`] with the full LUT
+    ///
+    /// `buffer` is the image shown at the end. Counts as a full refresh and starts the counting
+    /// of [`RefreshScheduler::deep_clean_due`] over.
+    pub fn deep_clean<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &mut [u8],
+        steps: &[CleanStep<EPD::DisplayColor>],
+    ) -> Result<(), SPI::Error>
+    where
+        SPI: SpiDevice,
+        BUSY: InputPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        EPD::DisplayColor: Copy,
+    {
+        self.epd.set_lut(spi, delay, Some(RefreshLut::Full))?;
+        self.epd.deep_clean(spi, delay, buffer, steps)?;
+        self.record(RefreshLut::Full, 0);
+        self.full_refreshes = 0;
+        Ok(())
+    }
+
+    /// Show the new frame of a [`DoubleBufferedDisplay`]
+    ///
+    /// A quick refresh only sends the changed window (see [`DoubleBufferedDisplay::flush`]) and
+    /// does nothing if nothing changed. A full refresh sends the whole frame, even if nothing
+    /// changed. Returns the kind of refresh and the refreshed window, `None` if nothing was done.
+    #[cfg(feature = "graphics")]
+    pub fn flush<
+        SPI,
+        BUSY,
+        DC,
+        RST,
+        DELAY,
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        COLOR: ColorType + PixelColor,
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    >(
+        &mut self,
+        display: &mut DoubleBufferedDisplay<
+            WIDTH,
+            HEIGHT,
+            BWRBIT,
+            BYTECOUNT,
+            COLOR,
+            BACKGROUND,
+            INVERTED,
+            CHROMATIC,
+        >,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<Option<(RefreshLut, Rect)>, SPI::Error>
+    where
+        SPI: SpiDevice,
+        BUSY: InputPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY> + QuickRefresh<SPI, BUSY, DC, RST, DELAY>,
+    {
+        match self.next_refresh() {
+            refresh @ (RefreshLut::Full | RefreshLut::Fast) => {
+                self.epd.set_lut(spi, delay, Some(refresh))?;
+                self.epd
+                    .update_and_display_frame(spi, display.buffer(), delay)?;
+                display.mark_displayed();
+                self.record(refresh, WIDTH.saturating_mul(HEIGHT));
+                Ok(Some((refresh, Rect::new(0, 0, WIDTH, HEIGHT))))
+            }
+            RefreshLut::Quick => {
+                if display.changed_window().is_none() {
+                    return Ok(None);
+                }
+                self.epd.set_lut(spi, delay, Some(RefreshLut::Quick))?;
+                let window = display.flush(&mut self.epd, spi, delay)?;
+                Ok(window.map(|window| {
+                    self.record(RefreshLut::Quick, window.area());
+                    (RefreshLut::Quick, window)
+                }))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "graphics"))]
+mod tests {
+    use super::*;
+    use crate::epd2in9::{Display2in9, Epd2in9, HEIGHT, WIDTH};
+    use crate::epd4in2::{self, Epd4in2};
+    use crate::epd5in65f::{self, Display5in65f, Epd5in65f};
+    use crate::graphics::DoubleBufferedDisplay;
+    use crate::simulator::{Controller, Refresh, Simulator};
+    use crate::traits::DefaultCleanSequence;
+    use crate::{
+        buffer_len,
+        color::{Color, OctColor},
+    };
+    use core::convert::Infallible;
+    use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+
+    #[test]
+    fn policy_limits() {
+        let policy = RefreshPolicy {
+            max_quick_refreshes: Some(2),
+            max_changed_pixels: Some(100),
+            max_elapsed_ms: Some(1000),
+            max_full_refreshes: Some(3),
+        };
+        let mut scheduler = RefreshScheduler::with_policy((), policy);
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Full);
+        scheduler.record(RefreshLut::Full, 0);
+
+        scheduler.record(RefreshLut::Quick, 10);
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Quick);
+        scheduler.record(RefreshLut::Quick, 10);
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Full);
+        scheduler.record(RefreshLut::Full, 0);
+        assert_eq!(scheduler.quick_refreshes(), 0);
+
+        scheduler.record(RefreshLut::Quick, 100);
+        assert_eq!(scheduler.changed_pixels(), 100);
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Full);
+        scheduler.record(RefreshLut::Full, 0);
+
+        scheduler.elapsed(999);
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Quick);
+        scheduler.elapsed(1);
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Full);
+        // a fast refresh is a full one as well
+        scheduler.record(RefreshLut::Fast, 0);
+        assert_eq!(scheduler.elapsed_ms(), 0);
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Quick);
+
+        scheduler.request_full_refresh();
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Full);
+        assert_eq!(scheduler.full_refreshes(), 4);
+        assert!(scheduler.deep_clean_due());
+
+        scheduler.set_policy(RefreshPolicy::UNLIMITED);
+        scheduler.record(RefreshLut::Full, 0);
+        for _ in 0..100 {
+            scheduler.record(RefreshLut::Quick, u32::MAX);
+        }
+        scheduler.elapsed(u32::MAX);
+        assert_eq!(scheduler.next_refresh(), RefreshLut::Quick);
+    }
+
+    #[test]
+    fn lut_switching() {
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let epd = Epd2in9::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut epd = RefreshScheduler::new(epd);
+        assert_eq!(
+            epd.policy().max_quick_refreshes,
+            Epd2in9::<(), (), (), (), ()>::REFRESH_POLICY.max_quick_refreshes
+        );
+        epd.set_policy(RefreshPolicy {
+            max_quick_refreshes: Some(3),
+            ..epd.policy()
+        });
+
+        let mut display = Display2in9::default();
+        let mut refreshes = std::vec::Vec::new();
+        for x in 0..6 {
+            Pixel(Point::new(x * 8, 0), Color::Black)
+                .draw(&mut display)
+                .unwrap();
+            refreshes.push(
+                epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+                    .unwrap(),
+            );
+            assert_eq!(
+                simulator.last_refresh(),
+                Some(Refresh::from(refreshes[x as usize]))
+            );
+        }
+        use RefreshLut::{Full, Quick};
+        assert_eq!(refreshes, [Full, Quick, Quick, Quick, Full, Quick]);
+        assert_eq!(simulator.displayed().pixel(40, 0), Some(Rgb888::BLACK));
+
+        // a full refresh of a partial update redraws everything
+        epd.request_full_refresh();
+        let refresh = epd
+            .update_and_display_partial_frame(&mut spi, &mut delay, &[0x00; 2], 64, 8, 8, 2)
+            .unwrap();
+        assert_eq!(refresh, Full);
+        assert_eq!(simulator.max_ghosting(), 0);
+        assert_eq!(simulator.displayed().pixel(64, 8), Some(Rgb888::BLACK));
+        let refresh = epd
+            .update_and_display_partial_frame(&mut spi, &mut delay, &[0xFF; 2], 64, 8, 8, 2)
+            .unwrap();
+        assert_eq!(refresh, Quick);
+        assert_eq!(epd.changed_pixels(), 16);
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+    }
+
+    #[test]
+    fn deep_clean_cycle() {
+        let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let epd = Epd2in9::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut epd = RefreshScheduler::with_policy(
+            epd,
+            RefreshPolicy {
+                max_quick_refreshes: Some(2),
+                max_full_refreshes: Some(2),
+                ..RefreshPolicy::UNLIMITED
+            },
+        );
+
+        let mut display = Display2in9::default();
+        for x in 0..6 {
+            Pixel(Point::new(x * 8, 0), Color::Black)
+                .draw(&mut display)
+                .unwrap();
+            epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+                .unwrap();
+        }
+        assert!(epd.deep_clean_due());
+        let image = simulator.displayed();
+        let refreshes = simulator.refreshes();
+
+        let mut buffer = std::vec::Vec::from(display.buffer());
+        epd.deep_clean(&mut spi, &mut delay, &mut buffer, Color::CLEAN_SEQUENCE)
+            .unwrap();
+        assert_eq!(simulator.refreshes(), refreshes + 4);
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+        assert_eq!(simulator.max_ghosting(), 0);
+        assert_eq!(simulator.displayed(), image);
+        assert_eq!(buffer, display.buffer());
+        assert!(!epd.deep_clean_due());
+        assert_eq!(epd.next_refresh(), RefreshLut::Quick);
+        // the background color is kept
+        assert_eq!(epd.epd().background_color(), &Color::White);
+    }
+
+    /// Busy pin of a controller which is never busy, whichever level the driver waits for
+    struct NeverBusy;
+
+    impl embedded_hal::digital::ErrorType for NeverBusy {
+        type Error = Infallible;
+    }
+
+    impl InputPin for NeverBusy {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(false)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            Ok(false)
+        }
+    }
+
+    #[test]
+    fn deep_clean_single_waveform() {
+        // the RAM of the simulated controller holds the 4 bits per pixel of the seven colors
+        let simulator = Simulator::new(Controller::Uc81xx, epd5in65f::WIDTH * 4, epd5in65f::HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let epd = Epd5in65f::new(
+            &mut spi,
+            NeverBusy,
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let mut epd = RefreshScheduler::with_policy(
+            epd,
+            RefreshPolicy {
+                max_full_refreshes: Some(1),
+                ..RefreshPolicy::UNLIMITED
+            },
+        );
+
+        let mut display = Display5in65f::default();
+        Pixel(Point::new(3, 5), OctColor::Red)
+            .draw(&mut display)
+            .unwrap();
+        let refresh = epd
+            .update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+            .unwrap();
+        assert_eq!(refresh, RefreshLut::Full);
+        assert!(epd.deep_clean_due());
+        let refreshes = simulator.refreshes();
+
+        let mut buffer = std::vec::Vec::from(display.buffer());
+        epd.deep_clean(&mut spi, &mut delay, &mut buffer, OctColor::CLEAN_SEQUENCE)
+            .unwrap();
+        assert_eq!(
+            simulator.refreshes(),
+            refreshes + OctColor::CLEAN_SEQUENCE.len() as u32
+        );
+        assert_eq!(buffer, display.buffer());
+        assert!(!epd.deep_clean_due());
+        assert_eq!(epd.epd().background_color(), &OctColor::White);
+    }
+
+    #[test]
+    fn double_buffered_flush() {
+        let simulator = Simulator::new(Controller::Uc81xx, epd4in2::WIDTH, epd4in2::HEIGHT);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        let epd = Epd4in2::new(
+            &mut spi,
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut delay,
+            None,
+        )
+        .unwrap();
+        let policy = RefreshPolicy {
+            max_changed_pixels: Some(16),
+            ..RefreshPolicy::UNLIMITED
+        };
+        let mut epd = RefreshScheduler::with_policy(epd, policy);
+        let mut display: DoubleBufferedDisplay<
+            { epd4in2::WIDTH },
+            { epd4in2::HEIGHT },
+            false,
+            { buffer_len(epd4in2::WIDTH as usize, epd4in2::HEIGHT as usize) },
+            Color,
+            0xFF,
+        > = DoubleBufferedDisplay::default();
+
+        // the first flush is a full one, even without changes
+        let (refresh, window) = epd
+            .flush(&mut display, &mut spi, &mut delay)
+            .unwrap()
+            .unwrap();
+        assert_eq!(refresh, RefreshLut::Full);
+        assert_eq!(window.w, epd4in2::WIDTH);
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+        assert_eq!(epd.flush(&mut display, &mut spi, &mut delay), Ok(None));
+
+        for (i, expected) in [RefreshLut::Quick, RefreshLut::Quick, RefreshLut::Full]
+            .into_iter()
+            .enumerate()
+        {
+            let point = Point::new(16 * i as i32 + 3, 5);
+            Pixel(point, Color::Black).draw(&mut display).unwrap();
+            let (refresh, _) = epd
+                .flush(&mut display, &mut spi, &mut delay)
+                .unwrap()
+                .unwrap();
+            assert_eq!(refresh, expected);
+            assert_eq!(simulator.last_refresh(), Some(Refresh::from(refresh)));
+            assert_eq!(
+                simulator.displayed().pixel(point.x as u32, point.y as u32),
+                Some(Rgb888::BLACK)
+            );
+        }
+        assert_eq!(epd.changed_pixels(), 0);
+        assert_eq!(simulator.writes_while_busy(), 0);
+    }
+
+    impl From<RefreshLut> for Refresh {
+        fn from(refresh: RefreshLut) -> Self {
+            match refresh {
+                RefreshLut::Full | RefreshLut::Fast => Refresh::Full,
+                RefreshLut::Quick => Refresh::Partial,
+            }
+        }
+    }
+}
@@ -0,0 +1,1101 @@
+//! Simulated panel to run the drivers without hardware, e.g. in unit tests or desktop previews
+//!
+//! Needs the `std` feature, which enables `graphics` as well.
+//!
+//! A [`Simulator`] emulates the RAM and the refresh of a black/white controller. Its [`Spi`],
+//! [`Busy`], [`Dc`] and [`Rst`] handles implement the embedded-hal traits, so the drivers run
+//! unmodified on top of it. The simulator keeps the image shown by the panel apart from the
+//! pending RAM content, and counts the busy polls and the partial refreshes each pixel went through
+//! since the last full refresh (where ghosting builds up on a real panel).
+//!
+//! Refreshes are partial in display mode 2 of SSD168x controllers, in the partial mode of UC81xx
+//! controllers, with the fast waveform of a forced temperature or with a LUT which doesn't flash
+//! the pixels keeping their color. They only drive the pixels which differ between the old and the
+//! new RAM.
+//!
+//! Writing while the controller is in deep sleep, or writing past the RAM window, panics.
+//!
+//!```rust, no_run
+//!use embedded_hal_mock::eh1::delay::NoopDelay;
+//!use epd_waveshare::{epd2in9::*, prelude::*, simulator::{Controller, Simulator}};
+//!
+//!let simulator = Simulator::new(Controller::Ssd168x, WIDTH, HEIGHT);
+//!let mut spi = simulator.spi();
+//!let mut delay = NoopDelay::new();
+//!let mut epd = Epd2in9::new(
+//!    &mut spi,
+//!    simulator.busy(),
+//!    simulator.dc(),
+//!    simulator.rst(),
+//!    &mut delay,
+//!    None,
+//!)
+//!.unwrap();
+//!
+//!let display = Display2in9::default();
+//!epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+//!    .unwrap();
+//!simulator.save("frame.png").unwrap();
+//!```
+
+use crate::color::Color;
+use crate::graphics::Bitmap;
+use crate::snapshot::Snapshot;
+use core::convert::Infallible;
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use embedded_hal::{
+    digital::{self, InputPin, OutputPin},
+    spi::{self, Operation, SpiDevice},
+};
+use std::{cell::RefCell, collections::BTreeMap, io, path::Path, rc::Rc, vec, vec::Vec};
+
+/// Command set of the simulated controller
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Controller {
+    /// SSD1608 and SSD168x controllers (e.g. epd1in54, epd2in9, epd2in9_v2), the RAM is written
+    /// through a window and address counters. The busy pin is high while busy.
+    Ssd168x,
+    /// SSD1677 controllers (e.g. epd3in7), a [`Controller::Ssd168x`] with the RAM x addresses in
+    /// pixels instead of bytes. The kind of refresh follows the LUT, as display mode 2 is used
+    /// for the full refreshes as well.
+    Ssd1677,
+    /// UC8151, IL0373, UC8176 and UC8179 controllers (e.g. epd2in9bc, epd4in2, epd7in5_v2), the
+    /// RAM is written frame by frame or into the partial window. The busy pin is low while busy.
+    Uc81xx,
+}
+
+/// Kind of a refresh of the panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Refresh {
+    /// All pixels are driven through the full waveform, which clears the ghosting
+    Full,
+    /// Only the changed pixels are driven with a quick waveform, which leaves ghosting behind
+    Partial,
+}
+
+/// Number of busy polls of the operations, i.e. how often the driver reads the busy pin before the
+/// controller is idle again
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusyPolls {
+    /// Full refresh
+    pub full: u32,
+    /// Partial refresh
+    pub partial: u32,
+    /// Other operations like power on/off, software reset or an update without refresh
+    pub other: u32,
+}
+
+impl Default for BusyPolls {
+    fn default() -> Self {
+        Self {
+            full: 8,
+            partial: 3,
+            other: 1,
+        }
+    }
+}
+
+/// Simulated controller and panel, see the [module documentation](self)
+#[derive(Clone)]
+pub struct Simulator {
+    state: Rc<RefCell<State>>,
+}
+
+impl Simulator {
+    /// Panel of `width` x `height` pixels with a `controller`, it starts white
+    pub fn new(controller: Controller, width: u32, height: u32) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State::new(controller, width, height))),
+        }
+    }
+
+    /// SPI device to pass to the driver
+    pub fn spi(&self) -> Spi {
+        Spi(self.state.clone())
+    }
+
+    /// Busy pin to pass to the driver
+    pub fn busy(&self) -> Busy {
+        Busy(self.state.clone())
+    }
+
+    /// Data/command pin to pass to the driver
+    pub fn dc(&self) -> Dc {
+        Dc(self.state.clone())
+    }
+
+    /// Reset pin to pass to the driver
+    pub fn rst(&self) -> Rst {
+        Rst(self.state.clone())
+    }
+
+    /// Change the duration of the operations
+    pub fn set_busy_polls(&self, polls: BusyPolls) {
+        self.state.borrow_mut().polls = polls;
+    }
+
+    /// Keep the old data of UC81xx controllers between the refreshes, like the controller does
+    ///
+    /// By default a refresh compares with the image on the panel when no old data (DTM1) was
+    /// written since the last refresh. When kept, it compares with the old data written last, or
+    /// with the new data of the last refresh if that was copied by N2OCP of the VCOM and data
+    /// interval setting. Drivers which rely on the old data being current are only checked then.
+    pub fn set_keep_old_data(&self, keep: bool) {
+        self.state.borrow_mut().keep_old_data = keep;
+    }
+
+    /// Take the first data transmission (DTM1) of UC81xx controllers with 2 bits per pixel, like
+    /// the black/white data of the IL0376F of epd1in54b. Each bit of the second RAM is the lower
+    /// bit of its pixel.
+    pub fn set_two_bit_old_data(&self, two_bits: bool) {
+        self.state.borrow_mut().two_bit_old_data = two_bits;
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> u32 {
+        self.state.borrow().width
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> u32 {
+        self.state.borrow().height
+    }
+
+    /// Image currently shown by the panel
+    pub fn displayed(&self) -> Snapshot {
+        let state = self.state.borrow();
+        state.snapshot(&state.displayed)
+    }
+
+    /// Image in the RAM, which is shown by the next refresh
+    pub fn ram(&self) -> Snapshot {
+        let state = self.state.borrow();
+        state.snapshot(&state.ram[0])
+    }
+
+    /// Image in the second RAM, the old frame of the quick refreshes or the chromatic plane of
+    /// tricolor panels (set bits are shown white)
+    pub fn second_ram(&self) -> Snapshot {
+        let state = self.state.borrow();
+        state.snapshot(&state.ram[1])
+    }
+
+    /// Write the image shown by the panel to a file, see [`Snapshot::save`]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.displayed().save(path)
+    }
+
+    /// Whether the controller is in deep sleep, it only wakes up by a reset
+    pub fn is_asleep(&self) -> bool {
+        self.state.borrow().asleep
+    }
+
+    /// Whether the controller is busy
+    pub fn is_busy(&self) -> bool {
+        self.state.borrow().busy > 0
+    }
+
+    /// Number of busy pin reads which returned busy
+    pub fn busy_polls(&self) -> u32 {
+        self.state.borrow().busy_polls
+    }
+
+    /// Number of bytes written while the controller was busy, a real controller might ignore them
+    pub fn writes_while_busy(&self) -> u32 {
+        self.state.borrow().writes_while_busy
+    }
+
+    /// Number of refreshes
+    pub fn refreshes(&self) -> u32 {
+        self.state.borrow().refreshes
+    }
+
+    /// Kind of the last refresh
+    pub fn last_refresh(&self) -> Option<Refresh> {
+        self.state.borrow().last_refresh
+    }
+
+    /// Waveform last written to the LUT register (the VCOM LUT for UC81xx), empty after a reset
+    pub fn lut(&self) -> Vec<u8> {
+        self.state.borrow().lut.clone()
+    }
+
+    /// Parameters last sent with `command`, `None` if it wasn't sent since the simulator was
+    /// created
+    pub fn register(&self, command: u8) -> Option<Vec<u8>> {
+        let state = self.state.borrow();
+        match state.command {
+            Some(current) if current == command => Some(state.params.clone()),
+            _ => state.registers.get(&command).cloned(),
+        }
+    }
+
+    /// Number of partial refreshes since the last full refresh
+    pub fn partial_refreshes(&self) -> u32 {
+        self.state.borrow().partial_refreshes
+    }
+
+    /// Number of partial refreshes which changed the pixel since the last full refresh, `None`
+    /// outside of the panel
+    pub fn ghosting(&self, x: u32, y: u32) -> Option<u16> {
+        let state = self.state.borrow();
+        if x >= state.width || y >= state.height {
+            return None;
+        }
+        Some(state.ghosting[(y * state.width + x) as usize])
+    }
+
+    /// Highest [`ghosting`](Self::ghosting) count of all pixels
+    pub fn max_ghosting(&self) -> u16 {
+        let state = self.state.borrow();
+        state.ghosting.iter().copied().max().unwrap_or(0)
+    }
+}
+
+/// SPI device of a [`Simulator`]
+pub struct Spi(Rc<RefCell<State>>);
+
+/// Busy pin of a [`Simulator`]
+pub struct Busy(Rc<RefCell<State>>);
+
+/// Data/command pin of a [`Simulator`]
+pub struct Dc(Rc<RefCell<State>>);
+
+/// Reset pin of a [`Simulator`]
+pub struct Rst(Rc<RefCell<State>>);
+
+impl spi::ErrorType for Spi {
+    type Error = Infallible;
+}
+
+impl SpiDevice for Spi {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        let mut state = self.0.borrow_mut();
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => data.iter().for_each(|&byte| state.write(byte)),
+                Operation::Transfer(read, write) => {
+                    write.iter().for_each(|&byte| state.write(byte));
+                    read.fill(0);
+                }
+                Operation::TransferInPlace(data) => {
+                    data.iter().for_each(|&byte| state.write(byte));
+                    data.fill(0);
+                }
+                Operation::Read(data) => data.fill(0),
+                Operation::DelayNs(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl digital::ErrorType for Busy {
+    type Error = Infallible;
+}
+
+impl InputPin for Busy {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        let mut state = self.0.borrow_mut();
+        let busy = state.poll();
+        // SSD168x signal busy with a high level, UC81xx with a low level
+        Ok(busy == (state.controller == Controller::Ssd168x))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+impl digital::ErrorType for Dc {
+    type Error = Infallible;
+}
+
+impl OutputPin for Dc {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().data = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().data = true;
+        Ok(())
+    }
+}
+
+impl digital::ErrorType for Rst {
+    type Error = Infallible;
+}
+
+impl OutputPin for Rst {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().in_reset = true;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        let mut state = self.0.borrow_mut();
+        if state.in_reset {
+            state.in_reset = false;
+            state.reset();
+        }
+        Ok(())
+    }
+}
+
+// Inclusive range of RAM columns (bytes) and rows
+#[derive(Clone, Copy, Debug)]
+struct Window {
+    x: (u32, u32),
+    y: (u32, u32),
+}
+
+impl Window {
+    fn contains(&self, x: u32, y: u32) -> bool {
+        let within = |v: u32, (a, b): (u32, u32)| a.min(b) <= v && v <= a.max(b);
+        within(x, self.x) && within(y, self.y)
+    }
+}
+
+struct State {
+    /// Command set, SSD1677 are simulated as SSD168x with `x_in_pixels`
+    controller: Controller,
+    /// RAM x addresses in pixels (SSD1677)
+    x_in_pixels: bool,
+    width: u32,
+    height: u32,
+    polls: BusyPolls,
+    /// Level of the data/command pin
+    data: bool,
+    in_reset: bool,
+    asleep: bool,
+    /// Remaining busy polls
+    busy: u32,
+    busy_polls: u32,
+    writes_while_busy: u32,
+    command: Option<u8>,
+    params: Vec<u8>,
+    /// Parameters of the previous commands
+    registers: BTreeMap<u8, Vec<u8>>,
+    /// New (black/white) and old (or red) RAM
+    ram: [Vec<u8>; 2],
+    /// UC81xx old data written since the last refresh, the controller compares with the image
+    /// of the last refresh otherwise
+    old_written: bool,
+    /// The old data is compared with even if it wasn't written since the last refresh
+    keep_old_data: bool,
+    /// DTM1 takes 2 bits per pixel, the first byte of each pair waits for the second one
+    two_bit_old_data: bool,
+    pending_old_byte: Option<u8>,
+    window: Window,
+    counter: (u32, u32),
+    /// The window has been completely written
+    overflow: bool,
+    data_entry_mode: u8,
+    update_control: u8,
+    lut: Vec<u8>,
+    /// UC81xx waveforms of the pixels staying white and staying black (LUTWW, LUTBB), used
+    /// instead of the OTP with REG_EN of the panel setting
+    unchanged_luts: [Vec<u8>; 2],
+    lut_from_register: bool,
+    partial_mode: bool,
+    /// UC81xx waveform chosen by a forced temperature (cascade setting TSFIX)
+    temperature_fixed: bool,
+    forced_temperature: u8,
+    displayed: Vec<u8>,
+    ghosting: Vec<u16>,
+    refreshes: u32,
+    partial_refreshes: u32,
+    last_refresh: Option<Refresh>,
+}
+
+impl State {
+    fn new(controller: Controller, width: u32, height: u32) -> Self {
+        let size = ((width + 7) / 8 * height) as usize;
+        let mut state = Self {
+            controller: match controller {
+                Controller::Ssd1677 => Controller::Ssd168x,
+                controller => controller,
+            },
+            x_in_pixels: controller == Controller::Ssd1677,
+            width,
+            height,
+            polls: BusyPolls::default(),
+            data: false,
+            in_reset: false,
+            asleep: false,
+            busy: 0,
+            busy_polls: 0,
+            writes_while_busy: 0,
+            command: None,
+            params: Vec::new(),
+            registers: BTreeMap::new(),
+            ram: [vec![0xFF; size], vec![0xFF; size]],
+            old_written: false,
+            keep_old_data: false,
+            two_bit_old_data: false,
+            pending_old_byte: None,
+            window: Window {
+                x: (0, 0),
+                y: (0, 0),
+            },
+            counter: (0, 0),
+            overflow: false,
+            data_entry_mode: 0,
+            update_control: 0,
+            lut: Vec::new(),
+            unchanged_luts: [Vec::new(), Vec::new()],
+            lut_from_register: false,
+            partial_mode: false,
+            temperature_fixed: false,
+            forced_temperature: 0,
+            displayed: vec![0xFF; size],
+            ghosting: vec![0; (width * height) as usize],
+            refreshes: 0,
+            partial_refreshes: 0,
+            last_refresh: None,
+        };
+        state.reset();
+        state
+    }
+
+    fn row_bytes(&self) -> u32 {
+        (self.width + 7) / 8
+    }
+
+    fn full_window(&self) -> Window {
+        Window {
+            x: (0, self.row_bytes() - 1),
+            y: (0, self.height - 1),
+        }
+    }
+
+    // Window the RAM is written through, UC81xx only use their partial window in partial mode
+    fn ram_window(&self) -> Window {
+        match self.controller {
+            Controller::Uc81xx if !self.partial_mode => self.full_window(),
+            _ => self.window,
+        }
+    }
+
+    // Registers after a hardware or software reset, the RAM and the panel keep their content
+    fn reset(&mut self) {
+        self.asleep = false;
+        if let Some(previous) = self.command.take() {
+            self.registers
+                .insert(previous, core::mem::take(&mut self.params));
+        }
+        self.window = self.full_window();
+        self.counter = (0, 0);
+        self.overflow = false;
+        self.data_entry_mode = 0x03;
+        self.update_control = 0;
+        self.lut.clear();
+        self.unchanged_luts.iter_mut().for_each(Vec::clear);
+        self.lut_from_register = false;
+        self.partial_mode = false;
+        self.temperature_fixed = false;
+        self.forced_temperature = 0;
+    }
+
+    fn poll(&mut self) -> bool {
+        if self.busy > 0 {
+            self.busy -= 1;
+            self.busy_polls += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn write(&mut self, byte: u8) {
+        if self.asleep {
+            panic!(
+                "{} {:#04x} written to the {:?} controller in deep sleep",
+                if self.data { "data" } else { "command" },
+                byte,
+                self.controller
+            );
+        }
+        if self.busy > 0 && (self.data || !self.is_status_command(byte)) {
+            self.writes_while_busy += 1;
+        }
+        if self.data {
+            self.data(byte);
+        } else {
+            self.command(byte);
+        }
+    }
+
+    // Commands which can be sent while the controller is busy
+    fn is_status_command(&self, command: u8) -> bool {
+        match self.controller {
+            // NOP
+            Controller::Ssd168x | Controller::Ssd1677 => command == 0xFF,
+            // get status
+            Controller::Uc81xx => command == 0x71,
+        }
+    }
+
+    fn command(&mut self, command: u8) {
+        if let Some(previous) = self.command.replace(command) {
+            self.registers
+                .insert(previous, core::mem::take(&mut self.params));
+        }
+        self.params.clear();
+        self.pending_old_byte = None;
+        match (self.controller, command) {
+            // write RAM (black/white), write RAM (red)
+            (Controller::Ssd168x, 0x24) | (Controller::Ssd168x, 0x26) => self.overflow = false,
+            // write LUT register, VCOM LUT
+            (Controller::Ssd168x, 0x32) | (Controller::Uc81xx, 0x20) => self.lut.clear(),
+            // white to white LUT, black to black LUT
+            (Controller::Uc81xx, 0x21) => self.unchanged_luts[0].clear(),
+            (Controller::Uc81xx, 0x24) => self.unchanged_luts[1].clear(),
+            // software reset
+            (Controller::Ssd168x, 0x12) => {
+                self.reset();
+                self.busy = self.polls.other;
+            }
+            // master activation
+            (Controller::Ssd168x, 0x20) => {
+                // the display step of the update sequence (display mode 2 is the partial one)
+                // the load LUT step replaces the LUT register by the waveform of the OTP
+                if self.update_control & 0x10 != 0 {
+                    self.lut.clear();
+                }
+                if self.update_control & 0x04 != 0 {
+                    let display_mode_2 = self.update_control & 0x08 != 0;
+                    let partial = if self.x_in_pixels {
+                        !ssd1677_lut_flashes(&self.lut)
+                    } else {
+                        display_mode_2 || !ssd1608_lut_flashes(&self.lut)
+                    };
+                    let refresh = if partial {
+                        Refresh::Partial
+                    } else {
+                        Refresh::Full
+                    };
+                    // display mode 2 compares with the old RAM, which is updated afterwards.
+                    // Otherwise the transitions start from the image on the panel.
+                    let old = if display_mode_2 {
+                        self.ram[1].clone()
+                    } else {
+                        self.displayed.clone()
+                    };
+                    self.refresh(refresh, self.full_window(), &old);
+                    if display_mode_2 {
+                        self.ram[1] = self.ram[0].clone();
+                    }
+                } else {
+                    self.busy = self.polls.other;
+                }
+            }
+            // data start transmission 1 (old) and 2 (new)
+            (Controller::Uc81xx, 0x10) | (Controller::Uc81xx, 0x13) => {
+                let window = self.ram_window();
+                self.counter = (window.x.0, window.y.0);
+                self.overflow = false;
+            }
+            // power off, power on
+            (Controller::Uc81xx, 0x02) | (Controller::Uc81xx, 0x04) => {
+                self.busy = self.polls.other;
+            }
+            // display refresh
+            (Controller::Uc81xx, 0x12) => {
+                // the waveforms for forced temperatures of 100 degrees and more are the fast ones
+                let fast_waveform =
+                    self.temperature_fixed && (100..=127).contains(&self.forced_temperature);
+                let quick_lut = self.lut_from_register
+                    && !self
+                        .unchanged_luts
+                        .iter()
+                        .all(|lut| uc81xx_lut_flashes(lut));
+                let refresh = if self.partial_mode || fast_waveform || quick_lut {
+                    Refresh::Partial
+                } else {
+                    Refresh::Full
+                };
+                // the LUTs are chosen by the old (DTM1) and the new (DTM2) data of each pixel
+                let old = if self.old_written || self.keep_old_data {
+                    self.ram[1].clone()
+                } else {
+                    self.displayed.clone()
+                };
+                self.old_written = false;
+                self.refresh(refresh, self.ram_window(), &old);
+                // N2OCP copies the new data to the old data
+                let interval = self.registers.get(&0x50).and_then(|params| params.first());
+                if interval.map_or(false, |byte| byte & 0x08 != 0) {
+                    self.ram[1] = self.ram[0].clone();
+                }
+            }
+            // partial in, partial out
+            (Controller::Uc81xx, 0x91) => self.partial_mode = true,
+            (Controller::Uc81xx, 0x92) => self.partial_mode = false,
+            _ => {}
+        }
+    }
+
+    fn data(&mut self, byte: u8) {
+        let command = match self.command {
+            Some(command) => command,
+            None => return,
+        };
+        self.params.push(byte);
+        let index = self.params.len() - 1;
+        let word = |low: u8, high: u8| u32::from(low) | u32::from(high & 0x03) << 8;
+        match (self.controller, command) {
+            // deep sleep
+            (Controller::Ssd168x, 0x10) => self.asleep = byte & 0x03 != 0,
+            // data entry mode
+            (Controller::Ssd168x, 0x11) => self.data_entry_mode = byte & 0x07,
+            // display update control 2
+            (Controller::Ssd168x, 0x22) => self.update_control = byte,
+            // write RAM (black/white), write RAM (red)
+            (Controller::Ssd168x, 0x24) => self.write_ram(0, byte),
+            (Controller::Ssd168x, 0x26) => self.write_ram(1, byte),
+            // write LUT register
+            (Controller::Ssd168x, 0x32) => self.lut.push(byte),
+            // RAM x start/end and counter in pixels, the columns are aligned to bytes
+            (Controller::Ssd168x, 0x44) if self.x_in_pixels => {
+                match index {
+                    1 => self.window.x.0 = word(self.params[0], byte) / 8,
+                    3 => self.window.x.1 = word(self.params[2], byte) / 8,
+                    _ => {}
+                }
+                self.overflow = false;
+            }
+            (Controller::Ssd168x, 0x4E) if self.x_in_pixels => {
+                if index == 1 {
+                    self.counter.0 = word(self.params[0], byte) / 8;
+                }
+                self.overflow = false;
+            }
+            // RAM x start/end
+            (Controller::Ssd168x, 0x44) => {
+                match index {
+                    0 => self.window.x.0 = u32::from(byte),
+                    1 => self.window.x.1 = u32::from(byte),
+                    _ => {}
+                }
+                self.overflow = false;
+            }
+            // RAM y start/end
+            (Controller::Ssd168x, 0x45) => {
+                match index {
+                    1 => self.window.y.0 = word(self.params[0], byte),
+                    3 => self.window.y.1 = word(self.params[2], byte),
+                    _ => {}
+                }
+                self.overflow = false;
+            }
+            // RAM x counter
+            (Controller::Ssd168x, 0x4E) => {
+                self.counter.0 = u32::from(byte);
+                self.overflow = false;
+            }
+            // RAM y counter
+            (Controller::Ssd168x, 0x4F) => {
+                self.counter.1 = match index {
+                    0 => u32::from(byte),
+                    _ => word(self.params[0], byte),
+                };
+                self.overflow = false;
+            }
+            // deep sleep with its check code
+            (Controller::Uc81xx, 0x07) => self.asleep = byte == 0xA5,
+            // data start transmission 1 (old) and 2 (new)
+            (Controller::Uc81xx, 0x10) if self.two_bit_old_data => {
+                match self.pending_old_byte.take() {
+                    Some(high) => self.write_ram(1, lower_bits(high, byte)),
+                    None => self.pending_old_byte = Some(byte),
+                }
+                self.old_written = true;
+            }
+            (Controller::Uc81xx, 0x10) => {
+                self.write_ram(1, byte);
+                self.old_written = true;
+            }
+            (Controller::Uc81xx, 0x13) => self.write_ram(0, byte),
+            // panel setting, REG_EN takes the LUTs from the registers instead of the OTP
+            (Controller::Uc81xx, 0x00) if index == 0 => self.lut_from_register = byte & 0x20 != 0,
+            // VCOM LUT, white to white LUT, black to black LUT
+            (Controller::Uc81xx, 0x20) => self.lut.push(byte),
+            (Controller::Uc81xx, 0x21) => self.unchanged_luts[0].push(byte),
+            (Controller::Uc81xx, 0x24) => self.unchanged_luts[1].push(byte),
+            // cascade setting, TSFIX selects the waveform of the forced temperature
+            (Controller::Uc81xx, 0xE0) => self.temperature_fixed = byte & 0x02 != 0,
+            (Controller::Uc81xx, 0xE5) => self.forced_temperature = byte,
+            // partial window in pixels, the columns are aligned to bytes. The controllers of
+            // panels up to 256 pixels wide (UC8151, IL0373) take a single byte per column.
+            (Controller::Uc81xx, 0x90) if index == 5 && self.width <= 256 => {
+                let p = &self.params;
+                self.window = Window {
+                    x: (u32::from(p[0]) / 8, u32::from(p[1]) / 8),
+                    y: (word(p[3], p[2]), word(p[5], p[4])),
+                };
+            }
+            (Controller::Uc81xx, 0x90) if index == 7 && self.width > 256 => {
+                let p = &self.params;
+                self.window = Window {
+                    x: (word(p[1], p[0]) / 8, word(p[3], p[2]) / 8),
+                    y: (word(p[5], p[4]), word(p[7], p[6])),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn write_ram(&mut self, plane: usize, byte: u8) {
+        let (x, y) = self.counter;
+        let window = self.ram_window();
+        if self.overflow || !window.contains(x, y) {
+            panic!(
+                "RAM write at column {} row {} outside of the RAM window {:?}",
+                x, y, window
+            );
+        }
+        if x >= self.row_bytes() || y >= self.height {
+            panic!(
+                "RAM write at column {} row {} outside of the RAM of {} x {} pixels",
+                x, y, self.width, self.height
+            );
+        }
+        let index = (y * self.row_bytes() + x) as usize;
+        self.ram[plane][index] = byte;
+
+        // UC81xx always write line by line
+        let mode = match self.controller {
+            Controller::Ssd168x | Controller::Ssd1677 => self.data_entry_mode,
+            Controller::Uc81xx => 0x03,
+        };
+        let (x_increment, y_increment) = (mode & 0x01 != 0, mode & 0x02 != 0);
+        let (mut x, mut y) = (x, y);
+        let mut x_axis = (&mut x, window.x, x_increment);
+        let mut y_axis = (&mut y, window.y, y_increment);
+        // the address counter is updated in x direction, or in y direction with AM set
+        let (inner, outer) = if mode & 0x04 == 0 {
+            (&mut x_axis, &mut y_axis)
+        } else {
+            (&mut y_axis, &mut x_axis)
+        };
+        let wrapped = step(inner.0, inner.1, inner.2) && step(outer.0, outer.1, outer.2);
+        self.counter = (x, y);
+        self.overflow = wrapped;
+    }
+
+    // A full refresh drives all pixels to the new RAM. A partial refresh only drives the pixels
+    // which differ between the `old` image and the new RAM, the others keep what the panel shows.
+    fn refresh(&mut self, refresh: Refresh, window: Window, old: &[u8]) {
+        let row = self.row_bytes();
+        for y in window.y.0.min(window.y.1)..=window.y.0.max(window.y.1).min(self.height - 1) {
+            for x in window.x.0.min(window.x.1)..=window.x.0.max(window.x.1).min(row - 1) {
+                let index = (y * row + x) as usize;
+                let new = self.ram[0][index];
+                let driven = match refresh {
+                    Refresh::Full => 0xFF,
+                    Refresh::Partial => old[index] ^ new,
+                };
+                let changed = (self.displayed[index] ^ new) & driven;
+                for bit in 0..8 {
+                    let pixel = x * 8 + bit;
+                    if pixel >= self.width {
+                        break;
+                    }
+                    let ghosting = &mut self.ghosting[(y * self.width + pixel) as usize];
+                    match refresh {
+                        Refresh::Full => *ghosting = 0,
+                        Refresh::Partial if changed & (0x80 >> bit) != 0 => *ghosting += 1,
+                        Refresh::Partial => {}
+                    }
+                }
+                self.displayed[index] = self.displayed[index] & !driven | new & driven;
+            }
+        }
+        self.refreshes += 1;
+        self.last_refresh = Some(refresh);
+        match refresh {
+            Refresh::Full => {
+                self.partial_refreshes = 0;
+                self.busy = self.polls.full;
+            }
+            Refresh::Partial => {
+                self.partial_refreshes += 1;
+                self.busy = self.polls.partial;
+            }
+        }
+    }
+
+    fn snapshot(&self, buffer: &[u8]) -> Snapshot {
+        let bitmap = Bitmap::<Color>::new(buffer, self.width, self.height)
+            .expect("the RAM has the size of the panel");
+        Snapshot::from_bitmap(&bitmap, false, Rgb888::RED)
+    }
+}
+
+// Whether a waveform of the LUT register of SSD1608 controllers drives the pixels which keep
+// their color to both voltages, which clears the ghosting. The first 20 bytes select the voltages
+// of the phases, from the highest bits for the transitions 0 to 0, 0 to 1, 1 to 0 and 1 to 1.
+// Empty LUTs (the waveform of the OTP) and the LUTs of other controllers are full waveforms, their
+// partial refreshes use display mode 2.
+fn ssd1608_lut_flashes(lut: &[u8]) -> bool {
+    if lut.len() != 30 {
+        return true;
+    }
+    let unchanged = |shift: u32| lut[..20].iter().map(move |byte| byte >> shift & 0x03);
+    [6, 0].iter().all(|&shift| {
+        unchanged(shift).any(|voltage| voltage == 0x01)
+            && unchanged(shift).any(|voltage| voltage == 0x02)
+    })
+}
+
+// Whether a SSD1677 LUT drives the pixels which keep their color to both voltages. The first 50
+// bytes are the voltage selects of the transitions 0 to 0, 0 to 1, 1 to 0 and 1 to 1 (and a fifth
+// unused one), 10 bytes each with 4 phases from the highest bits. Empty LUTs (the waveform of the
+// OTP) are full waveforms.
+fn ssd1677_lut_flashes(lut: &[u8]) -> bool {
+    if lut.len() < 50 {
+        return true;
+    }
+    let unchanged = |start: usize| {
+        lut[start..start + 10]
+            .iter()
+            .flat_map(|byte| (0..4).map(move |phase| byte >> (6 - 2 * phase) & 0x03))
+    };
+    [0, 30].iter().all(|&start| {
+        unchanged(start).any(|voltage| voltage == 0x01)
+            && unchanged(start).any(|voltage| voltage == 0x02)
+    })
+}
+
+// Whether a UC8176 LUT drives its pixels to both VDH and VDL, which clears the ghosting. Each
+// group of 6 bytes starts with the level selects of its 4 phases from the highest bits.
+fn uc81xx_lut_flashes(lut: &[u8]) -> bool {
+    let levels = || {
+        lut.chunks(6)
+            .flat_map(|group| (0..4).map(move |phase| group[0] >> (6 - 2 * phase) & 0x03))
+    };
+    lut.is_empty() || levels().any(|level| level == 0x01) && levels().any(|level| level == 0x02)
+}
+
+// Lower bits of the 8 pixels of two bytes with 2 bits per pixel
+fn lower_bits(high: u8, low: u8) -> u8 {
+    let pack = |byte: u8| (0..4).fold(0, |bits, pixel| bits << 1 | byte >> (6 - 2 * pixel) & 0x01);
+    pack(high) << 4 | pack(low)
+}
+
+// Moves `value` one step through the inclusive `range`, returns true if it wrapped around
+fn step(value: &mut u32, (start, end): (u32, u32), increment: bool) -> bool {
+    let (low, high) = (start.min(end), start.max(end));
+    match (increment, *value) {
+        (true, v) if v >= high => {
+            *value = low;
+            true
+        }
+        (false, v) if v <= low => {
+            *value = high;
+            true
+        }
+        (true, _) => {
+            *value += 1;
+            false
+        }
+        (false, _) => {
+            *value -= 1;
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::epd2in9::{self, Display2in9, Epd2in9};
+    use crate::epd4in2::{self, Display4in2, Epd4in2};
+    use crate::prelude::*;
+    use embedded_graphics::{
+        prelude::*,
+        primitives::{PrimitiveStyle, Rectangle},
+    };
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+
+    fn epd2in9(simulator: &Simulator) -> Epd2in9<Spi, Busy, Dc, Rst, NoopDelay> {
+        Epd2in9::new(
+            &mut simulator.spi(),
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut NoopDelay::new(),
+            None,
+        )
+        .unwrap()
+    }
+
+    fn epd4in2(simulator: &Simulator) -> Epd4in2<Spi, Busy, Dc, Rst, NoopDelay> {
+        Epd4in2::new(
+            &mut simulator.spi(),
+            simulator.busy(),
+            simulator.dc(),
+            simulator.rst(),
+            &mut NoopDelay::new(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn ssd168x_full_and_partial_refresh() {
+        let simulator = Simulator::new(Controller::Ssd168x, epd2in9::WIDTH, epd2in9::HEIGHT);
+        let mut epd = epd2in9(&simulator);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+
+        let mut display = Display2in9::default();
+        Rectangle::new(Point::new(8, 10), Size::new(16, 4))
+            .into_styled(PrimitiveStyle::with_fill(Color::Black))
+            .draw(&mut display)
+            .unwrap();
+        epd.update_frame(&mut spi, display.buffer(), &mut delay)
+            .unwrap();
+        assert_eq!(simulator.ram().pixel(8, 10), Some(Rgb888::BLACK));
+        assert_eq!(simulator.displayed().pixel(8, 10), Some(Rgb888::WHITE));
+
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.displayed(), Snapshot::from_display(&display));
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+        assert!(simulator.is_busy());
+
+        // the quick LUT makes the next refreshes partial
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))
+            .unwrap();
+        assert!(!simulator.is_busy());
+        assert_eq!(simulator.busy_polls(), BusyPolls::default().full);
+        for x in [30, 40] {
+            Pixel(Point::new(x, 0), Color::Black)
+                .draw(&mut display)
+                .unwrap();
+            epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+                .unwrap();
+        }
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+        assert_eq!(simulator.refreshes(), 3);
+        assert_eq!(simulator.partial_refreshes(), 2);
+        assert_eq!(simulator.ghosting(30, 0), Some(1));
+        assert_eq!(simulator.ghosting(8, 10), Some(0));
+        assert_eq!(simulator.max_ghosting(), 1);
+        assert_eq!(simulator.displayed().pixel(40, 0), Some(Rgb888::BLACK));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.partial_refreshes(), 0);
+        assert_eq!(simulator.max_ghosting(), 0);
+        assert_eq!(simulator.writes_while_busy(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the RAM window")]
+    fn ssd168x_write_past_window() {
+        let simulator = Simulator::new(Controller::Ssd168x, epd2in9::WIDTH, epd2in9::HEIGHT);
+        let mut epd = epd2in9(&simulator);
+        let buffer = [0xFF; epd2in9::WIDTH as usize / 8 * epd2in9::HEIGHT as usize + 1];
+        let _ = epd.update_frame(&mut simulator.spi(), &buffer, &mut NoopDelay::new());
+    }
+
+    #[test]
+    fn uc81xx_refresh_and_sleep() {
+        let simulator = Simulator::new(Controller::Uc81xx, epd4in2::WIDTH, epd4in2::HEIGHT);
+        let mut epd = epd4in2(&simulator);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+
+        let mut display = Display4in2::default();
+        Rectangle::new(Point::new(100, 50), Size::new(50, 20))
+            .into_styled(PrimitiveStyle::with_fill(Color::Black))
+            .draw(&mut display)
+            .unwrap();
+        epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+            .unwrap();
+        assert_eq!(simulator.displayed(), Snapshot::from_display(&display));
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+
+        // the partial window is written into the RAM only
+        epd.update_partial_frame(&mut spi, &mut delay, &[0x00; 2 * 3], 16, 8, 16, 3)
+            .unwrap();
+        assert_eq!(simulator.ram().pixel(16, 8), Some(Rgb888::BLACK));
+        assert_eq!(simulator.ram().pixel(31, 10), Some(Rgb888::BLACK));
+        assert_eq!(simulator.ram().pixel(32, 10), Some(Rgb888::WHITE));
+        assert_eq!(simulator.ram().pixel(16, 11), Some(Rgb888::WHITE));
+        assert_eq!(simulator.displayed().pixel(16, 8), Some(Rgb888::WHITE));
+
+        epd.sleep(&mut spi, &mut delay).unwrap();
+        assert!(simulator.is_asleep());
+        assert_eq!(simulator.writes_while_busy(), 0);
+
+        epd.wake_up(&mut spi, &mut delay).unwrap();
+        assert!(!simulator.is_asleep());
+    }
+
+    #[test]
+    fn uc81xx_quick_lut_drives_old_to_new() {
+        let simulator = Simulator::new(Controller::Uc81xx, epd4in2::WIDTH, epd4in2::HEIGHT);
+        let mut epd = epd4in2(&simulator);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+
+        let blank = Display4in2::default();
+        epd.update_and_display_frame(&mut spi, blank.buffer(), &mut delay)
+            .unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+
+        // the quick LUTs don't flash the unchanged pixels
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))
+            .unwrap();
+        let mut display = Display4in2::default();
+        Rectangle::new(Point::new(8, 4), Size::new(8, 2))
+            .into_styled(PrimitiveStyle::with_fill(Color::Black))
+            .draw(&mut display)
+            .unwrap();
+        epd.update_old_frame(&mut spi, blank.buffer(), &mut delay)
+            .unwrap();
+        epd.update_and_display_new_frame(&mut spi, display.buffer(), &mut delay)
+            .unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Partial));
+        assert_eq!(simulator.displayed(), Snapshot::from_display(&display));
+        assert_eq!(simulator.ghosting(8, 4), Some(1));
+        assert_eq!(simulator.max_ghosting(), 1);
+
+        // an old frame which doesn't match the panel leaves the pixels as they are
+        epd.update_old_frame(&mut spi, blank.buffer(), &mut delay)
+            .unwrap();
+        epd.update_and_display_new_frame(&mut spi, blank.buffer(), &mut delay)
+            .unwrap();
+        assert_eq!(simulator.displayed().pixel(8, 4), Some(Rgb888::BLACK));
+
+        epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Full))
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(simulator.last_refresh(), Some(Refresh::Full));
+        assert_eq!(simulator.displayed(), Snapshot::from_display(&blank));
+        assert_eq!(simulator.max_ghosting(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "deep sleep")]
+    fn uc81xx_write_while_asleep() {
+        let simulator = Simulator::new(Controller::Uc81xx, epd4in2::WIDTH, epd4in2::HEIGHT);
+        let mut epd = epd4in2(&simulator);
+        let (mut spi, mut delay) = (simulator.spi(), NoopDelay::new());
+        epd.sleep(&mut spi, &mut delay).unwrap();
+        let _ = epd.clear_frame(&mut spi, &mut delay);
+    }
+
+    #[test]
+    fn window_steps() {
+        let mut value = 3;
+        assert!(!step(&mut value, (2, 4), true));
+        assert_eq!(value, 4);
+        assert!(step(&mut value, (2, 4), true));
+        assert_eq!(value, 2);
+        assert!(step(&mut value, (4, 2), false));
+        assert_eq!(value, 4);
+    }
+}
@@ -0,0 +1,562 @@
+//! Export of display buffers to image files, mainly to check rendered screens without hardware
+//!
+//! Needs the `std` feature, which enables `graphics` as well.
+//!
+//! A [`Snapshot`] is an RGB copy of a display buffer, as it is sent to the device (i.e. without
+//! the rotation of the drawing). It can be written as PBM, PPM or PNG, and compared with a golden
+//! file with [`Snapshot::check_golden`].
+//!
+//!```rust, no_run
+//!use epd_waveshare::{epd2in9_v2::*, prelude::*, snapshot::Snapshot};
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!
+//!let mut display = Display2in9::default();
+//!let _ = Line::new(Point::new(0, 0), Point::new(127, 295))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!
+//!Snapshot::from_display(&display)
+//!    .check_golden("tests/golden/line.pbm")
+//!    .unwrap();
+//!```
+
+use crate::color::{ColorType, TriColor};
+use crate::graphics::{get_raw, Bitmap, Display, VarDisplay};
+use crate::image::{self, Dither, ImageError};
+use core::convert::Infallible;
+use embedded_graphics_core::{pixelcolor::Rgb888, prelude::*};
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    vec,
+    vec::Vec,
+};
+
+/// Environment variable which makes [`Snapshot::check_golden`] (re)write the golden files
+/// instead of comparing them
+pub const UPDATE_GOLDEN: &str = "EPD_UPDATE_GOLDEN";
+
+/// RGB image of a display buffer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgb888>,
+}
+
+impl Snapshot {
+    /// White image of `width` x `height` pixels
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Rgb888::WHITE; width as usize * height as usize],
+        }
+    }
+
+    /// Image of the buffer of a display, the chromatic pixels of tricolor displays show the
+    /// CHROMATIC ink of the display
+    pub fn from_display<
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        COLOR: ColorType + PixelColor,
+        const BACKGROUND: u16,
+        const INVERTED: bool,
+        const CHROMATIC: u32,
+    >(
+        display: &Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR, BACKGROUND, INVERTED, CHROMATIC>,
+    ) -> Self {
+        let (r, g, b) = TriColor::ink_rgb(CHROMATIC);
+        Self::from_bitmap(&display.bitmap(), INVERTED, Rgb888::new(r, g, b))
+    }
+
+    /// Image of the buffer of a display, the chromatic color of tricolor displays is red
+    pub fn from_var_display<COLOR: ColorType + PixelColor>(
+        display: &VarDisplay<'_, COLOR>,
+    ) -> Self {
+        Self::from_bitmap(&display.bitmap(), false, Rgb888::RED)
+    }
+
+    /// Image of a buffer
+    ///
+    /// `inverted` is the polarity of the black/white bits (see [`Display`]) and `chromatic` the
+    /// color used for the chromatic pixels of tricolor buffers, e.g. red or yellow.
+    pub fn from_bitmap<COLOR: ColorType>(
+        bitmap: &Bitmap<'_, COLOR>,
+        inverted: bool,
+        chromatic: Rgb888,
+    ) -> Self {
+        let (width, height) = (bitmap.width(), bitmap.height());
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let raw = get_raw::<COLOR>(bitmap.buffer(), width, x, y);
+                pixels.push(raw_to_rgb::<COLOR>(raw, inverted, chromatic));
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Read an image file in one of the formats of [`image`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GoldenError> {
+        let data = fs::read(path)?;
+        // the first pass only gets the size
+        let size = image::draw(
+            data.as_slice(),
+            &mut Snapshot::new(0, 0),
+            Point::zero(),
+            Dither::None,
+        )?;
+        let mut snapshot = Snapshot::new(size.width, size.height);
+        image::draw(data.as_slice(), &mut snapshot, Point::zero(), Dither::None)?;
+        Ok(snapshot)
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Color of a pixel, `None` outside of the image
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Rgb888> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[(y * self.width + x) as usize])
+    }
+
+    /// Write as a raw PBM (P4), pixels darker than mid gray are black
+    pub fn write_pbm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P4\n{} {}\n", self.width, self.height)?;
+        let mut line = vec![0u8; (self.width as usize + 7) / 8];
+        for row in self.rows() {
+            line.fill(0);
+            for (x, color) in row.iter().enumerate() {
+                if luma(*color) < 128 {
+                    line[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+            writer.write_all(&line)?;
+        }
+        Ok(())
+    }
+
+    /// Write as a raw PPM (P6)
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for color in &self.pixels {
+            writer.write_all(&[color.r(), color.g(), color.b()])?;
+        }
+        Ok(())
+    }
+
+    /// Write as an uncompressed 8 bit RGB PNG
+    pub fn write_png<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut raw = Vec::with_capacity(self.pixels.len() * 3 + self.height as usize);
+        for row in self.rows() {
+            // filter type: none
+            raw.push(0);
+            for color in row {
+                raw.extend_from_slice(&[color.r(), color.g(), color.b()]);
+            }
+        }
+
+        // zlib stream made of stored deflate blocks
+        let mut zlib = vec![0x78, 0x01];
+        let mut blocks = raw.chunks(0xFFFF).peekable();
+        if blocks.peek().is_none() {
+            zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+        }
+        while let Some(block) = blocks.next() {
+            let len = block.len() as u16;
+            zlib.push(blocks.peek().is_none() as u8);
+            zlib.extend_from_slice(&len.to_le_bytes());
+            zlib.extend_from_slice(&(!len).to_le_bytes());
+            zlib.extend_from_slice(block);
+        }
+        zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8 bits per channel, RGB, deflate, no filter, no interlace
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+        write_chunk(&mut writer, b"IHDR", &header)?;
+        write_chunk(&mut writer, b"IDAT", &zlib)?;
+        write_chunk(&mut writer, b"IEND", &[])
+    }
+
+    /// Write to a file, the format is chosen by the extension: `pbm`, `ppm` or `png`
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let writer = io::BufWriter::new(fs::File::create(path)?);
+        match extension {
+            Some("pbm") => self.write_pbm(writer),
+            Some("ppm") => self.write_ppm(writer),
+            Some("png") => self.write_png(writer),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unknown image extension, use pbm, ppm or png",
+            )),
+        }
+    }
+
+    /// Compare with another image, returns the number of differing pixels and an image of the
+    /// differences: matching pixels are faded and differing ones are magenta.
+    ///
+    /// Pixels which are only in one of the images count as differences.
+    pub fn diff(&self, other: &Snapshot) -> (usize, Snapshot) {
+        let mut diff = Snapshot::new(self.width.max(other.width), self.height.max(other.height));
+        let mut count = 0;
+        for y in 0..diff.height {
+            for x in 0..diff.width {
+                let color = match (self.pixel(x, y), other.pixel(x, y)) {
+                    (Some(a), Some(b)) if a == b => {
+                        let fade = |value: u8| 191 + value / 4;
+                        Rgb888::new(fade(a.r()), fade(a.g()), fade(a.b()))
+                    }
+                    _ => {
+                        count += 1;
+                        Rgb888::MAGENTA
+                    }
+                };
+                diff.pixels[(y * diff.width + x) as usize] = color;
+            }
+        }
+        (count, diff)
+    }
+
+    /// Compare with a golden image file
+    ///
+    /// The golden file can be in any format of [`image`], e.g. PBM for black and white displays
+    /// and PPM for the others. On a mismatch, this image and a diff image (see [`Snapshot::diff`])
+    /// are written next to the golden file as `<name>.actual.ppm` and `<name>.diff.png`.
+    ///
+    /// When the environment variable [`UPDATE_GOLDEN`] is set, the golden file is written
+    /// instead, in the format given by its extension (`pbm`, `ppm` or `png`).
+    pub fn check_golden(&self, path: impl AsRef<Path>) -> Result<(), GoldenError> {
+        let path = path.as_ref();
+        if std::env::var_os(UPDATE_GOLDEN).is_some() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            self.save(path)?;
+            return Ok(());
+        }
+
+        let actual = sibling(path, "actual.ppm");
+        if !path.exists() {
+            self.save(&actual)?;
+            return Err(GoldenError::Missing { actual });
+        }
+        let golden = Snapshot::load(path)?;
+        let (pixels, diff_image) = self.diff(&golden);
+        if pixels == 0 {
+            return Ok(());
+        }
+        let diff = sibling(path, "diff.png");
+        self.save(&actual)?;
+        diff_image.save(&diff)?;
+        Err(GoldenError::Mismatch {
+            pixels,
+            actual,
+            diff,
+        })
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Rgb888]> {
+        // `max` avoids a panic for an empty image
+        self.pixels.chunks(self.width.max(1) as usize)
+    }
+}
+
+/// For loading images with [`image::draw`]
+impl DrawTarget for Snapshot {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if point.x >= 0
+                && point.y >= 0
+                && (point.x as u32) < self.width
+                && (point.y as u32) < self.height
+            {
+                self.pixels[(point.y as u32 * self.width + point.x as u32) as usize] = color;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Snapshot {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+/// Error found by [`Snapshot::check_golden`]
+#[derive(Debug)]
+pub enum GoldenError {
+    /// Reading or writing a file failed
+    Io(io::Error),
+    /// The golden file could not be decoded
+    Image(ImageError<Infallible>),
+    /// There is no golden file, the image has been written to `actual`
+    Missing {
+        /// Path of the image
+        actual: PathBuf,
+    },
+    /// The image doesn't match the golden file
+    Mismatch {
+        /// Number of differing pixels
+        pixels: usize,
+        /// Path of the image
+        actual: PathBuf,
+        /// Path of the diff image
+        diff: PathBuf,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoldenError::Io(error) => write!(f, "golden file: {}", error),
+            GoldenError::Image(error) => write!(f, "golden file can't be decoded: {:?}", error),
+            GoldenError::Missing { actual } => write!(
+                f,
+                "golden file is missing, the image has been written to {} (set {} to create it)",
+                actual.display(),
+                UPDATE_GOLDEN
+            ),
+            GoldenError::Mismatch {
+                pixels,
+                actual,
+                diff,
+            } => write!(
+                f,
+                "{} pixels differ from the golden file, see {} and {}",
+                pixels,
+                actual.display(),
+                diff.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+impl From<io::Error> for GoldenError {
+    fn from(error: io::Error) -> Self {
+        GoldenError::Io(error)
+    }
+}
+
+impl From<ImageError<Infallible>> for GoldenError {
+    fn from(error: ImageError<Infallible>) -> Self {
+        GoldenError::Image(error)
+    }
+}
+
+// Color of the raw bits of a pixel as returned by `get_raw`
+fn raw_to_rgb<COLOR: ColorType>(raw: u16, inverted: bool, chromatic: Rgb888) -> Rgb888 {
+    if COLOR::BUFFER_COUNT == 2 && raw >> 8 != 0 {
+        chromatic
+    } else if COLOR::BITS_PER_PIXEL_PER_BUFFER == 1 {
+        if (raw & 1 != 0) != inverted {
+            Rgb888::WHITE
+        } else {
+            Rgb888::BLACK
+        }
+    } else {
+        // values which aren't colors stand out
+        COLOR::raw_rgb(raw).map_or(Rgb888::MAGENTA, |(r, g, b)| Rgb888::new(r, g, b))
+    }
+}
+
+fn luma(color: Rgb888) -> u32 {
+    (u32::from(color.r()) * 299 + u32::from(color.g()) * 587 + u32::from(color.b()) * 114) / 1000
+}
+
+// `dir/name.pbm` -> `dir/name.<suffix>`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(std::format!("{}.{}", stem, suffix))
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    writer.write_all(&crc.to_be_bytes())
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Color, OctColor, SixColor, TriColor};
+    use crate::graphics::Display;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(std::format!(
+            "epd-waveshare-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn colors() {
+        let mut display = Display::<8, 1, false, 2, TriColor, 0xFF>::default();
+        display.set_pixel(Pixel(Point::new(1, 0), TriColor::Black));
+        display.set_pixel(Pixel(Point::new(2, 0), TriColor::Chromatic));
+        let snapshot = Snapshot::from_display(&display);
+        assert_eq!(snapshot.pixel(0, 0), Some(Rgb888::WHITE));
+        assert_eq!(snapshot.pixel(1, 0), Some(Rgb888::BLACK));
+        assert_eq!(snapshot.pixel(2, 0), Some(Rgb888::RED));
+        assert_eq!(snapshot.pixel(8, 0), None);
+
+        let yellow = Snapshot::from_bitmap(&display.bitmap(), false, Rgb888::YELLOW);
+        assert_eq!(yellow.pixel(2, 0), Some(Rgb888::YELLOW));
+
+        let mut display =
+            Display::<8, 1, false, 2, TriColor, 0xFF, false, { TriColor::YELLOW_INK }>::default();
+        display.set_pixel(Pixel(Point::new(2, 0), TriColor::Chromatic));
+        let yellow = Snapshot::from_display(&display);
+        assert_eq!(yellow.pixel(2, 0), Some(Rgb888::YELLOW));
+
+        let mut display = Display::<2, 1, false, 1, OctColor>::default();
+        display.set_pixel(Pixel(Point::new(1, 0), OctColor::Orange));
+        let snapshot = Snapshot::from_display(&display);
+        assert_eq!(snapshot.pixel(0, 0), Some(Rgb888::BLACK));
+        assert_eq!(snapshot.pixel(1, 0), Some(Rgb888::new(0xFF, 0x80, 0)));
+
+        // same bit count, different palette
+        let mut display = Display::<2, 1, false, 1, SixColor, 0x11>::default();
+        display.set_pixel(Pixel(Point::new(1, 0), SixColor::Blue));
+        let snapshot = Snapshot::from_display(&display);
+        assert_eq!(snapshot.pixel(0, 0), Some(Rgb888::WHITE));
+        assert_eq!(snapshot.pixel(1, 0), Some(Rgb888::BLUE));
+
+        let display = Display::<8, 1, false, 1, Color, 0xFF, true>::default();
+        assert_eq!(
+            Snapshot::from_display(&display).pixel(0, 0),
+            Some(Rgb888::WHITE)
+        );
+    }
+
+    #[test]
+    fn pbm_ppm_round_trip() {
+        let mut display = Display::<16, 2, false, 8, TriColor, 0xFF>::default();
+        display.set_pixel(Pixel(Point::new(9, 1), TriColor::Black));
+        display.set_pixel(Pixel(Point::new(3, 0), TriColor::Chromatic));
+        let snapshot = Snapshot::from_display(&display);
+
+        let mut ppm = Vec::new();
+        snapshot.write_ppm(&mut ppm).unwrap();
+        let mut loaded = Snapshot::new(16, 2);
+        image::draw(ppm.as_slice(), &mut loaded, Point::zero(), Dither::None).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        let mut pbm = Vec::new();
+        snapshot.write_pbm(&mut pbm).unwrap();
+        assert_eq!(pbm, b"P4\n16 2\n\x10\x00\x00\x40");
+    }
+
+    #[test]
+    fn png() {
+        let mut snapshot = Snapshot::new(2, 1);
+        snapshot.pixels[1] = Rgb888::BLACK;
+        let mut png = Vec::new();
+        snapshot.write_png(&mut png).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[8..16], b"\x00\x00\x00\x0dIHDR");
+        assert_eq!(&png[16..29], [0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        // zlib header, a single final stored block with the filtered row, adler32
+        assert_eq!(&png[33..41], b"\x00\x00\x00\x12IDAT");
+        let idat = &png[41..59];
+        assert_eq!(&idat[..7], [0x78, 0x01, 1, 7, 0, 0xF8, 0xFF]);
+        assert_eq!(&idat[7..14], [0, 0xFF, 0xFF, 0xFF, 0, 0, 0]);
+        assert_eq!(idat[14..18], adler32(&idat[7..14]).to_be_bytes());
+        // well known CRC of the IEND chunk
+        assert_eq!(
+            &png[png.len() - 12..],
+            b"\x00\x00\x00\x00IEND\xAE\x42\x60\x82"
+        );
+    }
+
+    #[test]
+    fn golden() {
+        let dir = temp_dir("golden");
+        let golden = dir.join("screen.pbm");
+        let mut display = Display::<8, 2, false, 2, Color, 0xFF>::default();
+        display.set_pixel(Pixel(Point::new(1, 1), Color::Black));
+        let snapshot = Snapshot::from_display(&display);
+
+        match snapshot.check_golden(&golden) {
+            Err(GoldenError::Missing { actual }) => assert!(actual.ends_with("screen.actual.ppm")),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        snapshot.save(&golden).unwrap();
+        snapshot.check_golden(&golden).unwrap();
+
+        display.set_pixel(Pixel(Point::new(2, 0), Color::Black));
+        match Snapshot::from_display(&display).check_golden(&golden) {
+            Err(GoldenError::Mismatch { pixels, diff, .. }) => {
+                assert_eq!(pixels, 1);
+                assert!(diff.ends_with("screen.diff.png"));
+                assert!(diff.exists());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -16,6 +16,28 @@ pub enum RefreshLut {
     /// The quick LUT where not the full refresh sequence is followed.
     /// This might lead to some
     Quick,
+    /// A full refresh with the shorter waveform the controller uses for high temperatures
+    ///
+    /// Supported by epd1in54_v2, epd2in9_v2 and epd2in13_v2, the other devices use the full LUT.
+    Fast,
+}
+
+/// One refresh of the ghost clearing cycle of [`WaveshareDisplay::deep_clean`]
+#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+pub enum CleanStep<C> {
+    /// The image with all bits inverted, only meaningful for one bit per pixel and a single
+    /// plane, the chromatic plane of a three color buffer would be inverted too
+    Inverted,
+    /// The whole screen in one color
+    Fill(C),
+    /// The image itself
+    Image,
+}
+
+/// Ghost clearing cycle recommended for the panels of a color type
+pub trait DefaultCleanSequence: Sized + 'static {
+    /// Steps of [`WaveshareDisplay::deep_clean`], ending with the image
+    const CLEAN_SEQUENCE: &'static [CleanStep<Self>];
 }
 
 pub(crate) trait InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
@@ -82,6 +104,159 @@ where
     ) -> Result<(), SPI::Error>;
 }
 
+/// Windowed updates of the black/white and the chromatic data of three color panels
+///
+/// `x` and `width` have to be multiples of 8, the buffers only hold the window
+/// (`width / 8 * height` bytes, `(x, y)` is the top left corner).
+pub trait WaveshareThreeColorPartialDisplay<SPI, BUSY, DC, RST, DELAY>:
+    WaveshareThreeColorDisplay<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Update only the black/white data of a window
+    #[allow(clippy::too_many_arguments)]
+    fn update_partial_achromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        achromatic: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error>;
+
+    /// Update only the chromatic data of a window
+    ///
+    /// This data takes precedence over the black/white data.
+    #[allow(clippy::too_many_arguments)]
+    fn update_partial_chromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        chromatic: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error>;
+
+    /// Refresh the window
+    ///
+    /// Controllers without a windowed refresh refresh the whole screen.
+    fn display_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error>;
+}
+
+/// Mirror the image in hardware by reprogramming the scan direction of the controller
+///
+/// This costs nothing on the host side, but not every controller can mirror both axes at pixel
+/// granularity. Check [`HORIZONTAL`](HardwareMirror::HORIZONTAL) and
+/// [`VERTICAL`](HardwareMirror::VERTICAL), and use a
+/// [`DisplayTransform`](crate::graphics::DisplayTransform) for everything else.
+///
+/// The setting survives [`WaveshareDisplay::wake_up`].
+pub trait HardwareMirror<SPI, BUSY, DC, RST, DELAY>:
+    WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Whether the controller can swap left and right
+    const HORIZONTAL: bool;
+
+    /// Whether the controller can swap top and bottom
+    const VERTICAL: bool;
+
+    /// Mirror the output of the controller
+    ///
+    /// Axes that the controller can't mirror are ignored. Partial windows keep using frame
+    /// coordinates. Send the frame again afterwards, depending on the controller the data already
+    /// in its RAM might not be mirrored.
+    fn set_mirror(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        horizontal: bool,
+        vertical: bool,
+    ) -> Result<(), SPI::Error>;
+}
+
+/// Refresh with a waveform supplied by the host instead of the built-in ones
+///
+/// The waveform types live in [`lut`](crate::lut), one per controller family.
+pub trait CustomLut<SPI, BUSY, DC, RST, DELAY>:
+    WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Waveform type of the controller
+    type Lut;
+
+    /// Load `lut` for the following refreshes
+    ///
+    /// It stays active until a LUT of the crate is loaded again, i.e. by
+    /// [`WaveshareDisplay::set_lut`], [`WaveshareDisplay::wake_up`] or a quick refresh of
+    /// drivers that load their quick LUT on every update.
+    fn set_custom_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: &Self::Lut,
+    ) -> Result<(), SPI::Error>;
+}
+
+/// Tell the controller the temperature of the panel instead of measuring it
+///
+/// The waveform of a full refresh has to match the temperature of the panel. The controllers
+/// measure it with their internal sensor, which is off when the panel and the controller
+/// don't share the same place (or just the same heat).
+pub trait Temperature<SPI, BUSY, DC, RST, DELAY>:
+    WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Refresh for a panel at `celsius`, or with the internal sensor again for `None`
+    ///
+    /// Drivers that load the waveforms of this crate pick them by temperature band and switch to
+    /// the waveform the controller has for the temperature outside of their bands. Waveshare
+    /// only publishes waveforms for room temperature, so the only band is
+    /// [`HOST_LUT_CELSIUS`](crate::lut::HOST_LUT_CELSIUS). The other drivers pass the
+    /// temperature on to the controller, which picks the waveform of its OTP. Quick refreshes
+    /// keep their waveform. The temperature survives [`WaveshareDisplay::wake_up`].
+    fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        celsius: Option<i8>,
+    ) -> Result<(), SPI::Error>;
+
+    /// The temperature given to [`set_temperature`](Temperature::set_temperature)
+    fn temperature(&self) -> Option<i8>;
+}
+
 /// All the functions to interact with the EPDs
 ///
 /// This trait includes all public functions to use the EPDs
@@ -154,6 +329,10 @@ where
 {
     /// The Color Type used by the Display
     type DisplayColor;
+
+    /// Whether [`clear_frame`](WaveshareDisplay::clear_frame) also refreshes the screen
+    const CLEAR_FRAME_REFRESHES: bool = false;
+
     /// Creates a new driver from a SPI peripheral, CS Pin, Busy InputPin, DC
     ///
     /// `delay_us` is the number of us the idle loop should sleep on.
@@ -237,6 +416,51 @@ where
     /// The background color can be changed with [`WaveshareDisplay::set_background_color`]
     fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error>;
 
+    /// Clears the ghosting which many quick refreshes leave behind and a single full refresh
+    /// doesn't remove
+    ///
+    /// Every step is shown with a refresh, e.g. [`DefaultCleanSequence::CLEAN_SEQUENCE`] of the
+    /// color type. `buffer` is the image, it is only changed while it is shown inverted. The fills
+    /// go through [`clear_frame`](WaveshareDisplay::clear_frame) with the color of the step as
+    /// background color.
+    ///
+    /// The refreshes use the LUT currently loaded, drivers with quick refreshes have to be
+    /// switched to the full LUT first (see
+    /// [`RefreshScheduler::deep_clean`](crate::scheduler::RefreshScheduler::deep_clean)).
+    fn deep_clean(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &mut [u8],
+        steps: &[CleanStep<Self::DisplayColor>],
+    ) -> Result<(), SPI::Error>
+    where
+        Self::DisplayColor: Copy,
+    {
+        let background = *self.background_color();
+        for step in steps {
+            match *step {
+                CleanStep::Inverted => {
+                    buffer.iter_mut().for_each(|byte| *byte = !*byte);
+                    let result = self.update_and_display_frame(spi, buffer, delay);
+                    buffer.iter_mut().for_each(|byte| *byte = !*byte);
+                    result?;
+                }
+                CleanStep::Fill(color) => {
+                    self.set_background_color(color);
+                    let mut result = self.clear_frame(spi, delay);
+                    if result.is_ok() && !Self::CLEAR_FRAME_REFRESHES {
+                        result = self.display_frame(spi, delay);
+                    }
+                    self.set_background_color(background);
+                    result?;
+                }
+                CleanStep::Image => self.update_and_display_frame(spi, buffer, delay)?,
+            }
+        }
+        Ok(())
+    }
+
     /// Trait for using various Waveforms from different LUTs
     /// E.g. for partial refreshes
     ///
@@ -0,0 +1,50 @@
+//! SPI Commands for the Waveshare (G) E-Ink Displays with 2 bits per pixel
+
+use crate::traits;
+
+#[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Command {
+    PanelSetting = 0x00,
+    PowerSetting = 0x01,
+    PowerOff = 0x02,
+    PowerOn = 0x04,
+    BoosterSoftStart1 = 0x05,
+    BoosterSoftStart2 = 0x06,
+    DeepSleep = 0x07,
+    BoosterSoftStart3 = 0x08,
+
+    DataStartTransmission = 0x10,
+    DisplayRefresh = 0x12,
+
+    IPC = 0x13,
+
+    PllControl = 0x30,
+    TSE = 0x41,
+    VcomAndDataIntervalSetting = 0x50,
+    TconSetting = 0x60,
+    ResolutionSetting = 0x61,
+
+    Ox66 = 0x66,
+    Ox82 = 0x82,
+    Ox84 = 0x84,
+    AGID = 0x86,
+
+    PartialWindow = 0x90,
+    PartialIn = 0x91,
+    PartialOut = 0x92,
+
+    CMDH = 0xAA,
+    OxB0 = 0xB0,
+
+    CCSET = 0xE0,
+    PowerSaving = 0xE3,
+    TSSET = 0xE6,
+}
+
+impl traits::Command for Command {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
@@ -0,0 +1,170 @@
+//! Shared parts of the (G) displays with 2 bits per pixel, see [`QuadColor`]
+//!
+//! The panels only differ in their size and their init sequence, so the drivers wrap a
+//! [`QuadColorPanel`] of their size and send their own init sequence through it.
+
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+    spi::SpiDevice,
+};
+
+use crate::{color::QuadColor, interface::DisplayInterface, rect::Rect};
+
+use self::command::Command;
+
+pub(crate) mod command;
+
+/// Default mode of writing data (single byte vs blockwise)
+const SINGLE_BYTE_WRITE: bool = true;
+
+/// Connection and background color of a `WIDTH` x `HEIGHT` (G) display
+pub(crate) struct QuadColorPanel<SPI, BUSY, DC, RST, DELAY, const WIDTH: u32, const HEIGHT: u32> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    pub(crate) color: QuadColor,
+}
+
+impl<SPI, BUSY, DC, RST, DELAY, const WIDTH: u32, const HEIGHT: u32>
+    QuadColorPanel<SPI, BUSY, DC, RST, DELAY, WIDTH, HEIGHT>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    pub(crate) fn new(
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay_us: Option<u32>,
+        color: QuadColor,
+    ) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+        QuadColorPanel { interface, color }
+    }
+
+    /// Resets the panel and waits until it is ready for the init sequence
+    pub(crate) fn reset(&mut self, delay: &mut DELAY) {
+        self.interface.reset(delay, 20_000, 2_000);
+        self.wait_busy_low(delay);
+    }
+
+    pub(crate) fn command(&mut self, spi: &mut SPI, command: Command) -> Result<(), SPI::Error> {
+        self.interface.cmd(spi, command)
+    }
+
+    pub(crate) fn cmd_with_data(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+        data: &[u8],
+    ) -> Result<(), SPI::Error> {
+        self.interface.cmd_with_data(spi, command, data)
+    }
+
+    pub(crate) fn wait_busy_low(&mut self, delay: &mut DELAY) {
+        self.interface.wait_until_idle(delay, true);
+    }
+
+    pub(crate) fn set_resolution(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+        self.cmd_with_data(
+            spi,
+            Command::ResolutionSetting,
+            &[
+                (WIDTH >> 8) as u8,
+                WIDTH as u8,
+                (HEIGHT >> 8) as u8,
+                HEIGHT as u8,
+            ],
+        )
+    }
+
+    /// Powers the panel on at the end of the init sequence
+    pub(crate) fn power_on(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.command(spi, Command::PowerOn)?;
+        self.wait_busy_low(delay);
+        Ok(())
+    }
+
+    pub(crate) fn sleep(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+        self.cmd_with_data(spi, Command::DeepSleep, &[0xA5])
+    }
+
+    pub(crate) fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.wait_busy_low(delay);
+        self.cmd_with_data(spi, Command::DataStartTransmission, buffer)
+    }
+
+    pub(crate) fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        window: Rect,
+    ) -> Result<(), SPI::Error> {
+        self.wait_busy_low(delay);
+        self.command(spi, Command::PartialIn)?;
+        self.interface
+            .cmd_partial_window(spi, Command::PartialWindow, window, WIDTH > 256)?;
+        self.cmd_with_data(spi, Command::DataStartTransmission, buffer)?;
+        self.command(spi, Command::PartialOut)
+    }
+
+    pub(crate) fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.command(spi, Command::PowerOn)?;
+        self.wait_busy_low(delay);
+
+        self.cmd_with_data(spi, Command::DisplayRefresh, &[0x00])?;
+        self.wait_busy_low(delay);
+
+        self.cmd_with_data(spi, Command::PowerOff, &[0x00])?;
+        self.wait_busy_low(delay);
+
+        Ok(())
+    }
+
+    pub(crate) fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        let bg = QuadColor::colors_byte(self.color, self.color, self.color, self.color);
+
+        self.wait_busy_low(delay);
+        self.command(spi, Command::DataStartTransmission)?;
+        self.interface.data_x_times(spi, bg, WIDTH * HEIGHT / 4)?;
+
+        self.display_frame(spi, delay)
+    }
+
+    /// Show 4 stripes of color, used for quick testing
+    pub(crate) fn show_4stripes(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.wait_busy_low(delay);
+        self.command(spi, Command::DataStartTransmission)?;
+        for color in QuadColor::ALL {
+            self.interface.data_x_times(
+                spi,
+                QuadColor::colors_byte(color, color, color, color),
+                WIDTH / 4 * HEIGHT / 4,
+            )?;
+        }
+
+        self.display_frame(spi, delay)
+    }
+}